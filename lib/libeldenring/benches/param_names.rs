@@ -0,0 +1,92 @@
+//! Compares the compact `PARAM_NAMES` table to the JSON map it replaced.
+//!
+//! Run with `cargo bench -p libeldenring --bench param_names`.
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libeldenring::params::ParamNames;
+
+const DATA: &[u8] = include_bytes!("../src/codegen/param_names.bin");
+const PARAM: &str = "EquipParamGoods";
+
+type JsonNames = HashMap<String, HashMap<usize, String>>;
+
+// The JSON the map was deserialized from, rebuilt from the table.
+fn json() -> String {
+    let names = ParamNames::parse(DATA).unwrap();
+    let map = names
+        .keys()
+        .map(|param| (param, names.get(param).unwrap().iter().collect::<HashMap<_, _>>()))
+        .collect::<HashMap<_, _>>();
+    serde_json::to_string(&map).unwrap()
+}
+
+fn startup(c: &mut Criterion) {
+    let json = json();
+    let mut group = c.benchmark_group("startup");
+    group.bench_function("json", |b| {
+        b.iter(|| serde_json::from_str::<JsonNames>(black_box(&json)).unwrap())
+    });
+    // Up to the first lookup, which decodes the table of its param.
+    group.bench_function("compact", |b| {
+        b.iter(|| {
+            let names = ParamNames::parse(black_box(DATA)).unwrap();
+            names.get(PARAM).unwrap().get(&100).is_some()
+        })
+    });
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let json = serde_json::from_str::<JsonNames>(&json()).unwrap();
+    let names = ParamNames::parse(DATA).unwrap();
+    let ids = names.get(PARAM).unwrap().iter().map(|(id, _)| id).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("lookup");
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter(|id| json.get(PARAM).and_then(|names| names.get(id)).is_some())
+                .count()
+        })
+    });
+    group.bench_function("compact", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter(|id| names.get(PARAM).and_then(|names| names.get(id)).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+// One row of each param, as search and diff results go through all of them.
+fn lookup_all(c: &mut Criterion) {
+    let json = serde_json::from_str::<JsonNames>(&json()).unwrap();
+    let names = ParamNames::parse(DATA).unwrap();
+    let rows = names
+        .keys()
+        .filter_map(|param| Some((param, names.get(param)?.iter().next()?.0)))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("lookup_all");
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            rows.iter()
+                .filter(|(param, id)| json.get(*param).and_then(|names| names.get(id)).is_some())
+                .count()
+        })
+    });
+    group.bench_function("compact", |b| {
+        b.iter(|| {
+            rows.iter()
+                .filter(|(param, id)| names.get(param).and_then(|names| names.get(id)).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, startup, lookup, lookup_all);
+criterion_main!(benches);
@@ -0,0 +1,281 @@
+//! Runtime signature scanner. Finds the base addresses in the game's `.text`
+//! section when the game version is not one of the known ones, using the same
+//! patterns the `xtask codegen` step uses to generate `base_addresses.rs`,
+//! which are listed in `aob_table.rs`.
+
+use crate::codegen::base_addresses::BaseAddresses;
+
+/// How the address of interest is computed from the position of a match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AobKind {
+    /// The address is the start of the match itself.
+    Direct,
+    /// The match contains a RIP-relative instruction: the address is the
+    /// signed 32-bit displacement read at `offset_read` bytes into the match,
+    /// added to the address of the next instruction, which starts
+    /// `offset_instruction` bytes into the match. Like in the codegen, the
+    /// sum wraps around at 32 bits.
+    IndirectTwice { offset_read: usize, offset_instruction: usize },
+}
+
+/// A named set of alternative byte patterns. Bytes are written in hex and
+/// separated by whitespace; `??` matches any byte.
+#[derive(Clone, Copy, Debug)]
+pub struct Aob {
+    pub name: &'static str,
+    pub patterns: &'static [&'static str],
+    pub kind: AobKind,
+}
+
+const fn aob_direct(name: &'static str, patterns: &'static [&'static str]) -> Aob {
+    Aob { name, patterns, kind: AobKind::Direct }
+}
+
+const fn aob_indirect_twice(
+    name: &'static str,
+    patterns: &'static [&'static str],
+    offset_read: usize,
+    offset_instruction: usize,
+) -> Aob {
+    Aob { name, patterns, kind: AobKind::IndirectTwice { offset_read, offset_instruction } }
+}
+
+impl Aob {
+    /// Looks for the first pattern that matches in `text`, which is mapped at
+    /// the relative virtual address `text_rva`, and returns the RVA it
+    /// resolves to.
+    pub fn find(&self, text: &[u8], text_rva: usize) -> Option<usize> {
+        self.patterns.iter().find_map(|pattern| {
+            let offset = find_pattern(text, &parse_pattern(pattern)?)?;
+
+            match self.kind {
+                AobKind::Direct => Some(text_rva + offset),
+                AobKind::IndirectTwice { offset_read, offset_instruction } => {
+                    let read = offset + offset_read;
+                    let displacement =
+                        u32::from_le_bytes(text.get(read..read + 4)?.try_into().ok()?);
+                    let next_instruction = (text_rva + offset + offset_instruction) as u32;
+                    Some(next_instruction.wrapping_add(displacement) as usize)
+                },
+            }
+        })
+    }
+}
+
+/// Parses a pattern such as `"48 8B 0D ?? ?? ?? ??"`. Returns `None` if any of
+/// the bytes is malformed.
+pub fn parse_pattern(pattern: &str) -> Option<Vec<Option<u8>>> {
+    pattern
+        .split_whitespace()
+        .map(|byte| match byte {
+            "??" => Some(None),
+            byte => u8::from_str_radix(byte, 16).ok().map(Some),
+        })
+        .collect()
+}
+
+/// Returns the offset of the first occurrence of `pattern` in `haystack`.
+pub fn find_pattern(haystack: &[u8], pattern: &[Option<u8>]) -> Option<usize> {
+    if pattern.is_empty() {
+        return None;
+    }
+
+    haystack.windows(pattern.len()).position(|window| {
+        window.iter().zip(pattern).all(|(byte, expected)| expected.is_none_or(|e| e == *byte))
+    })
+}
+
+/// Finds the `.text` section in the headers of a PE image, and returns its
+/// relative virtual address and size.
+pub fn text_section(headers: &[u8]) -> Option<(usize, usize)> {
+    let read_u16 = |at: usize| Some(u16::from_le_bytes(headers.get(at..at + 2)?.try_into().ok()?));
+    let read_u32 = |at: usize| Some(u32::from_le_bytes(headers.get(at..at + 4)?.try_into().ok()?));
+
+    if headers.get(..2)? != b"MZ" {
+        return None;
+    }
+
+    let pe = read_u32(0x3c)? as usize;
+    if headers.get(pe..pe + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let section_count = read_u16(pe + 6)? as usize;
+    let optional_header_size = read_u16(pe + 20)? as usize;
+    let sections = pe + 24 + optional_header_size;
+
+    (0..section_count).map(|i| sections + i * 40).find_map(|section| {
+        if headers.get(section..section + 8)? != b".text\0\0\0" {
+            return None;
+        }
+
+        let size = read_u32(section + 8)? as usize;
+        let rva = read_u32(section + 12)? as usize;
+        Some((rva, size))
+    })
+}
+
+macro_rules! aob_table {
+    ($($field:ident: $aob:expr,)*) => {
+        /// All the signatures, in the same order as the fields of
+        /// [`BaseAddresses`].
+        pub const AOBS: &[Aob] = &[$($aob,)*];
+
+        /// Scans `text`, mapped at the relative virtual address `text_rva`,
+        /// for all the base addresses. The returned addresses are relative to
+        /// the module's base, like the generated ones. If any signature is not
+        /// found, returns the names of the missing ones.
+        pub fn scan_base_addresses(
+            text: &[u8],
+            text_rva: usize,
+        ) -> Result<BaseAddresses, Vec<&'static str>> {
+            let mut missing = Vec::new();
+            let mut aobs = AOBS.iter();
+
+            $(
+                let aob = aobs.next().unwrap();
+                let $field = aob.find(text, text_rva).unwrap_or_else(|| {
+                    missing.push(aob.name);
+                    0
+                });
+            )*
+
+            if missing.is_empty() {
+                Ok(BaseAddresses { $($field,)* })
+            } else {
+                Err(missing)
+            }
+        }
+
+        impl BaseAddresses {
+            /// Looks up an address by field name, e.g. `world_chr_man`, or
+            /// by signature name, e.g. `WorldChrMan`.
+            pub fn get(&self, name: &str) -> Option<usize> {
+                let mut aobs = AOBS.iter();
+
+                $(
+                    let aob = aobs.next().unwrap();
+                    if name == stringify!($field) || name == aob.name {
+                        return Some(self.$field);
+                    }
+                )*
+
+                None
+            }
+        }
+    };
+}
+
+include!("aob_table.rs");
+
+/// Scans the `.text` section of the module loaded at `module_base` in the
+/// current process.
+///
+/// # Safety
+///
+/// `module_base` must point to a PE image mapped in memory.
+#[cfg(windows)]
+pub unsafe fn scan_module(module_base: usize) -> Result<BaseAddresses, String> {
+    // The section table always fits in the first page.
+    let headers = std::slice::from_raw_parts(module_base as *const u8, 0x1000);
+    let (text_rva, text_size) =
+        text_section(headers).ok_or_else(|| "Could not find the .text section".to_string())?;
+    let text = std::slice::from_raw_parts((module_base + text_rva) as *const u8, text_size);
+
+    scan_base_addresses(text, text_rva)
+        .map_err(|missing| format!("Signatures not found: {}", missing.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(
+            parse_pattern("48 8b ?? 0D"),
+            Some(vec![Some(0x48), Some(0x8b), None, Some(0x0d)])
+        );
+        assert_eq!(parse_pattern("48 8G"), None);
+        assert!(AOBS.iter().flat_map(|aob| aob.patterns).all(|p| parse_pattern(p).is_some()));
+    }
+
+    #[test]
+    fn test_find() {
+        // mov rcx, [rip + 0x1000]; followed by the WorldChrMan signature tail.
+        let mut text = vec![0xccu8; 0x40];
+        let signature = [
+            0x48, 0x8B, 0x05, 0x00, 0x10, 0x00, 0x00, 0x48, 0x85, 0xC0, 0x74, 0x0F, 0x48, 0x39,
+            0x88,
+        ];
+        text[0x10..0x10 + signature.len()].copy_from_slice(&signature);
+
+        let world_chr_man = AOBS.iter().find(|aob| aob.name == "WorldChrMan").unwrap();
+        assert_eq!(world_chr_man.find(&text, 0x1000), Some(0x1000 + 0x10 + 7 + 0x1000));
+
+        // Negative displacements point before the instruction.
+        text[0x13..0x17].copy_from_slice(&(-0x20i32).to_le_bytes());
+        assert_eq!(world_chr_man.find(&text, 0x1000), Some(0x1000 + 0x10 + 7 - 0x20));
+
+        let lua_warp = AOBS.iter().find(|aob| aob.name == "LuaWarp").unwrap();
+        assert_eq!(lua_warp.find(&text, 0x1000), None);
+        let lua_warp_signature =
+            [0xC3, 0, 0, 0, 0, 0, 0, 0x57, 0x48, 0x83, 0xEC, 0x28, 0x48, 0x8B, 0xFA, 0x44];
+        text[0x20..0x20 + lua_warp_signature.len()].copy_from_slice(&lua_warp_signature);
+        assert_eq!(lua_warp.find(&text, 0x1000), Some(0x1020));
+    }
+
+    #[test]
+    fn test_scan_base_addresses() {
+        // Lay out the first pattern of every signature one after the other,
+        // with the wildcards zeroed. RIP-relative displacements are then all
+        // zero, so indirect addresses point to the next instruction.
+        let mut text = Vec::new();
+        let mut expected = std::collections::HashMap::new();
+        for aob in AOBS {
+            let offset = text.len();
+            text.extend(
+                parse_pattern(aob.patterns[0]).unwrap().into_iter().map(|b| b.unwrap_or(0)),
+            );
+            text.extend([0xcc; 16]);
+
+            expected.insert(aob.name, match aob.kind {
+                AobKind::Direct => 0x1000 + offset,
+                AobKind::IndirectTwice { offset_instruction, .. } => {
+                    0x1000 + offset + offset_instruction
+                },
+            });
+        }
+
+        let base_addresses = scan_base_addresses(&text, 0x1000).unwrap();
+        assert_eq!(base_addresses.bullet_man, expected["BulletMan"]);
+        assert_eq!(base_addresses.world_chr_man, expected["WorldChrMan"]);
+        assert_eq!(base_addresses.func_item_spawn, expected["FuncItemSpawn"]);
+        assert_eq!(base_addresses.event_patch1, expected["EventPatch1"]);
+        assert_eq!(base_addresses.font_patch, expected["FontPatch"]);
+
+        let missing = scan_base_addresses(&text[..text.len() / 2], 0x1000).unwrap_err();
+        assert!(missing.contains(&"FontPatch"));
+        assert!(!missing.contains(&"BulletMan"));
+    }
+
+    #[test]
+    fn test_text_section() {
+        let mut headers = vec![0u8; 0x400];
+        headers[..2].copy_from_slice(b"MZ");
+        headers[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        headers[0x80..0x84].copy_from_slice(b"PE\0\0");
+        headers[0x86..0x88].copy_from_slice(&2u16.to_le_bytes());
+        headers[0x94..0x96].copy_from_slice(&0xf0u16.to_le_bytes());
+
+        let sections = 0x80 + 24 + 0xf0;
+        headers[sections..sections + 8].copy_from_slice(b".rdata\0\0");
+        headers[sections + 40..sections + 48].copy_from_slice(b".text\0\0\0");
+        headers[sections + 48..sections + 52].copy_from_slice(&0x2345u32.to_le_bytes());
+        headers[sections + 52..sections + 56].copy_from_slice(&0x1000u32.to_le_bytes());
+
+        assert_eq!(text_section(&headers), Some((0x1000, 0x2345)));
+        assert_eq!(text_section(&headers[..0x100]), None);
+        assert_eq!(text_section(b"ELF"), None);
+    }
+}
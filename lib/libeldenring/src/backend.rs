@@ -0,0 +1,387 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+/// Abstracts the memory that pointer chains are evaluated against. Reads and
+/// writes are expected to fail gracefully (returning `None`) instead of
+/// crashing when the address range is not accessible.
+pub trait MemoryBackend: Clone + Debug {
+    /// Reads `buf.len()` bytes starting at `addr`.
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()>;
+
+    /// Writes the contents of `buf` starting at `addr`.
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()>;
+}
+
+/// Accesses the memory of the current process. This is the default backend,
+/// used when the library is running inside the game.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InProcess;
+
+#[cfg(windows)]
+mod win {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    use super::{InProcess, MemoryBackend};
+
+    pub(super) fn read(proc: HANDLE, addr: usize, buf: &mut [u8]) -> Option<()> {
+        unsafe { ReadProcessMemory(proc, addr as _, buf.as_mut_ptr() as _, buf.len(), None).ok() }
+    }
+
+    pub(super) fn write(proc: HANDLE, addr: usize, buf: &[u8]) -> Option<()> {
+        unsafe { WriteProcessMemory(proc, addr as _, buf.as_ptr() as _, buf.len(), None).ok() }
+    }
+
+    impl MemoryBackend for InProcess {
+        fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+            read(unsafe { GetCurrentProcess() }, addr, buf)
+        }
+
+        fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+            write(unsafe { GetCurrentProcess() }, addr, buf)
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod proc_self {
+    use std::fs::{File, OpenOptions};
+    use std::os::unix::fs::FileExt;
+    use std::sync::OnceLock;
+
+    use super::{InProcess, MemoryBackend};
+
+    // Going through /proc/self/mem instead of dereferencing makes unmapped
+    // addresses fail with an error instead of a segmentation fault.
+    fn proc_self_mem() -> Option<&'static File> {
+        static PROC_SELF_MEM: OnceLock<Option<File>> = OnceLock::new();
+        PROC_SELF_MEM
+            .get_or_init(|| OpenOptions::new().read(true).write(true).open("/proc/self/mem").ok())
+            .as_ref()
+    }
+
+    impl MemoryBackend for InProcess {
+        fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+            proc_self_mem()?.read_exact_at(buf, addr as u64).ok()
+        }
+
+        fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+            proc_self_mem()?.write_all_at(buf, addr as u64).ok()
+        }
+    }
+}
+
+/// Accesses the memory of another process through a handle opened with
+/// `PROCESS_VM_READ | PROCESS_VM_WRITE | PROCESS_VM_OPERATION` access rights.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug)]
+pub struct ForeignProcess(pub windows::Win32::Foundation::HANDLE);
+
+#[cfg(windows)]
+unsafe impl Send for ForeignProcess {}
+#[cfg(windows)]
+unsafe impl Sync for ForeignProcess {}
+
+#[cfg(windows)]
+impl MemoryBackend for ForeignProcess {
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+        win::read(self.0, addr, buf)
+    }
+
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+        win::write(self.0, addr, buf)
+    }
+}
+
+/// Accesses the memory of another process on Linux through `/proc/<pid>/mem`.
+/// This is what allows driving the game when it runs under Wine/Proton from a
+/// native process.
+///
+/// The caller needs ptrace access to the target process: either be its
+/// parent, or have `kernel.yama.ptrace_scope` set to 0, or hold
+/// `CAP_SYS_PTRACE`.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug)]
+pub struct ProcMem {
+    pid: u32,
+    mem: Arc<std::fs::File>,
+}
+
+#[cfg(target_os = "linux")]
+impl ProcMem {
+    /// Opens the memory of the process with the given pid for reading and
+    /// writing.
+    pub fn open(pid: u32) -> std::io::Result<Self> {
+        let mem =
+            std::fs::OpenOptions::new().read(true).write(true).open(format!("/proc/{pid}/mem"))?;
+        Ok(ProcMem { pid, mem: Arc::new(mem) })
+    }
+
+    /// Looks for a running process by name, matching either its `comm` or the
+    /// file name of its executable as it appears in its command line. The
+    /// latter is what identifies Windows executables running under Wine.
+    pub fn find_process(name: &str) -> std::io::Result<Option<u32>> {
+        for entry in std::fs::read_dir("/proc")? {
+            let Some(pid) = entry?.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+
+            let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).unwrap_or_default();
+            let cmdline = std::fs::read(format!("/proc/{pid}/cmdline")).unwrap_or_default();
+            let argv0 = cmdline.split(|&c| c == 0).next().unwrap_or_default();
+
+            if comm.trim_end() == name || file_name_matches(&String::from_utf8_lossy(argv0), name) {
+                return Ok(Some(pid));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Returns the lowest address at which the module with the given file
+    /// name is mapped, as listed in `/proc/<pid>/maps`. For PE images loaded
+    /// by Wine, this is the module's base address.
+    pub fn module_base(&self, module: &str) -> std::io::Result<Option<usize>> {
+        let maps = std::fs::read_to_string(format!("/proc/{}/maps", self.pid))?;
+        Ok(parse_module_base(&maps, module))
+    }
+
+    /// Resolves the base addresses of the given game version against the
+    /// `eldenring.exe` mapping of the process, ready to be passed to
+    /// [`Pointers::with_backend`](crate::pointers::Pointers::with_backend).
+    pub fn base_addresses(
+        &self,
+        version: crate::version::Version,
+    ) -> std::io::Result<crate::codegen::base_addresses::BaseAddresses> {
+        let base = self.module_base("eldenring.exe")?.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "eldenring.exe is not mapped")
+        })?;
+
+        Ok(crate::codegen::base_addresses::BaseAddresses::from(version).with_module_base_addr(base))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl MemoryBackend for ProcMem {
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+        std::os::unix::fs::FileExt::read_exact_at(&*self.mem, buf, addr as u64).ok()
+    }
+
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+        std::os::unix::fs::FileExt::write_all_at(&*self.mem, buf, addr as u64).ok()
+    }
+}
+
+// Matches the file name part of either a Unix or a Windows path.
+#[cfg(target_os = "linux")]
+fn file_name_matches(path: &str, name: &str) -> bool {
+    path.rsplit(['/', '\\']).next().map(|f| f.eq_ignore_ascii_case(name)).unwrap_or(false)
+}
+
+// Each line of /proc/<pid>/maps looks like
+// `start-end perms offset dev inode [path]`.
+#[cfg(target_os = "linux")]
+fn parse_module_base(maps: &str, module: &str) -> Option<usize> {
+    maps.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(6, ' ');
+            let range = fields.next()?;
+            let path = fields.nth(4)?.trim_start();
+            if !file_name_matches(path, module) {
+                return None;
+            }
+            usize::from_str_radix(range.split('-').next()?, 16).ok()
+        })
+        .min()
+}
+
+/// A synthetic, contiguous memory image starting at address `base`. Clones
+/// share the same underlying bytes, so writes through one pointer chain are
+/// visible from the others.
+///
+/// Any access falling even partially outside of `base..base + len` fails, as
+/// it would for unmapped memory in a real process.
+#[derive(Clone, Debug)]
+pub struct MemoryImage {
+    base: usize,
+    bytes: Arc<RwLock<Vec<u8>>>,
+}
+
+impl MemoryImage {
+    /// Creates a zero-filled image of `len` bytes mapped at `base`.
+    pub fn new(base: usize, len: usize) -> Self {
+        Self::from_bytes(base, vec![0u8; len])
+    }
+
+    /// Creates an image mapping `bytes` at `base`.
+    pub fn from_bytes(base: usize, bytes: Vec<u8>) -> Self {
+        MemoryImage { base, bytes: Arc::new(RwLock::new(bytes)) }
+    }
+
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.read().is_empty()
+    }
+
+    /// Returns a copy of the current contents of the image.
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.bytes.read().clone()
+    }
+
+    fn range(&self, addr: usize, len: usize) -> Option<std::ops::Range<usize>> {
+        let start = addr.checked_sub(self.base)?;
+        let end = start.checked_add(len)?;
+        (end <= self.len()).then_some(start..end)
+    }
+}
+
+impl MemoryBackend for MemoryImage {
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+        let range = self.range(addr, buf.len())?;
+        buf.copy_from_slice(&self.bytes.read()[range]);
+        Some(())
+    }
+
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+        let range = self.range(addr, buf.len())?;
+        self.bytes.write()[range].copy_from_slice(buf);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_bounds() {
+        let image = MemoryImage::new(0x1000, 0x10);
+        let mut buf = [0u8; 4];
+
+        assert!(image.read(0x1000, &mut buf).is_some());
+        assert!(image.read(0x100c, &mut buf).is_some());
+        assert!(image.read(0x100d, &mut buf).is_none());
+        assert!(image.read(0xfff, &mut buf).is_none());
+        assert!(image.write(usize::MAX - 1, &buf).is_none());
+    }
+
+    #[test]
+    fn test_image_shared_writes() {
+        let image = MemoryImage::new(0x1000, 0x10);
+        let other = image.clone();
+
+        image.write(0x1004, &[1, 2, 3, 4]).unwrap();
+
+        let mut buf = [0u8; 4];
+        other.read(0x1004, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(&other.snapshot()[4..8], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_in_process() {
+        let value = 0x1234_5678_u32;
+        let mut buf = [0u8; 4];
+
+        InProcess.read(&value as *const u32 as usize, &mut buf).unwrap();
+        assert_eq!(u32::from_ne_bytes(buf), value);
+        assert!(InProcess.read(0, &mut buf).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_module_base() {
+        let maps = "\
+            00010000-00011000 r--p 00000000 00:00 0 \n\
+            140001000-143d5e000 r-xp 00001000 00:1e 1234       /games/ELDEN RING/Game/eldenring.exe\n\
+            140000000-140001000 r--p 00000000 00:1e 1234       /games/ELDEN RING/Game/eldenring.exe\n\
+            7f0000000000-7f0000001000 r--p 00000000 00:1e 99   /usr/lib/libc.so.6\n";
+
+        assert_eq!(parse_module_base(maps, "eldenring.exe"), Some(0x140000000));
+        assert_eq!(parse_module_base(maps, "EldenRing.exe"), Some(0x140000000));
+        assert_eq!(parse_module_base(maps, "libc.so.6"), Some(0x7f0000000000));
+        assert_eq!(parse_module_base(maps, "start_protected_game.exe"), None);
+    }
+
+    // Not a test on its own: `test_proc_mem` runs the test binary again with
+    // this environment variable set, to get a child process whose memory
+    // layout is known.
+    #[cfg(target_os = "linux")]
+    const CHILD_ENV: &str = "LIBELDENRING_PROC_MEM_CHILD";
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn proc_mem_child() {
+        if std::env::var_os(CHILD_ENV).is_none() {
+            return;
+        }
+
+        let buffer = Box::new(*b"eldenring-procfs");
+        let root = Box::new(buffer.as_ptr() as usize);
+        println!("root={:x}", &*root as *const usize as usize);
+
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line).ok();
+        assert_eq!(&*buffer, b"eldenRING-procfs");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_proc_mem() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::process::{Command, Stdio};
+
+        use crate::memedit::PointerChain;
+        use crate::pointer_chain;
+
+        let exe = std::env::current_exe().unwrap();
+        let mut child = Command::new(&exe)
+            .args(["--exact", "backend::tests::proc_mem_child", "--nocapture", "--test-threads=1"])
+            .env(CHILD_ENV, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+        let root = stdout
+            .by_ref()
+            .map_while(Result::ok)
+            .find_map(|line| {
+                // The harness prints the test name on the same line.
+                line.split_once("root=").and_then(|(_, a)| usize::from_str_radix(a, 16).ok())
+            })
+            .unwrap();
+
+        let mem = ProcMem::open(child.id()).unwrap();
+
+        let module = exe.file_name().unwrap().to_str().unwrap();
+        let base = mem.module_base(module).unwrap().unwrap();
+        let mut magic = [0u8; 4];
+        mem.read(base, &mut magic).unwrap();
+        assert_eq!(&magic, b"\x7fELF");
+
+        let chain: PointerChain<[u8; 4], _> = pointer_chain!(mem => root, 5);
+        assert_eq!(chain.read(), Some(*b"ring"));
+        chain.write(*b"RING").unwrap();
+        assert_eq!(chain.read(), Some(*b"RING"));
+
+        child.stdin.take().unwrap().write_all(b"\n").unwrap();
+        stdout.for_each(drop);
+        assert!(child.wait().unwrap().success());
+    }
+}
@@ -272,6 +272,214 @@ pub static PARAM_VTABLE: Lazy<HashMap<String, BoxedVisitorLambda>> = Lazy::new(|
     .into_iter()
     .collect()
 });
+
+pub static PARAM_FIELDS: Lazy<HashMap<String, &'static [FieldMeta]>> = Lazy::new(|| {
+    [
+        ("ActionButtonParam".to_string(), ActionButtonParam::FIELDS),
+        ("AiAnimTblParam".to_string(), AiAnimTblParam::FIELDS),
+        ("AIAttackParam".to_string(), AIAttackParam::FIELDS),
+        ("AiOddsParam".to_string(), AiOddsParam::FIELDS),
+        ("AiSoundParam".to_string(), AiSoundParam::FIELDS),
+        ("AiStandardInfo".to_string(), AiStandardInfo::FIELDS),
+        ("AssetGeometryParam".to_string(), AssetGeometryParam::FIELDS),
+        ("AssetMaterialSfxParam".to_string(), AssetMaterialSfxParam::FIELDS),
+        ("AssetModelSfxParam".to_string(), AssetModelSfxParam::FIELDS),
+        ("AtkParam".to_string(), AtkParam::FIELDS),
+        ("AttackElementCorrectParam".to_string(), AttackElementCorrectParam::FIELDS),
+        ("AutoCreateEnvSoundParam".to_string(), AutoCreateEnvSoundParam::FIELDS),
+        ("BaseChrSelectMenuParam".to_string(), BaseChrSelectMenuParam::FIELDS),
+        ("BehaviorParam".to_string(), BehaviorParam::FIELDS),
+        ("BonfireWarpParam".to_string(), BonfireWarpParam::FIELDS),
+        ("BonfireWarpSubCategoryParam".to_string(), BonfireWarpSubCategoryParam::FIELDS),
+        ("BonfireWarpTabParam".to_string(), BonfireWarpTabParam::FIELDS),
+        ("BuddyParam".to_string(), BuddyParam::FIELDS),
+        ("BuddyStoneParam".to_string(), BuddyStoneParam::FIELDS),
+        ("BudgetParam".to_string(), BudgetParam::FIELDS),
+        ("BulletCreateLimitParam".to_string(), BulletCreateLimitParam::FIELDS),
+        ("BulletParam".to_string(), BulletParam::FIELDS),
+        ("CalcCorrectGraph".to_string(), CalcCorrectGraph::FIELDS),
+        ("CameraFadeParam".to_string(), CameraFadeParam::FIELDS),
+        ("CeremonyParam".to_string(), CeremonyParam::FIELDS),
+        ("CharaInitParam".to_string(), CharaInitParam::FIELDS),
+        ("CharMakeMenuListItemParam".to_string(), CharMakeMenuListItemParam::FIELDS),
+        ("CharMakeMenuTopParam".to_string(), CharMakeMenuTopParam::FIELDS),
+        ("ChrActivateConditionParam".to_string(), ChrActivateConditionParam::FIELDS),
+        ("ChrModelParam".to_string(), ChrModelParam::FIELDS),
+        ("ClearCountCorrectParam".to_string(), ClearCountCorrectParam::FIELDS),
+        ("CommonSystemParam".to_string(), CommonSystemParam::FIELDS),
+        ("CoolTimeParam".to_string(), CoolTimeParam::FIELDS),
+        ("CutsceneGparamTimeParam".to_string(), CutsceneGparamTimeParam::FIELDS),
+        ("CutsceneGparamWeatherParam".to_string(), CutsceneGparamWeatherParam::FIELDS),
+        ("CutsceneMapIdParam".to_string(), CutsceneMapIdParam::FIELDS),
+        ("CutSceneTextureLoadParam".to_string(), CutSceneTextureLoadParam::FIELDS),
+        ("CutsceneTimezoneConvertParam".to_string(), CutsceneTimezoneConvertParam::FIELDS),
+        (
+            "CutsceneWeatherOverrideGparamIdConvertParam".to_string(),
+            CutsceneWeatherOverrideGparamIdConvertParam::FIELDS,
+        ),
+        ("DecalParam".to_string(), DecalParam::FIELDS),
+        ("DirectionCameraParam".to_string(), DirectionCameraParam::FIELDS),
+        ("EnemyCommonParam".to_string(), EnemyCommonParam::FIELDS),
+        ("EnemyStandardInfo".to_string(), EnemyStandardInfo::FIELDS),
+        ("EnvObjLotParam".to_string(), EnvObjLotParam::FIELDS),
+        ("EquipMtrlSetParam".to_string(), EquipMtrlSetParam::FIELDS),
+        ("EquipParamAccessory".to_string(), EquipParamAccessory::FIELDS),
+        ("EquipParamCustomWeapon".to_string(), EquipParamCustomWeapon::FIELDS),
+        ("EquipParamGem".to_string(), EquipParamGem::FIELDS),
+        ("EquipParamGoods".to_string(), EquipParamGoods::FIELDS),
+        ("EquipParamProtector".to_string(), EquipParamProtector::FIELDS),
+        ("EquipParamWeapon".to_string(), EquipParamWeapon::FIELDS),
+        ("EstusFlaskRecoveryParam".to_string(), EstusFlaskRecoveryParam::FIELDS),
+        ("EventFlagUsageParam".to_string(), EventFlagUsageParam::FIELDS),
+        ("FaceParam".to_string(), FaceParam::FIELDS),
+        ("FaceRangeParam".to_string(), FaceRangeParam::FIELDS),
+        ("FeTextEffectParam".to_string(), FeTextEffectParam::FIELDS),
+        ("FootSfxParam".to_string(), FootSfxParam::FIELDS),
+        ("GameAreaParam".to_string(), GameAreaParam::FIELDS),
+        ("GameInfoParam".to_string(), GameInfoParam::FIELDS),
+        ("GameSystemCommonParam".to_string(), GameSystemCommonParam::FIELDS),
+        ("Gconfig_AAQuality".to_string(), Gconfig_AAQuality::FIELDS),
+        ("Gconfig_DecalQuality".to_string(), Gconfig_DecalQuality::FIELDS),
+        ("Gconfig_DOFQuality".to_string(), Gconfig_DOFQuality::FIELDS),
+        ("Gconfig_EffectQuality".to_string(), Gconfig_EffectQuality::FIELDS),
+        ("Gconfig_LightingQuality".to_string(), Gconfig_LightingQuality::FIELDS),
+        ("Gconfig_MotionBlurQuality".to_string(), Gconfig_MotionBlurQuality::FIELDS),
+        ("Gconfig_ReflectionQuality".to_string(), Gconfig_ReflectionQuality::FIELDS),
+        ("Gconfig_ShaderQuality".to_string(), Gconfig_ShaderQuality::FIELDS),
+        ("Gconfig_ShadowQuality".to_string(), Gconfig_ShadowQuality::FIELDS),
+        ("Gconfig_SSAOQuality".to_string(), Gconfig_SSAOQuality::FIELDS),
+        ("Gconfig_TextureFilterQuality".to_string(), Gconfig_TextureFilterQuality::FIELDS),
+        ("Gconfig_VolumetricEffectQuality".to_string(), Gconfig_VolumetricEffectQuality::FIELDS),
+        ("Gconfig_WaterQuality".to_string(), Gconfig_WaterQuality::FIELDS),
+        ("GestureParam".to_string(), GestureParam::FIELDS),
+        ("GparamGridRegionInfo".to_string(), GparamGridRegionInfo::FIELDS),
+        ("GparamRefSettingsParam".to_string(), GparamRefSettingsParam::FIELDS),
+        ("GraphicsCommonParam".to_string(), GraphicsCommonParam::FIELDS),
+        ("GraphicsConfig".to_string(), GraphicsConfig::FIELDS),
+        ("GrassLodRangeParam".to_string(), GrassLodRangeParam::FIELDS),
+        ("GrassMapSettings".to_string(), GrassMapSettings::FIELDS),
+        ("GrassTypeParam".to_string(), GrassTypeParam::FIELDS),
+        ("HitEffectSeParam".to_string(), HitEffectSeParam::FIELDS),
+        ("HitEffectSfxConceptParam".to_string(), HitEffectSfxConceptParam::FIELDS),
+        ("HitEffectSfxParam".to_string(), HitEffectSfxParam::FIELDS),
+        ("HitMtrlParam".to_string(), HitMtrlParam::FIELDS),
+        ("ItemLotParam".to_string(), ItemLotParam::FIELDS),
+        ("KeyAssignMenuItemParam".to_string(), KeyAssignMenuItemParam::FIELDS),
+        ("KeyAssignParam".to_string(), KeyAssignParam::FIELDS),
+        ("KnockBackParam".to_string(), KnockBackParam::FIELDS),
+        ("KnowledgeLoadScreenItemParam".to_string(), KnowledgeLoadScreenItemParam::FIELDS),
+        (
+            "LegacyDistantViewPartsReplaceParam".to_string(),
+            LegacyDistantViewPartsReplaceParam::FIELDS,
+        ),
+        ("LoadBalancerDrawDistScaleParam".to_string(), LoadBalancerDrawDistScaleParam::FIELDS),
+        (
+            "LoadBalancerNewDrawDistScaleParam".to_string(),
+            LoadBalancerNewDrawDistScaleParam::FIELDS,
+        ),
+        ("LoadBalancerParam".to_string(), LoadBalancerParam::FIELDS),
+        ("LockCamParam".to_string(), LockCamParam::FIELDS),
+        ("MagicParam".to_string(), MagicParam::FIELDS),
+        ("MapDefaultInfoParam".to_string(), MapDefaultInfoParam::FIELDS),
+        ("MapGdRegionDrawParam".to_string(), MapGdRegionDrawParam::FIELDS),
+        ("MapGdRegionInfo".to_string(), MapGdRegionInfo::FIELDS),
+        ("MapGridCreateHeightLimitInfo".to_string(), MapGridCreateHeightLimitInfo::FIELDS),
+        ("MapMimicryEstablishmentParam".to_string(), MapMimicryEstablishmentParam::FIELDS),
+        ("MapNameTexParam".to_string(), MapNameTexParam::FIELDS),
+        ("MapPieceTexParam".to_string(), MapPieceTexParam::FIELDS),
+        ("MaterialEx".to_string(), MaterialEx::FIELDS),
+        ("MenuCommonParam".to_string(), MenuCommonParam::FIELDS),
+        ("MenuOffscrRendParam".to_string(), MenuOffscrRendParam::FIELDS),
+        ("MenuParamColorTable".to_string(), MenuParamColorTable::FIELDS),
+        ("MenuPropertyLayoutParam".to_string(), MenuPropertyLayoutParam::FIELDS),
+        ("MenuPropertySpecParam".to_string(), MenuPropertySpecParam::FIELDS),
+        ("MenuValueTableSpecParam".to_string(), MenuValueTableSpecParam::FIELDS),
+        ("MimicryEstablishmentTexParam".to_string(), MimicryEstablishmentTexParam::FIELDS),
+        ("MissileParam".to_string(), MissileParam::FIELDS),
+        ("ModelSfxParam".to_string(), ModelSfxParam::FIELDS),
+        ("MoveParam".to_string(), MoveParam::FIELDS),
+        ("MultiEstusFlaskBonusParam".to_string(), MultiEstusFlaskBonusParam::FIELDS),
+        ("MultiPlayCorrectionParam".to_string(), MultiPlayCorrectionParam::FIELDS),
+        ("MultiSoulBonusRateParam".to_string(), MultiSoulBonusRateParam::FIELDS),
+        ("NetworkAreaParam".to_string(), NetworkAreaParam::FIELDS),
+        ("NetworkMsgParam".to_string(), NetworkMsgParam::FIELDS),
+        ("NetworkParam".to_string(), NetworkParam::FIELDS),
+        ("NpcAiActionParam".to_string(), NpcAiActionParam::FIELDS),
+        ("NpcAiBehaviorProbabilityParam".to_string(), NpcAiBehaviorProbabilityParam::FIELDS),
+        ("NpcParam".to_string(), NpcParam::FIELDS),
+        ("NpcThinkParam".to_string(), NpcThinkParam::FIELDS),
+        ("ObjActParam".to_string(), ObjActParam::FIELDS),
+        ("ObjectMaterialSfxParam".to_string(), ObjectMaterialSfxParam::FIELDS),
+        ("ObjectParam".to_string(), ObjectParam::FIELDS),
+        ("PartsDrawParam".to_string(), PartsDrawParam::FIELDS),
+        ("PerformanceCheckParam".to_string(), PerformanceCheckParam::FIELDS),
+        ("PhantomParam".to_string(), PhantomParam::FIELDS),
+        ("PlayerCommonParam".to_string(), PlayerCommonParam::FIELDS),
+        ("PlayRegionParam".to_string(), PlayRegionParam::FIELDS),
+        ("PostureControlParam_Gender".to_string(), PostureControlParam_Gender::FIELDS),
+        ("PostureControlParam_Pro".to_string(), PostureControlParam_Pro::FIELDS),
+        ("PostureControlParam_WepLeft".to_string(), PostureControlParam_WepLeft::FIELDS),
+        ("PostureControlParam_WepRight".to_string(), PostureControlParam_WepRight::FIELDS),
+        ("RandomAppearEditParam".to_string(), RandomAppearEditParam::FIELDS),
+        ("RandomAppearParam".to_string(), RandomAppearParam::FIELDS),
+        ("ReinforceParamProtector".to_string(), ReinforceParamProtector::FIELDS),
+        ("ReinforceParamWeapon".to_string(), ReinforceParamWeapon::FIELDS),
+        ("ResistCorrectParam".to_string(), ResistCorrectParam::FIELDS),
+        ("ReverbAuxSendBusParam".to_string(), ReverbAuxSendBusParam::FIELDS),
+        ("RideParam".to_string(), RideParam::FIELDS),
+        ("RoleParam".to_string(), RoleParam::FIELDS),
+        ("RollingObjLotParam".to_string(), RollingObjLotParam::FIELDS),
+        ("RuntimeBoneControlParam".to_string(), RuntimeBoneControlParam::FIELDS),
+        ("SeActivationRangeParam".to_string(), SeActivationRangeParam::FIELDS),
+        ("SeMaterialConvertParam".to_string(), SeMaterialConvertParam::FIELDS),
+        ("SfxBlockResShareParam".to_string(), SfxBlockResShareParam::FIELDS),
+        ("ShopLineupParam".to_string(), ShopLineupParam::FIELDS),
+        ("SignPuddleParam".to_string(), SignPuddleParam::FIELDS),
+        (
+            "SoundAssetSoundObjEnableDistParam".to_string(),
+            SoundAssetSoundObjEnableDistParam::FIELDS,
+        ),
+        ("SoundAutoEnvSoundGroupParam".to_string(), SoundAutoEnvSoundGroupParam::FIELDS),
+        (
+            "SoundAutoReverbEvaluationDistParam".to_string(),
+            SoundAutoReverbEvaluationDistParam::FIELDS,
+        ),
+        ("SoundAutoReverbSelectParam".to_string(), SoundAutoReverbSelectParam::FIELDS),
+        ("SoundChrPhysicsSeParam".to_string(), SoundChrPhysicsSeParam::FIELDS),
+        ("SoundCommonIngameParam".to_string(), SoundCommonIngameParam::FIELDS),
+        ("SoundCommonSystemParam".to_string(), SoundCommonSystemParam::FIELDS),
+        ("SoundCutsceneParam".to_string(), SoundCutsceneParam::FIELDS),
+        ("SpeedtreeModel".to_string(), SpeedtreeModel::FIELDS),
+        ("SpEffect".to_string(), SpEffect::FIELDS),
+        ("SpEffectSetParam".to_string(), SpEffectSetParam::FIELDS),
+        ("SpEffectVfx".to_string(), SpEffectVfx::FIELDS),
+        ("SwordArtsParam".to_string(), SwordArtsParam::FIELDS),
+        ("TalkParam".to_string(), TalkParam::FIELDS),
+        ("ThrowDirectionSfxParam".to_string(), ThrowDirectionSfxParam::FIELDS),
+        ("ThrowParam".to_string(), ThrowParam::FIELDS),
+        ("ToughnessParam".to_string(), ToughnessParam::FIELDS),
+        ("TutorialParam".to_string(), TutorialParam::FIELDS),
+        ("WaypointParam".to_string(), WaypointParam::FIELDS),
+        ("WeatherAssetCreateParam".to_string(), WeatherAssetCreateParam::FIELDS),
+        ("WeatherAssetReplaceParam".to_string(), WeatherAssetReplaceParam::FIELDS),
+        ("WeatherLotParam".to_string(), WeatherLotParam::FIELDS),
+        ("WeatherLotTexParam".to_string(), WeatherLotTexParam::FIELDS),
+        ("WeatherParam".to_string(), WeatherParam::FIELDS),
+        ("WepAbsorpPosParam".to_string(), WepAbsorpPosParam::FIELDS),
+        ("WetAspectParam".to_string(), WetAspectParam::FIELDS),
+        ("WhiteSignCoolTimeParam".to_string(), WhiteSignCoolTimeParam::FIELDS),
+        ("WorldMapLegacyConvParam".to_string(), WorldMapLegacyConvParam::FIELDS),
+        ("WorldMapPieceParam".to_string(), WorldMapPieceParam::FIELDS),
+        ("WorldMapPlaceNameParam".to_string(), WorldMapPlaceNameParam::FIELDS),
+        ("WorldMapPointParam".to_string(), WorldMapPointParam::FIELDS),
+        (
+            "WwiseValueToStrConvertParamFormat".to_string(),
+            WwiseValueToStrConvertParamFormat::FIELDS,
+        ),
+    ]
+    .into_iter()
+    .collect()
+});
 #[derive(ParamStruct, Debug)]
 #[repr(C)]
 pub struct ActionButtonParam {
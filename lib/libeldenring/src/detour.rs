@@ -0,0 +1,506 @@
+//! Inline hooks. A detour overwrites the first instructions of a function with
+//! a `jmp` to a code cave, which runs a payload, then the overwritten
+//! ("stolen") instructions, relocated to the cave, and finally jumps back.
+
+use std::ops::Range;
+
+use crate::backend::{InProcess, MemoryBackend};
+
+/// Encoders for the few instructions the detours need.
+pub mod asm {
+    /// `jmp rel32` from `from` to `to`, or `None` if `to` is out of range.
+    pub fn jmp_rel32(from: usize, to: usize) -> Option<[u8; 5]> {
+        let rel = rel32(from + 5, to)?;
+        let mut bytes = [0xe9, 0, 0, 0, 0];
+        bytes[1..].copy_from_slice(&rel.to_le_bytes());
+        Some(bytes)
+    }
+
+    /// `jmp [rip]` followed by the absolute address. Reaches anywhere.
+    pub fn jmp_abs(to: usize) -> [u8; 14] {
+        let mut bytes = [0xff, 0x25, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        bytes[6..].copy_from_slice(&(to as u64).to_le_bytes());
+        bytes
+    }
+
+    /// `movabs rax, imm64`.
+    pub fn movabs_rax(imm: u64) -> [u8; 10] {
+        let mut bytes = [0x48, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0];
+        bytes[2..].copy_from_slice(&imm.to_le_bytes());
+        bytes
+    }
+
+    /// `movabs [addr], rax`: stores `rax` at an absolute address.
+    pub fn movabs_store_rax(addr: usize) -> [u8; 10] {
+        let mut bytes = [0x48, 0xa3, 0, 0, 0, 0, 0, 0, 0, 0];
+        bytes[2..].copy_from_slice(&(addr as u64).to_le_bytes());
+        bytes
+    }
+
+    /// The displacement from the end of an instruction at `next` to `to`.
+    pub(super) fn rel32(next: usize, to: usize) -> Option<i32> {
+        i32::try_from(to as i64 - next as i64).ok()
+    }
+}
+
+// The only operands that need fixing up when an instruction is moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    None,
+    // `[rip + disp32]`, with the displacement at this offset.
+    RipRelative(usize),
+    // `call`/`jmp`/`jcc rel32`, with the displacement at this offset.
+    Rel32(usize),
+    // `jmp rel8`.
+    Rel8Jmp,
+    // `jcc rel8`, with the condition code.
+    Rel8Jcc(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Instruction {
+    len: usize,
+    operand: Operand,
+}
+
+// Decodes the length of the instruction at the start of `code`. Only covers
+// the general-purpose and common SSE instructions that show up in function
+// prologues; anything else is an error rather than a wrong guess.
+fn decode(code: &[u8]) -> Result<Instruction, String> {
+    let byte = |i: usize| code.get(i).copied().ok_or_else(|| "Truncated instruction".to_string());
+
+    let mut i = 0;
+    let mut operand_16 = false;
+    let mut address_32 = false;
+    loop {
+        match byte(i)? {
+            0x66 => operand_16 = true,
+            0x67 => address_32 = true,
+            0xf0 | 0xf2 | 0xf3 | 0x26 | 0x2e | 0x36 | 0x3e | 0x64 | 0x65 => {},
+            _ => break,
+        }
+        i += 1;
+    }
+
+    let mut rex_w = false;
+    if let rex @ 0x40..=0x4f = byte(i)? {
+        rex_w = rex & 0x8 != 0;
+        i += 1;
+    }
+
+    let imm_z = if operand_16 { 2 } else { 4 };
+    let opcode = byte(i)?;
+    i += 1;
+
+    // Whether there is a ModRM byte, and the size of the immediate.
+    let (has_modrm, imm) = match opcode {
+        0x0f => {
+            let opcode = byte(i)?;
+            i += 1;
+            match opcode {
+                0x80..=0x8f => return Ok(Instruction { len: i + 4, operand: Operand::Rel32(i) }),
+                0x38 => {
+                    i += 1;
+                    (true, 0)
+                },
+                0x3a => {
+                    i += 1;
+                    (true, 1)
+                },
+                0x05 | 0x0b | 0x31 | 0xa0 | 0xa1 | 0xa2 | 0xa8 | 0xa9 | 0xc8..=0xcf => (false, 0),
+                0x70..=0x73 | 0xa4 | 0xac | 0xba | 0xc2 | 0xc4..=0xc6 => (true, 1),
+                _ => (true, 0),
+            }
+        },
+        0x00..=0x3f => match opcode & 0x7 {
+            0..=3 => (true, 0),
+            4 => (false, 1),
+            5 => (false, imm_z),
+            _ => return Err(format!("Unsupported opcode {opcode:02x}")),
+        },
+        0x50..=0x5f
+        | 0x90..=0x99
+        | 0x9b..=0x9f
+        | 0xa4..=0xa7
+        | 0xaa..=0xaf
+        | 0xc3
+        | 0xc9
+        | 0xcc => (false, 0),
+        0x63 | 0x84..=0x8f | 0xd0..=0xd3 | 0xd8..=0xdf | 0xfe | 0xff => (true, 0),
+        0x68 | 0xa9 => (false, imm_z),
+        0x6a | 0xa8 | 0xb0..=0xb7 => (false, 1),
+        0x69 | 0x81 | 0xc7 => (true, imm_z),
+        0x6b | 0x80 | 0x83 | 0xc0 | 0xc1 | 0xc6 => (true, 1),
+        0x70..=0x7f => {
+            return Ok(Instruction { len: i + 1, operand: Operand::Rel8Jcc(opcode & 0xf) })
+        },
+        0xa0..=0xa3 => (false, if address_32 { 4 } else { 8 }),
+        0xb8..=0xbf => (false, if rex_w { 8 } else { imm_z }),
+        0xc2 => (false, 2),
+        0xc8 => (false, 3),
+        0xe8 | 0xe9 => return Ok(Instruction { len: i + 4, operand: Operand::Rel32(i) }),
+        0xeb => return Ok(Instruction { len: i + 1, operand: Operand::Rel8Jmp }),
+        0xf6 | 0xf7 => {
+            // `test r/m, imm` has an immediate, the other instructions in
+            // the group don't.
+            let reg = (byte(i)? >> 3) & 0x7;
+            let imm = if opcode == 0xf6 { 1 } else { imm_z };
+            (true, if reg < 2 { imm } else { 0 })
+        },
+        _ => return Err(format!("Unsupported opcode {opcode:02x}")),
+    };
+
+    let mut operand = Operand::None;
+    if has_modrm {
+        let modrm = byte(i)?;
+        i += 1;
+
+        let mode = modrm >> 6;
+        let rm = modrm & 0x7;
+        if mode != 3 {
+            if rm == 4 {
+                let sib = byte(i)?;
+                i += 1;
+                if mode == 0 && sib & 0x7 == 5 {
+                    i += 4;
+                }
+            } else if mode == 0 && rm == 5 {
+                operand = Operand::RipRelative(i);
+                i += 4;
+            }
+
+            match mode {
+                1 => i += 1,
+                2 => i += 4,
+                _ => {},
+            }
+        }
+    }
+
+    let len = i + imm;
+    if len > code.len() {
+        return Err("Truncated instruction".to_string());
+    }
+
+    Ok(Instruction { len, operand })
+}
+
+// Re-encodes an instruction decoded at `from` so that it can run at `to`.
+// Short branches become their `rel32` forms.
+fn relocate(
+    code: &[u8],
+    instruction: Instruction,
+    from: usize,
+    to: usize,
+    stolen: &Range<usize>,
+) -> Result<Vec<u8>, String> {
+    let Instruction { len, operand } = instruction;
+    let code = &code[..len];
+
+    let branch_target = |target: usize| {
+        if stolen.contains(&target) && target != stolen.start {
+            Err(format!("Branch at {from:x} jumps into the overwritten bytes"))
+        } else {
+            Ok(target)
+        }
+    };
+    let out_of_range = || format!("Instruction at {from:x} can't be relocated to {to:x}");
+
+    match operand {
+        Operand::None => Ok(code.to_vec()),
+        Operand::RipRelative(offset) | Operand::Rel32(offset) => {
+            let disp = i32::from_le_bytes(code[offset..offset + 4].try_into().unwrap());
+            let target = (from + len).wrapping_add_signed(disp as isize);
+            if let Operand::Rel32(_) = operand {
+                branch_target(target)?;
+            }
+
+            let disp = asm::rel32(to + len, target).ok_or_else(out_of_range)?;
+            let mut bytes = code.to_vec();
+            bytes[offset..offset + 4].copy_from_slice(&disp.to_le_bytes());
+            Ok(bytes)
+        },
+        Operand::Rel8Jmp => {
+            let target =
+                branch_target((from + len).wrapping_add_signed(code[len - 1] as i8 as isize))?;
+            Ok(asm::jmp_rel32(to, target).ok_or_else(out_of_range)?.to_vec())
+        },
+        Operand::Rel8Jcc(condition) => {
+            let target =
+                branch_target((from + len).wrapping_add_signed(code[len - 1] as i8 as isize))?;
+            let disp = asm::rel32(to + 6, target).ok_or_else(out_of_range)?;
+            let mut bytes = vec![0x0f, 0x80 | condition];
+            bytes.extend(disp.to_le_bytes());
+            Ok(bytes)
+        },
+    }
+}
+
+/// The bytes to write for a detour.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Assembled {
+    /// Written at the hooked address: a `jmp` to the cave, padded with `nop`s
+    /// to the end of the last stolen instruction.
+    pub patch: Vec<u8>,
+    /// Written in the code cave: the payload, the relocated stolen
+    /// instructions and a `jmp` back after the patch.
+    pub cave: Vec<u8>,
+}
+
+/// Assembles a detour from `target`, whose code starts with `code`, to a
+/// cave at `cave` which runs `payload`. The payload must leave the registers
+/// and flags as it found them, unless it means to change the hooked code's
+/// behavior.
+pub fn assemble(
+    target: usize,
+    code: &[u8],
+    cave: usize,
+    payload: &[u8],
+) -> Result<Assembled, String> {
+    let jmp = asm::jmp_rel32(target, cave)
+        .ok_or_else(|| format!("Code cave {cave:x} is out of range of {target:x}"))?;
+
+    let mut instructions = Vec::new();
+    let mut stolen_len = 0;
+    while stolen_len < jmp.len() {
+        let instruction = decode(&code[stolen_len..])?;
+        instructions.push((stolen_len, instruction));
+        stolen_len += instruction.len;
+    }
+
+    let stolen = target..target + stolen_len;
+    let mut cave_bytes = payload.to_vec();
+    for (offset, instruction) in instructions {
+        let relocated = relocate(
+            &code[offset..],
+            instruction,
+            target + offset,
+            cave + cave_bytes.len(),
+            &stolen,
+        )?;
+        cave_bytes.extend(relocated);
+    }
+
+    let jmp_back = asm::jmp_rel32(cave + cave_bytes.len(), stolen.end)
+        .ok_or_else(|| format!("Code cave {cave:x} is out of range of {target:x}"))?;
+    cave_bytes.extend(jmp_back);
+
+    let mut patch = jmp.to_vec();
+    patch.resize(stolen_len, 0x90);
+
+    Ok(Assembled { patch, cave: cave_bytes })
+}
+
+/// An installed detour. Dropping it restores the original code. The code cave
+/// is never freed, as a thread could still be running in it.
+#[derive(Debug)]
+pub struct Detour<B: MemoryBackend = InProcess> {
+    backend: B,
+    target: usize,
+    original: Vec<u8>,
+    patch: Vec<u8>,
+}
+
+#[cfg(windows)]
+impl Detour {
+    /// Hooks the code at `target` in the current process, allocating a code
+    /// cave near it.
+    ///
+    /// # Safety
+    ///
+    /// `target` must be the start of an instruction, and no thread must be
+    /// executing the first bytes of it while the hook is being written.
+    pub unsafe fn new(target: usize, payload: &[u8]) -> Result<Self, String> {
+        let cave = alloc_code_cave(target, 0x1000)
+            .ok_or_else(|| format!("Couldn't allocate a code cave near {target:x}"))?;
+        Self::with_backend(InProcess, target, cave, payload)
+    }
+}
+
+impl<B: MemoryBackend> Detour<B> {
+    /// Hooks the code at `target` using the code cave at `cave`, which must be
+    /// executable and within 2 GiB of `target`.
+    pub fn with_backend(
+        backend: B,
+        target: usize,
+        cave: usize,
+        payload: &[u8],
+    ) -> Result<Self, String> {
+        // Longer than any sequence of instructions that could be stolen.
+        let mut code = [0u8; 32];
+        backend
+            .read(target, &mut code)
+            .ok_or_else(|| format!("Couldn't read code at {target:x}"))?;
+
+        let Assembled { patch, cave: cave_bytes } = assemble(target, &code, cave, payload)?;
+
+        backend
+            .write(cave, &cave_bytes)
+            .ok_or_else(|| format!("Couldn't write code cave at {cave:x}"))?;
+        backend
+            .write(target, &patch)
+            .ok_or_else(|| format!("Couldn't write detour at {target:x}"))?;
+
+        Ok(Detour { backend, target, original: code[..patch.len()].to_vec(), patch })
+    }
+
+    pub fn target(&self) -> usize {
+        self.target
+    }
+}
+
+impl<B: MemoryBackend> Drop for Detour<B> {
+    fn drop(&mut self) {
+        // Leave the code alone if someone else has patched it in the meantime.
+        let mut current = vec![0u8; self.patch.len()];
+        if self.backend.read(self.target, &mut current).is_some() && current == self.patch {
+            self.backend.write(self.target, &self.original);
+        }
+    }
+}
+
+/// Allocates executable memory within `rel32` range of `near`.
+#[cfg(windows)]
+pub fn alloc_code_cave(near: usize, size: usize) -> Option<usize> {
+    use windows::Win32::System::Memory::{
+        VirtualAlloc, MEM_COMMIT, MEM_RESERVE, PAGE_EXECUTE_READWRITE,
+    };
+
+    // Allocations are aligned to 64 KiB.
+    const GRANULARITY: usize = 0x10000;
+    const RANGE: usize = 0x7fff0000;
+
+    let start = near & !(GRANULARITY - 1);
+    let up = (start..start.saturating_add(RANGE)).step_by(GRANULARITY);
+    let down = (start.saturating_sub(RANGE)..start).step_by(GRANULARITY).rev();
+
+    up.chain(down).find_map(|addr| {
+        let ptr = unsafe {
+            VirtualAlloc(
+                Some(addr as *const _),
+                size,
+                MEM_COMMIT | MEM_RESERVE,
+                PAGE_EXECUTE_READWRITE,
+            )
+        };
+        (!ptr.is_null()).then_some(ptr as usize)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryImage;
+
+    #[test]
+    fn test_asm() {
+        assert_eq!(asm::jmp_rel32(0x1000, 0x2000), Some([0xe9, 0xfb, 0x0f, 0, 0]));
+        assert_eq!(asm::jmp_rel32(0x2000, 0x1000), Some([0xe9, 0xfb, 0xef, 0xff, 0xff]));
+        assert_eq!(asm::jmp_rel32(0x1000, 0x1_0000_1000), None);
+        assert_eq!(asm::jmp_abs(0x7ff612345678), [
+            0xff, 0x25, 0, 0, 0, 0, 0x78, 0x56, 0x34, 0x12, 0xf6, 0x7f, 0, 0
+        ]);
+        assert_eq!(asm::movabs_rax(0x1122334455667788), [
+            0x48, 0xb8, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11
+        ]);
+        assert_eq!(asm::movabs_store_rax(0x7ff612345678), [
+            0x48, 0xa3, 0x78, 0x56, 0x34, 0x12, 0xf6, 0x7f, 0, 0
+        ]);
+    }
+
+    #[test]
+    fn test_decode() {
+        let cases: &[(&[u8], usize)] = &[
+            (&[0x48, 0x8b, 0x48, 0x08], 4),             // mov rcx, [rax+8]
+            (&[0x49, 0x89, 0x8d, 0x10, 0x02, 0, 0], 7), // mov [r13+0x210], rcx
+            (&[0x48, 0x89, 0x5c, 0x24, 0x10], 5),       // mov [rsp+10], rbx
+            (&[0x55], 1),                               // push rbp
+            (&[0x41, 0x57], 2),                         // push r15
+            (&[0x48, 0x81, 0xec, 0x50, 0x01, 0, 0], 7), // sub rsp, 0x150
+            (&[0x48, 0x83, 0xec, 0x28], 4),             // sub rsp, 0x28
+            (&[0x48, 0x8d, 0xac, 0x24, 0x70, 0xff, 0xff, 0xff], 8), // lea rbp, [rsp-0x90]
+            (&[0x48, 0xc7, 0x45, 0xc0, 0xfe, 0xff, 0xff, 0xff], 8), // mov qword [rbp-0x40], -2
+            (&[0x0f, 0x29, 0xb4, 0x24, 0, 1, 0, 0], 8), // movaps [rsp+0x100], xmm6
+            (&[0x66, 0x41, 0xc7, 0x00, 0x34, 0x12], 6), // mov word [r8], 0x1234
+            (&[0xf6, 0xc1, 0x01], 3),                   // test cl, 1
+            (&[0xf7, 0xd8], 2),                         // neg eax
+            (&[0x48, 0xb8, 1, 2, 3, 4, 5, 6, 7, 8], 10), // movabs rax, imm64
+            (&[0x80, 0x3d, 1, 2, 3, 4, 0], 7),          // cmp byte [rip+...], 0
+        ];
+
+        for (code, len) in cases {
+            assert_eq!(decode(code).unwrap().len, *len, "{code:02x?}");
+        }
+
+        assert_eq!(decode(&[0x80, 0x3d, 1, 2, 3, 4, 0]).unwrap().operand, Operand::RipRelative(2));
+        assert_eq!(decode(&[0xe8, 1, 2, 3, 4]).unwrap().operand, Operand::Rel32(1));
+        assert_eq!(decode(&[0x0f, 0x84, 1, 2, 3, 4]).unwrap().operand, Operand::Rel32(2));
+        assert_eq!(decode(&[0x74, 0x53]).unwrap().operand, Operand::Rel8Jcc(4));
+        assert!(decode(&[0x48, 0x8b]).is_err());
+        assert!(decode(&[0x0e]).is_err());
+    }
+
+    #[test]
+    fn test_assemble_relocation() {
+        // mov rax, [rip+0x100]
+        let code = [0x48, 0x8b, 0x05, 0x00, 0x01, 0x00, 0x00, 0xcc];
+        let assembled = assemble(0x10000, &code, 0x20000, &[]).unwrap();
+        assert_eq!(assembled.patch, vec![0xe9, 0xfb, 0xff, 0x00, 0x00, 0x90, 0x90]);
+        assert_eq!(assembled.cave, vec![
+            0x48, 0x8b, 0x05, 0x00, 0x01, 0xff, 0xff, // mov rax, [rip-0xff00]
+            0xe9, 0xfb, 0xff, 0xfe, 0xff, // jmp 0x10007
+        ]);
+
+        // call 0x10025
+        let code = [0xe8, 0x20, 0x00, 0x00, 0x00, 0xcc];
+        let assembled = assemble(0x10000, &code, 0x20000, &[0x90]).unwrap();
+        assert_eq!(assembled.patch, vec![0xe9, 0xfb, 0xff, 0x00, 0x00]);
+        assert_eq!(assembled.cave, vec![
+            0x90, // payload
+            0xe8, 0x1f, 0x00, 0xff, 0xff, // call 0x10025
+            0xe9, 0xfa, 0xff, 0xfe, 0xff, // jmp 0x10005
+        ]);
+
+        // nop; nop; je 0x10014; nop
+        let code = [0x90, 0x90, 0x74, 0x10, 0x90, 0xcc];
+        let assembled = assemble(0x10000, &code, 0x20000, &[]).unwrap();
+        assert_eq!(assembled.patch, vec![0xe9, 0xfb, 0xff, 0x00, 0x00]);
+        assert_eq!(assembled.cave, vec![
+            0x90, 0x90, // nop; nop
+            0x0f, 0x84, 0x0c, 0x00, 0xff, 0xff, // je 0x10014
+            0x90, // nop
+            0xe9, 0xf7, 0xff, 0xfe, 0xff, // jmp 0x10005
+        ]);
+
+        assert!(assemble(0x10000, &code, 0x1_0002_0000, &[]).is_err());
+        // A jump into the overwritten bytes can't work.
+        assert!(assemble(0x10000, &[0x90, 0xeb, 0xfe, 0x90, 0x90], 0x20000, &[]).is_err());
+    }
+
+    #[test]
+    fn test_detour() {
+        // The hooked code from the target entity info widget.
+        let target = 0x10000;
+        let cave = 0x10100;
+        let image = MemoryImage::new(target, 0x200);
+        let code = [0x48, 0x8b, 0x48, 0x08, 0x49, 0x89, 0x8d, 0x10, 0x02, 0x00, 0x00, 0x49, 0x8b];
+        image.write(target, &code).unwrap();
+
+        let payload = asm::movabs_store_rax(0x7ff612345678);
+        let detour = Detour::with_backend(image.clone(), target, cave, &payload).unwrap();
+
+        let bytes = image.snapshot();
+        assert_eq!(&bytes[..13], &[
+            0xe9, 0xfb, 0x00, 0x00, 0x00, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x49, 0x8b
+        ]);
+        assert_eq!(&bytes[0x100..0x100 + 26], &[
+            0x48, 0xa3, 0x78, 0x56, 0x34, 0x12, 0xf6, 0x7f, 0x00, 0x00, // movabs [..], rax
+            0x48, 0x8b, 0x48, 0x08, // mov rcx, [rax+8]
+            0x49, 0x89, 0x8d, 0x10, 0x02, 0x00, 0x00, // mov [r13+0x210], rcx
+            0xe9, 0xf1, 0xfe, 0xff, 0xff, // jmp 0x1000b
+        ]);
+
+        drop(detour);
+        assert_eq!(&image.snapshot()[..13], &code);
+    }
+}
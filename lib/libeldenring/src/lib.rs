@@ -1,19 +1,29 @@
 use std::thread;
 use std::time::Duration;
 
+pub mod aob;
+pub mod backend;
 pub mod codegen;
+pub mod detour;
 pub mod memedit;
+pub mod offsets;
 pub mod params;
+pub mod patch;
+pub mod pointer_expr;
 pub mod pointers;
 pub mod version;
 
 pub mod prelude {
+    pub use crate::backend::*;
     pub use crate::codegen::*;
     pub use crate::memedit::*;
     pub use crate::params::*;
+    pub use crate::pointer_expr::*;
     pub use crate::pointers::*;
     pub use crate::version::*;
-    pub use crate::{wait_option, wait_option_thread, ParamStruct, ParamVisitor};
+    pub use crate::{
+        string_match, wait_option, wait_option_thread, FieldMeta, ParamStruct, ParamVisitor,
+    };
 }
 
 /// Wait for an option value to be valid. Repeatedly calls the provided
@@ -57,14 +67,66 @@ pub trait ParamVisitor {
     fn visit_i16(&mut self, name: &str, v: &mut i16);
     fn visit_i32(&mut self, name: &str, v: &mut i32);
     fn visit_f32(&mut self, name: &str, v: &mut f32);
+    fn visit_u64(&mut self, name: &str, v: &mut u64);
+    fn visit_i64(&mut self, name: &str, v: &mut i64);
+    fn visit_f64(&mut self, name: &str, v: &mut f64);
     fn visit_bool(&mut self, name: &str, v: &mut bool);
+    /// Byte arrays, e.g. padding or fixed-size narrow strings.
+    fn visit_bytes(&mut self, name: &str, v: &mut [u8]);
+    /// Fixed-size UTF-16 strings, padded with NULs.
+    fn visit_wstr(&mut self, name: &str, v: &mut [u16]);
 }
 
 /// Implemented by generated code. Visits all the fields of a `ParamVisitor`.
 pub trait ParamStruct {
+    /// The fields in the order they are visited.
+    const FIELDS: &'static [FieldMeta];
+
     fn visit<T: ParamVisitor + ?Sized>(&mut self, t: &mut T);
 }
 
+/// Layout and Paramdex documentation of a visited field. Bit flags have one
+/// entry each, at the offset of the byte holding them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldMeta {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    pub ty: &'static str,
+    pub bit: Option<u8>,
+    pub display_name: Option<&'static str>,
+    pub description: Option<&'static str>,
+    pub enum_type: Option<&'static str>,
+    /// The params whose row ids the field holds.
+    pub refs: &'static [&'static str],
+}
+
+impl FieldMeta {
+    /// Finds the entry of a visited field. Array elements, which are visited
+    /// as `name[i]`, share the entry of their array.
+    pub fn find(fields: &'static [FieldMeta], name: &str) -> Option<&'static FieldMeta> {
+        let name = name.split_once('[').map(|(name, _)| name).unwrap_or(name);
+        fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// Case-insensitive subsequence match, used by the filter inputs: `"hsk"`
+/// matches `"Hoslow's Katana"`.
+pub fn string_match(needle: &str, haystack: &str) -> bool {
+    let needle = needle.chars().flat_map(char::to_lowercase);
+    let mut haystack = haystack.chars().flat_map(char::to_lowercase);
+
+    'o: for c in needle {
+        for d in &mut haystack {
+            if c == d {
+                continue 'o;
+            }
+        }
+        return false;
+    }
+    true
+}
+
 pub fn print_hex<T: Sized>(ptr: *const T) {
     let ptr = ptr as *const u8;
 
@@ -89,3 +151,17 @@ pub fn print_hex<T: Sized>(ptr: *const T) {
         println!();
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::string_match;
+
+    #[test]
+    fn test_string_match() {
+        assert!(string_match("", "SpEffectParam"));
+        assert!(string_match("speff", "SpEffectParam"));
+        assert!(string_match("SEP", "SpEffectParam"));
+        assert!(!string_match("pse", "SpEffectParam"));
+        assert!(!string_match("SpEffectParams", "SpEffectParam"));
+    }
+}
@@ -1,9 +1,7 @@
 use std::fmt::Debug;
 use std::ops::{BitAnd, BitOr, BitXor, Not};
 
-use windows::Win32::Foundation::HANDLE;
-use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
-use windows::Win32::System::Threading::GetCurrentProcess;
+pub use crate::backend::*;
 
 /// Wraps CheatEngine's concept of pointer with nested offsets. Evaluates,
 /// if the evaluation does not fail, to a mutable pointer of type `T`.
@@ -12,56 +10,57 @@ use windows::Win32::System::Threading::GetCurrentProcess;
 /// base pointer, then recursively reading the next memory address in the
 /// chain at an offset from there. For example,
 ///
-/// ```
+/// ```ignore
 /// PointerChain::<T>::new(&[a, b, c, d, e])
 /// ```
 ///
 /// evaluates to
 ///
-/// ```
+/// ```text
 /// *(*(*(*(*a + b) + c) + d) + e)
 /// ```
 ///
 /// This is useful for managing reverse engineered structures which are not
 /// fully known.
+///
+/// The memory is accessed through a [`MemoryBackend`], which defaults to the
+/// memory of the current process.
 #[derive(Clone, Debug)]
-pub struct PointerChain<T> {
-    proc: HANDLE,
+pub struct PointerChain<T, B: MemoryBackend = InProcess> {
+    backend: B,
     base: *mut T,
     offsets: Vec<usize>,
 }
-unsafe impl<T> Send for PointerChain<T> {}
-unsafe impl<T> Sync for PointerChain<T> {}
+unsafe impl<T, B: MemoryBackend + Send> Send for PointerChain<T, B> {}
+unsafe impl<T, B: MemoryBackend + Sync> Sync for PointerChain<T, B> {}
 
 impl<T> PointerChain<T> {
     /// Creates a new pointer chain given an array of addresses.
     pub fn new(chain: &[usize]) -> PointerChain<T> {
+        PointerChain::with_backend(InProcess, chain)
+    }
+}
+
+impl<T, B: MemoryBackend> PointerChain<T, B> {
+    /// Creates a new pointer chain given an array of addresses, evaluated
+    /// against the memory exposed by `backend`.
+    pub fn with_backend(backend: B, chain: &[usize]) -> PointerChain<T, B> {
         let mut it = chain.iter();
         let base = *it.next().unwrap() as *mut T;
         PointerChain {
-            proc: unsafe { GetCurrentProcess() },
+            backend,
             base,
             offsets: it.copied().collect(), // it.map(|x| *x).collect(),
         }
     }
 
     fn safe_read(&self, addr: usize, offs: usize) -> Option<usize> {
-        let mut value = 0usize;
-        unsafe {
-            ReadProcessMemory(
-                self.proc,
-                addr as _,
-                &mut value as *mut usize as _,
-                std::mem::size_of::<usize>(),
-                None,
-            )
-            .ok()
-            .map(|_| value + offs)
-        }
+        let mut value = [0u8; std::mem::size_of::<usize>()];
+        self.backend.read(addr, &mut value).map(|_| usize::from_ne_bytes(value) + offs)
     }
 
     /// Safely evaluates the pointer chain.
-    /// Relies on `ReadProcessMemory` instead of pointer dereferencing for crash
+    /// Relies on the backend instead of pointer dereferencing for crash
     /// safety.  Returns `None` if the evaluation failed.
     pub fn eval(&self) -> Option<*mut T> {
         self.offsets
@@ -75,43 +74,40 @@ impl<T> PointerChain<T> {
     pub fn read(&self) -> Option<T> {
         let ptr = self.eval()?;
         let mut value: T = unsafe { std::mem::zeroed() };
-        unsafe {
-            ReadProcessMemory(
-                self.proc,
-                ptr as _,
-                &mut value as *mut _ as _,
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(
+                &mut value as *mut T as *mut u8,
                 std::mem::size_of::<T>(),
-                None,
             )
-            .ok()
-            .map(|_| value)
-        }
+        };
+        self.backend.read(ptr as usize, buf).map(|_| value)
     }
 
     /// Evaluates the pointer chain and attempts to write the datum.
     /// Returns `None` if either the evaluation or the write failed.
-    pub fn write(&self, mut value: T) -> Option<()> {
+    pub fn write(&self, value: T) -> Option<()> {
         let ptr = self.eval()?;
-        unsafe {
-            WriteProcessMemory(
-                self.proc,
-                ptr as _,
-                &mut value as *mut _ as _,
-                std::mem::size_of::<T>(),
-                None,
-            )
-            .ok()
-            .map(|_| ())
+        let buf = unsafe {
+            std::slice::from_raw_parts(&value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.backend.write(ptr as usize, buf)
+    }
+
+    pub fn cast<S>(&self) -> PointerChain<S, B> {
+        PointerChain {
+            backend: self.backend.clone(),
+            base: self.base as *mut S,
+            offsets: self.offsets.clone(),
         }
     }
 
-    pub fn cast<S>(&self) -> PointerChain<S> {
-        PointerChain { proc: self.proc, base: self.base as *mut S, offsets: self.offsets.clone() }
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
 }
 
 #[derive(Clone, Debug)]
-pub struct Bitflag<T>(PointerChain<T>, T);
+pub struct Bitflag<T, B: MemoryBackend = InProcess>(PointerChain<T, B>, T);
 
 // impl<T: Display + Debug> Debug for PointerChain<T> {
 //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -129,7 +125,7 @@ pub struct Bitflag<T>(PointerChain<T>, T);
 //     }
 // }
 
-impl<T> Bitflag<T>
+impl<T, B: MemoryBackend> Bitflag<T, B>
 where
     T: BitXor<Output = T>
         + BitAnd<Output = T>
@@ -138,7 +134,7 @@ where
         + PartialEq
         + Copy,
 {
-    pub fn new(c: PointerChain<T>, mask: T) -> Self {
+    pub fn new(c: PointerChain<T, B>, mask: T) -> Self {
         Bitflag(c, mask)
     }
 
@@ -164,12 +160,99 @@ where
 
 #[macro_export]
 macro_rules! pointer_chain {
+    ($backend:expr => $($e:expr),+) => { PointerChain::with_backend($backend.clone(), &[$($e,)*]) };
     ($($e:expr),+) => { PointerChain::new(&[$($e,)*]) }
 }
 
 #[macro_export]
 macro_rules! bitflag {
+    ($b:expr; $backend:expr => $($e:expr),+) => {
+        Bitflag::new(PointerChain::with_backend($backend.clone(), &[$($e,)*]), $b)
+    };
     ($b:expr; $($e:expr),+) => { Bitflag::new(PointerChain::new(&[$($e,)*]), $b) }
 }
 
 pub use {bitflag, pointer_chain};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: usize = 0x1000;
+
+    // Builds the chain `*(*(BASE + 0x10) + 0x8) + 0x4` where the intermediate
+    // pointers live at 0x1010 -> 0x1100 and 0x1108 -> 0x1200.
+    fn image() -> MemoryImage {
+        let image = MemoryImage::new(BASE, 0x400);
+        image.write(BASE + 0x10, &0x1100usize.to_ne_bytes()).unwrap();
+        image.write(0x1108, &0x1200usize.to_ne_bytes()).unwrap();
+        image
+    }
+
+    #[test]
+    fn test_pointer_chain_eval() {
+        let image = image();
+        let chain: PointerChain<u32, _> = pointer_chain!(image => BASE + 0x10, 0x8, 0x4);
+
+        assert_eq!(chain.eval(), Some(0x1204 as *mut u32));
+        assert_eq!(chain.read(), Some(0));
+
+        chain.write(0xdeadbeef).unwrap();
+        assert_eq!(chain.read(), Some(0xdeadbeef));
+        assert_eq!(&image.snapshot()[0x204..0x208], &0xdeadbeefu32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_pointer_chain_invalid() {
+        let image = image();
+
+        // Null intermediate pointer.
+        let chain: PointerChain<u32, _> = pointer_chain!(image => BASE + 0x18, 0x8);
+        assert_eq!(chain.eval(), Some(0x8 as *mut u32));
+        assert_eq!(chain.read(), None);
+        assert_eq!(chain.write(1), None);
+
+        // Base outside of the image.
+        let chain: PointerChain<u32, _> = pointer_chain!(image => 0x10, 0x8);
+        assert_eq!(chain.eval(), None);
+
+        // Datum straddling the end of the image.
+        let chain: PointerChain<u64, _> = pointer_chain!(image => BASE + 0x3fc);
+        assert_eq!(chain.read(), None);
+    }
+
+    #[test]
+    fn test_pointer_chain_cast() {
+        let image = image();
+        let chain: PointerChain<u32, _> = pointer_chain!(image => BASE + 0x10, 0x8, 0x4);
+        chain.write(0x04030201).unwrap();
+
+        assert_eq!(chain.cast::<[u8; 4]>().read(), Some([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_bitflag() {
+        let image = image();
+        let flag: Bitflag<u8, _> = bitflag!(0b100; image => BASE + 0x20);
+        let other: Bitflag<u8, _> = bitflag!(0b1; image => BASE + 0x20);
+        let byte: PointerChain<u8, _> = pointer_chain!(image => BASE + 0x20);
+
+        byte.write(0b1010_0001).unwrap();
+        assert_eq!(flag.get(), Some(false));
+        assert_eq!(other.get(), Some(true));
+
+        flag.set(true);
+        assert_eq!(byte.read(), Some(0b1010_0101));
+        assert_eq!(flag.get(), Some(true));
+
+        assert_eq!(flag.toggle(), Some(false));
+        assert_eq!(byte.read(), Some(0b1010_0001));
+
+        other.set(false);
+        assert_eq!(byte.read(), Some(0b1010_0000));
+
+        let unmapped: Bitflag<u8, _> = bitflag!(0b1; image => 0x10);
+        assert_eq!(unmapped.get(), None);
+        assert_eq!(unmapped.toggle(), None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Structure offsets that change between game versions. They are read from the
+//! embedded `offsets.toml` table, optionally overridden by a user-provided
+//! file, so that supporting a new patch only requires editing data.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Deserialize;
+
+use crate::version::{Version, VERSIONS};
+
+static OFFSETS: Lazy<RwLock<OffsetsTable>> = Lazy::new(|| RwLock::new(OffsetsTable::embedded()));
+
+macro_rules! offsets {
+    ($($field:ident: $ty:ty,)*) => {
+        /// The structure offsets of a game version. See `offsets.toml` for the
+        /// meaning of each field.
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct Offsets {
+            $(pub $field: $ty,)*
+        }
+
+        // An entry of the offsets table, where any field can be inherited.
+        #[derive(Clone, Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Entry {
+            inherits: Option<String>,
+            $($field: Option<$ty>,)*
+        }
+
+        impl Entry {
+            // Fills in the fields of `self` that are missing with those of
+            // `base`.
+            fn or(self, base: &Entry) -> Entry {
+                Entry {
+                    inherits: self.inherits.or_else(|| base.inherits.clone()),
+                    $($field: self.$field.or_else(|| base.$field.clone()),)*
+                }
+            }
+
+            fn complete(self) -> Result<Offsets, Vec<&'static str>> {
+                let mut missing = Vec::new();
+
+                $(
+                    let $field = self.$field.unwrap_or_else(|| {
+                        missing.push(stringify!($field));
+                        Default::default()
+                    });
+                )*
+
+                if missing.is_empty() {
+                    Ok(Offsets { $($field,)* })
+                } else {
+                    Err(missing)
+                }
+            }
+        }
+    };
+}
+
+offsets! {
+    map_id: usize,
+    global_position: usize,
+    display_stable_pos: usize,
+    net_players_ins: usize,
+    player_ins: usize,
+    torrent_enemy_ins: usize,
+    group_mask_delta: isize,
+    show_geom: Vec<usize>,
+    show_chr: usize,
+    character_blessings: Vec<usize>,
+    func_dbg_action_force: (u8, u8),
+}
+
+/// The offsets of all the versions, keyed by version string (e.g. `2.06.0`).
+#[derive(Clone, Debug)]
+pub struct OffsetsTable(HashMap<String, Entry>);
+
+impl OffsetsTable {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        toml::from_str(s).map(OffsetsTable).map_err(|e| format!("{}", e))
+    }
+
+    /// The table shipped with the library.
+    pub fn embedded() -> Self {
+        Self::parse(include_str!("offsets.toml")).unwrap()
+    }
+
+    /// Merges `other` on top of this table. Fields set in `other` replace
+    /// those of the entry for the same version; new versions are added.
+    pub fn with_override(mut self, other: OffsetsTable) -> Self {
+        for (version, entry) in other.0 {
+            let entry = match self.0.get(&version) {
+                Some(base) => entry.or(base),
+                None => entry,
+            };
+            self.0.insert(version, entry);
+        }
+        self
+    }
+
+    /// Resolves the complete entry for the given version string, following
+    /// the `inherits` links.
+    pub fn get(&self, version: &str) -> Result<Offsets, String> {
+        let mut entry =
+            self.0.get(version).cloned().ok_or_else(|| format!("No offsets for {version}"))?;
+        let mut parent = entry.inherits.clone();
+        let mut depth = 0;
+
+        while let Some(name) = parent {
+            depth += 1;
+            if depth > self.0.len() {
+                return Err(format!("Offsets for {version} inherit in a loop"));
+            }
+
+            let base = self
+                .0
+                .get(&name)
+                .ok_or_else(|| format!("Offsets for {version} inherit unknown version {name}"))?;
+            entry = entry.or(base);
+            parent = base.inherits.clone();
+        }
+
+        entry
+            .complete()
+            .map_err(|missing| format!("Offsets for {version} are missing: {}", missing.join(", ")))
+    }
+
+    /// Checks that every known game version resolves to a complete entry.
+    pub fn validate(&self) -> Result<(), String> {
+        let errors = VERSIONS
+            .iter()
+            .filter_map(|&version| self.get(&version_key(version)).err())
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+pub fn version_key(version: Version) -> String {
+    let (maj, min, patch) = version.into();
+    format!("{maj}.{min:02}.{patch}")
+}
+
+/// Returns the offsets for the given version from the current table.
+pub fn get_offsets(version: Version) -> Offsets {
+    OFFSETS.read().get(&version_key(version)).expect("Offsets table was validated")
+}
+
+/// Reads the override file at `path` and merges it on top of the embedded
+/// table. The current table is kept if the file is invalid, or if the merged
+/// table is incomplete.
+pub fn load_override(path: &Path) -> Result<(), String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Couldn't read offsets file: {}", e))?;
+    let table = OffsetsTable::embedded().with_override(OffsetsTable::parse(&content)?);
+    table.validate()?;
+    *OFFSETS.write() = table;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_is_complete() {
+        OffsetsTable::embedded().validate().unwrap();
+    }
+
+    #[test]
+    fn test_inherits() {
+        let table = OffsetsTable::embedded();
+
+        let v1_05_0 = table.get("1.05.0").unwrap();
+        assert_eq!(v1_05_0.group_mask_delta, -8);
+        assert_eq!(v1_05_0.show_chr, 0x4);
+        assert_eq!(v1_05_0.map_id, 0x6c0);
+
+        let v1_06_0 = table.get("1.06.0").unwrap();
+        assert_eq!(v1_06_0.group_mask_delta, 0);
+        assert_eq!(v1_06_0.show_chr, 0xe);
+        assert_eq!(v1_06_0.torrent_enemy_ins, 0x18378);
+
+        let v2_06_0 = get_offsets(Version::V2_06_0);
+        assert_eq!(v2_06_0.player_ins, 0x1e508);
+        assert_eq!(v2_06_0.character_blessings, vec![0x8, 0xfc]);
+        assert_eq!(v2_06_0.func_dbg_action_force, (0xc1, 0xc2));
+    }
+
+    #[test]
+    fn test_override() {
+        let table = OffsetsTable::embedded().with_override(
+            OffsetsTable::parse(
+                r#"
+                ["2.06.0"]
+                player_ins = 0x1234
+
+                ["2.07.0"]
+                inherits = "2.06.0"
+                map_id = 0x6e0
+                "#,
+            )
+            .unwrap(),
+        );
+
+        table.validate().unwrap();
+        assert_eq!(table.get("2.06.0").unwrap().player_ins, 0x1234);
+        assert_eq!(table.get("2.06.0").unwrap().map_id, 0x6d0);
+        assert_eq!(table.get("2.07.0").unwrap().player_ins, 0x1234);
+        assert_eq!(table.get("2.07.0").unwrap().map_id, 0x6e0);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let table = OffsetsTable::parse(
+            r#"
+            ["1.02.0"]
+            map_id = 0x6c8
+
+            ["1.02.1"]
+            inherits = "1.02.2"
+
+            ["1.02.2"]
+            inherits = "1.02.1"
+            "#,
+        )
+        .unwrap();
+
+        assert!(table.get("1.02.0").unwrap_err().contains("global_position"));
+        assert!(table.get("1.02.1").unwrap_err().contains("loop"));
+        assert!(table.get("2.06.0").is_err());
+        assert!(table.validate().is_err());
+
+        assert!(OffsetsTable::parse("[\"1.02.0\"]\nplayer = 1").is_err());
+    }
+}
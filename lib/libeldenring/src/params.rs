@@ -1,20 +1,45 @@
-use std::collections::{BTreeMap, HashMap};
+// Param discovery in the game's memory is only available on Windows.
+#![cfg_attr(not(windows), allow(dead_code))]
+
+use std::collections::BTreeMap;
 use std::ffi::c_void;
+#[cfg(windows)]
 use std::time::Duration;
+#[cfg(windows)]
 use std::{mem, thread};
 
 use log::*;
 use once_cell::sync::Lazy;
+#[cfg(windows)]
 use parking_lot::RwLock;
 use widestring::U16CStr;
+#[cfg(windows)]
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+#[cfg(windows)]
 use windows::Win32::System::Memory::{VirtualQuery, MEMORY_BASIC_INFORMATION, PAGE_READWRITE};
 
+pub use self::export::*;
+pub use self::history::*;
+pub use self::names::*;
+pub use self::patches::*;
+pub use self::regulation::*;
+pub use self::search::*;
 pub use crate::codegen::param_data::*;
-use crate::prelude::base_addresses::BaseAddresses;
 use crate::prelude::*;
+#[cfg(windows)]
 use crate::{pointer_chain, version};
 
+mod bytes;
+mod export;
+#[cfg(test)]
+mod fake;
+mod history;
+mod names;
+mod patches;
+mod regulation;
+mod search;
+
+#[cfg(windows)]
 pub static PARAMS: Lazy<RwLock<Params>> = Lazy::new(|| unsafe {
     let mut params = Params::new();
     wait_option(|| match params.refresh() {
@@ -27,8 +52,8 @@ pub static PARAMS: Lazy<RwLock<Params>> = Lazy::new(|| unsafe {
     RwLock::new(params)
 });
 
-pub static PARAM_NAMES: Lazy<HashMap<String, HashMap<usize, String>>> =
-    Lazy::new(|| serde_json::from_str(include_str!("codegen/param_names.json")).unwrap());
+pub static PARAM_NAMES: Lazy<ParamNames> =
+    Lazy::new(|| ParamNames::parse(include_bytes!("codegen/param_names.bin")).unwrap());
 
 #[derive(Debug)]
 #[repr(C)]
@@ -102,8 +127,9 @@ impl Params {
     ///
     /// Accesses raw pointers. Should never crash as the param pointers are
     /// static.
+    #[cfg(windows)]
     pub unsafe fn refresh(&mut self) -> Result<(), String> {
-        let addresses: BaseAddresses = version::get_version().into();
+        let addresses = version::get_base_addresses();
         let mut memory_basic_info = MEMORY_BASIC_INFORMATION::default();
 
         let module_base_addr = GetModuleHandleA(None).unwrap().0 as usize;
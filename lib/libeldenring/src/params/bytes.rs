@@ -0,0 +1,44 @@
+//! Bounds-checked readers for the binary formats of the game files.
+
+pub(super) fn read_bytes(data: &[u8], offset: usize, len: usize) -> Result<&[u8], String> {
+    offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| format!("Unexpected end of data at {offset:#x}"))
+}
+
+pub(super) fn read<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], String> {
+    read_bytes(data, offset, N).map(|b| b.try_into().unwrap())
+}
+
+pub(super) fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    read(data, offset).map(u16::from_le_bytes)
+}
+
+pub(super) fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    read(data, offset).map(u32::from_le_bytes)
+}
+
+pub(super) fn read_u64(data: &[u8], offset: usize) -> Result<u64, String> {
+    read(data, offset).map(u64::from_le_bytes)
+}
+
+pub(super) fn read_u32_be(data: &[u8], offset: usize) -> Result<u32, String> {
+    read(data, offset).map(u32::from_be_bytes)
+}
+
+pub(super) fn read_cstr(data: &[u8], offset: usize) -> Result<String, String> {
+    let s = data.get(offset..).ok_or_else(|| format!("Unexpected end of data at {offset:#x}"))?;
+    let len = s.iter().position(|&b| b == 0).unwrap_or(s.len());
+    Ok(String::from_utf8_lossy(&s[..len]).into_owned())
+}
+
+pub(super) fn read_wstr(data: &[u8], offset: usize) -> Result<String, String> {
+    let s = data.get(offset..).ok_or_else(|| format!("Unexpected end of data at {offset:#x}"))?;
+    let s = s
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&c| c != 0)
+        .collect::<Vec<_>>();
+    Ok(String::from_utf16_lossy(&s))
+}
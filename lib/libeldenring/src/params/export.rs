@@ -0,0 +1,494 @@
+//! Dumps params to CSV or JSON by visiting every field of every row.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::path::Path;
+
+use serde::{Serialize, Serializer};
+
+use super::{Params, PARAM_NAMES, PARAM_VTABLE};
+use crate::ParamVisitor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn format(&self, rows: &[ExportedRow]) -> String {
+        match self {
+            ExportFormat::Csv => to_csv(rows),
+            ExportFormat::Json => to_json(rows),
+        }
+    }
+}
+
+/// The value of a single field, as visited.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    F32(f32),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Bytes(#[serde(serialize_with = "serialize_hex")] Vec<u8>),
+    WStr(String),
+}
+
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&to_hex(bytes))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+        write!(s, "{b:02x}").ok();
+        s
+    })
+}
+
+/// Parses the output of `to_hex`, ignoring whitespace.
+pub fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = s.chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(&pair.iter().collect::<String>(), 16).ok())
+        .collect()
+}
+
+/// Decodes a NUL-padded UTF-16 string.
+pub fn decode_wstr(s: &[u16]) -> String {
+    let len = s.iter().position(|&c| c == 0).unwrap_or(s.len());
+    String::from_utf16_lossy(&s[..len])
+}
+
+/// Encodes a string into a NUL-padded UTF-16 buffer of length `len`, if it
+/// fits.
+pub fn encode_wstr(s: &str, len: usize) -> Option<Vec<u16>> {
+    let mut buf = s.encode_utf16().collect::<Vec<_>>();
+    if buf.len() > len {
+        return None;
+    }
+    buf.resize(len, 0);
+    Some(buf)
+}
+
+// Floats are compared bitwise, so that a NaN field is equal to itself and
+// isn't seen as changed.
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FieldValue::F32(a), FieldValue::F32(b)) => a.to_bits() == b.to_bits(),
+            (FieldValue::U8(a), FieldValue::U8(b)) => a == b,
+            (FieldValue::U16(a), FieldValue::U16(b)) => a == b,
+            (FieldValue::U32(a), FieldValue::U32(b)) => a == b,
+            (FieldValue::I8(a), FieldValue::I8(b)) => a == b,
+            (FieldValue::I16(a), FieldValue::I16(b)) => a == b,
+            (FieldValue::I32(a), FieldValue::I32(b)) => a == b,
+            (FieldValue::F64(a), FieldValue::F64(b)) => a.to_bits() == b.to_bits(),
+            (FieldValue::U64(a), FieldValue::U64(b)) => a == b,
+            (FieldValue::I64(a), FieldValue::I64(b)) => a == b,
+            (FieldValue::Bool(a), FieldValue::Bool(b)) => a == b,
+            (FieldValue::Bytes(a), FieldValue::Bytes(b)) => a == b,
+            (FieldValue::WStr(a), FieldValue::WStr(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::U8(v) => write!(f, "{v}"),
+            FieldValue::U16(v) => write!(f, "{v}"),
+            FieldValue::U32(v) => write!(f, "{v}"),
+            FieldValue::I8(v) => write!(f, "{v}"),
+            FieldValue::I16(v) => write!(f, "{v}"),
+            FieldValue::I32(v) => write!(f, "{v}"),
+            // Shortest representation that parses back to the same value.
+            FieldValue::F32(v) => write!(f, "{v}"),
+            FieldValue::U64(v) => write!(f, "{v}"),
+            FieldValue::I64(v) => write!(f, "{v}"),
+            FieldValue::F64(v) => write!(f, "{v}"),
+            FieldValue::Bool(v) => write!(f, "{v}"),
+            FieldValue::Bytes(v) => write!(f, "{}", to_hex(v)),
+            FieldValue::WStr(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// Records the name and value of every visited field, in order.
+#[derive(Debug, Default)]
+pub struct FieldCollector(pub Vec<(String, FieldValue)>);
+
+impl ParamVisitor for FieldCollector {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.0.push((name.to_string(), FieldValue::U8(*v)));
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.0.push((name.to_string(), FieldValue::U16(*v)));
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.0.push((name.to_string(), FieldValue::U32(*v)));
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.0.push((name.to_string(), FieldValue::I8(*v)));
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.0.push((name.to_string(), FieldValue::I16(*v)));
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.0.push((name.to_string(), FieldValue::I32(*v)));
+    }
+
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        self.0.push((name.to_string(), FieldValue::F32(*v)));
+    }
+
+    fn visit_u64(&mut self, name: &str, v: &mut u64) {
+        self.0.push((name.to_string(), FieldValue::U64(*v)));
+    }
+
+    fn visit_i64(&mut self, name: &str, v: &mut i64) {
+        self.0.push((name.to_string(), FieldValue::I64(*v)));
+    }
+
+    fn visit_f64(&mut self, name: &str, v: &mut f64) {
+        self.0.push((name.to_string(), FieldValue::F64(*v)));
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        self.0.push((name.to_string(), FieldValue::Bool(*v)));
+    }
+
+    fn visit_bytes(&mut self, name: &str, v: &mut [u8]) {
+        self.0.push((name.to_string(), FieldValue::Bytes(v.to_vec())));
+    }
+
+    fn visit_wstr(&mut self, name: &str, v: &mut [u16]) {
+        self.0.push((name.to_string(), FieldValue::WStr(decode_wstr(v))));
+    }
+}
+
+/// Sets fields from their textual representation, e.g. as exported. Fields
+/// without a value are left alone, and values that can't be parsed are
+/// recorded in `errors`.
+#[derive(Debug, Default)]
+pub struct FieldSetter<'a> {
+    values: HashMap<&'a str, &'a str>,
+    pub errors: Vec<String>,
+}
+
+impl<'a> FieldSetter<'a> {
+    pub fn new(values: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        FieldSetter { values: values.into_iter().collect(), errors: Vec::new() }
+    }
+
+    fn set<T: std::str::FromStr>(&mut self, name: &str, v: &mut T) {
+        if let Some(value) = self.values.get(name) {
+            match value.trim().parse() {
+                Ok(value) => *v = value,
+                Err(_) => self.errors.push(format!("Invalid value for {name}: {value}")),
+            }
+        }
+    }
+}
+
+impl ParamVisitor for FieldSetter<'_> {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.set(name, v);
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.set(name, v);
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.set(name, v);
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.set(name, v);
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.set(name, v);
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.set(name, v);
+    }
+
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        self.set(name, v);
+    }
+
+    fn visit_u64(&mut self, name: &str, v: &mut u64) {
+        self.set(name, v);
+    }
+
+    fn visit_i64(&mut self, name: &str, v: &mut i64) {
+        self.set(name, v);
+    }
+
+    fn visit_f64(&mut self, name: &str, v: &mut f64) {
+        self.set(name, v);
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        match self.values.get(name).map(|value| value.trim()) {
+            Some("1") => *v = true,
+            Some("0") => *v = false,
+            _ => self.set(name, v),
+        }
+    }
+
+    fn visit_bytes(&mut self, name: &str, v: &mut [u8]) {
+        if let Some(value) = self.values.get(name) {
+            match parse_hex(value).filter(|bytes| bytes.len() == v.len()) {
+                Some(bytes) => v.copy_from_slice(&bytes),
+                None => self.errors.push(format!("Invalid value for {name}: {value}")),
+            }
+        }
+    }
+
+    // Strings aren't trimmed, as whitespace is significant.
+    fn visit_wstr(&mut self, name: &str, v: &mut [u16]) {
+        if let Some(value) = self.values.get(name) {
+            match encode_wstr(value, v.len()) {
+                Some(buf) => v.copy_from_slice(&buf),
+                None => self.errors.push(format!("Value too long for {name}: {value}")),
+            }
+        }
+    }
+}
+
+/// A param row with all of its fields.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExportedRow {
+    pub id: u64,
+    pub name: Option<String>,
+    #[serde(serialize_with = "serialize_fields")]
+    pub fields: Vec<(String, FieldValue)>,
+}
+
+// Serializes the fields as a map, keeping them in struct order.
+fn serialize_fields<S: Serializer>(
+    fields: &[(String, FieldValue)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(fields.iter().map(|(name, value)| (name, value)))
+}
+
+impl ExportedRow {
+    /// Collects the fields of the row `id` of `param` through `visit`, and
+    /// looks up its name in `PARAM_NAMES`.
+    pub fn collect(param: &str, id: u64, visit: impl FnOnce(&mut FieldCollector)) -> Self {
+        let mut collector = FieldCollector::default();
+        visit(&mut collector);
+
+        let name =
+            PARAM_NAMES.get(param).and_then(|names| names.get(&(id as usize))).map(String::from);
+
+        ExportedRow { id, name, fields: collector.0 }
+    }
+}
+
+fn csv_escape(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\"")).into()
+    } else {
+        s.into()
+    }
+}
+
+/// One line per row, with a header of the field names of the first row.
+pub fn to_csv(rows: &[ExportedRow]) -> String {
+    let mut out = String::from("id,name");
+    if let Some(row) = rows.first() {
+        for (name, _) in &row.fields {
+            write!(out, ",{}", csv_escape(name)).ok();
+        }
+    }
+    out.push('\n');
+
+    for row in rows {
+        write!(out, "{},{}", row.id, csv_escape(row.name.as_deref().unwrap_or(""))).ok();
+        for (_, value) in &row.fields {
+            write!(out, ",{}", csv_escape(&value.to_string())).ok();
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// An array of `{ "id", "name", "fields": { ... } }` objects.
+pub fn to_json(rows: &[ExportedRow]) -> String {
+    serde_json::to_string_pretty(rows).unwrap()
+}
+
+impl Params {
+    /// Collects all the rows of a param, or `None` if the param isn't loaded
+    /// or has no known layout.
+    pub fn export_param(&self, param: &str) -> Option<Vec<ExportedRow>> {
+        PARAM_VTABLE.get(param)?;
+
+        // Safety: the param is loaded, as it was found in the map.
+        let ids = unsafe { self.iter_param_ids(param) }?;
+
+        Some(
+            ids.enumerate()
+                .map(|(idx, id)| {
+                    ExportedRow::collect(param, id, |v| self.visit_param_item(param, idx, v))
+                })
+                .collect(),
+        )
+    }
+
+    /// Writes every param to a file named after it in `dir`. Returns the
+    /// number of files written.
+    pub fn export_all(&self, dir: &Path, format: ExportFormat) -> Result<usize, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Couldn't create {}: {e}", dir.display()))?;
+
+        let mut count = 0;
+        for param in self.keys() {
+            let Some(rows) = self.export_param(param) else {
+                continue;
+            };
+
+            let path = dir.join(format!("{param}.{}", format.extension()));
+            std::fs::write(&path, format.format(&rows))
+                .map_err(|e| format!("Couldn't write {}: {e}", path.display()))?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macro_param::ParamStruct;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(ParamStruct, Debug, Default, Clone, PartialEq)]
+    #[repr(C)]
+    struct FakeRow {
+        weight: f32,
+        icon_id: u16,
+        sort_id: i32,
+        pad: [u8; 2],
+        #[bitflag(is_enabled, 0)]
+        #[bitflag(is_hidden, 1)]
+        flags: u8,
+        delta: i8,
+    }
+
+    fn fake_rows() -> Vec<ExportedRow> {
+        let mut a =
+            FakeRow { weight: 0.1, icon_id: 12, sort_id: -5, flags: 1, ..Default::default() };
+        let mut b = FakeRow { weight: 1e-7, delta: -128, flags: 2, ..Default::default() };
+
+        vec![
+            ExportedRow::collect("FakeParam", 10, |v| a.visit(v)),
+            ExportedRow::collect("FakeParam", 20, |v| b.visit(v)),
+        ]
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut rows = fake_rows();
+        rows[0].name = Some("Flask, \"Crimson\"".to_string());
+
+        assert_eq!(
+            to_csv(&rows),
+            "id,name,weight,icon_id,sort_id,pad,is_enabled,is_hidden,delta\n10,\"Flask, \
+             \"\"Crimson\"\"\",0.1,12,-5,0000,true,false,0\n20,,0.0000001,0,0,0000,false,true,\
+             -128\n"
+        );
+        assert_eq!(to_csv(&[]), "id,name\n");
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(&fake_rows())).unwrap();
+
+        assert_eq!(json[0]["id"], 10);
+        assert!(json[0]["name"].is_null());
+        assert_eq!(json[0]["fields"]["icon_id"], 12);
+        assert_eq!(json[0]["fields"]["is_enabled"], true);
+        assert_eq!(json[1]["fields"]["delta"], -128);
+
+        // Fields are kept in struct order.
+        let text = to_json(&fake_rows());
+        assert!(text.find("\"weight\"").unwrap() < text.find("\"icon_id\"").unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let original =
+            FakeRow { weight: 0.1, icon_id: 65535, sort_id: -5, flags: 3, delta: -1, pad: [0; 2] };
+        let rows = vec![ExportedRow::collect("FakeParam", 10, |v| original.clone().visit(v))];
+
+        // CSV
+        let csv = to_csv(&rows);
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap().split(',');
+        let values = lines.next().unwrap().split(',');
+
+        let mut row = FakeRow::default();
+        let mut setter = FieldSetter::new(header.zip(values));
+        row.visit(&mut setter);
+        assert!(setter.errors.is_empty());
+        assert_eq!(row, original);
+
+        // JSON
+        let json: serde_json::Value = serde_json::from_str(&to_json(&rows)).unwrap();
+        let values = json[0]["fields"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| {
+                (k.clone(), v.as_str().map(String::from).unwrap_or_else(|| v.to_string()))
+            })
+            .collect::<Vec<_>>();
+
+        let mut row = FakeRow::default();
+        let mut setter = FieldSetter::new(values.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        row.visit(&mut setter);
+        assert!(setter.errors.is_empty());
+        assert_eq!(row, original);
+
+        let mut setter = FieldSetter::new([("icon_id", "-1"), ("is_hidden", "1")]);
+        row.visit(&mut setter);
+        assert_eq!(setter.errors, vec!["Invalid value for icon_id: -1".to_string()]);
+        assert!(row.is_hidden());
+    }
+}
@@ -0,0 +1,45 @@
+//! Rows held in memory, standing in for the params in the tests.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use super::RowAccess;
+use crate::{FieldMeta, ParamStruct, ParamVisitor};
+
+pub(crate) struct FakeRows<T>(RefCell<BTreeMap<String, Vec<(u64, T)>>>);
+
+impl<T: ParamStruct + Clone> FakeRows<T> {
+    pub(crate) fn new<'a>(params: impl IntoIterator<Item = (&'a str, Vec<(u64, T)>)>) -> Self {
+        FakeRows(RefCell::new(
+            params.into_iter().map(|(param, rows)| (param.to_string(), rows)).collect(),
+        ))
+    }
+
+    pub(crate) fn get(&self, param: &str, id: u64) -> T {
+        self.0.borrow()[param].iter().find(|(i, _)| *i == id).unwrap().1.clone()
+    }
+}
+
+impl<T: ParamStruct> RowAccess for FakeRows<T> {
+    fn row_ids(&self) -> Vec<(String, Vec<u64>)> {
+        self.0
+            .borrow()
+            .iter()
+            .map(|(param, rows)| (param.clone(), rows.iter().map(|(id, _)| *id).collect()))
+            .collect()
+    }
+
+    fn visit_row_idx(&self, param: &str, idx: usize, visitor: &mut dyn ParamVisitor) -> bool {
+        match self.0.borrow_mut().get_mut(param).and_then(|rows| rows.get_mut(idx)) {
+            Some((_, row)) => {
+                row.visit(visitor);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn fields(&self, _: &str) -> &'static [FieldMeta] {
+        T::FIELDS
+    }
+}
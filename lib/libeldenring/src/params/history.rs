@@ -0,0 +1,572 @@
+//! Tracks the edits made to param rows, so that they can be undone, reverted
+//! and exported as patches.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{
+    to_hex, FieldCollector, FieldSetter, FieldValue, ParamPatch, Params, PatchValue, PARAM_FIELDS,
+    PARAM_VTABLE,
+};
+use crate::{FieldMeta, ParamVisitor};
+
+/// Access to param rows, by index or by id.
+pub trait RowAccess {
+    /// Lists the params with the ids of their rows.
+    fn row_ids(&self) -> Vec<(String, Vec<u64>)>;
+
+    /// Visits the row at `idx` in the ids of `param`, returning `false` if it
+    /// doesn't exist.
+    fn visit_row_idx(&self, param: &str, idx: usize, visitor: &mut dyn ParamVisitor) -> bool;
+
+    /// The fields of the rows of `param`.
+    fn fields(&self, param: &str) -> &'static [FieldMeta];
+
+    /// Visits the row, returning `false` if it doesn't exist.
+    fn visit_row(&self, param: &str, id: u64, visitor: &mut dyn ParamVisitor) -> bool {
+        let idx = self
+            .row_ids()
+            .into_iter()
+            .find(|(p, _)| p == param)
+            .and_then(|(_, ids)| ids.iter().position(|&i| i == id));
+
+        idx.is_some_and(|idx| self.visit_row_idx(param, idx, visitor))
+    }
+}
+
+impl RowAccess for Params {
+    fn row_ids(&self) -> Vec<(String, Vec<u64>)> {
+        self.keys()
+            .filter(|param| PARAM_VTABLE.contains_key(*param))
+            .filter_map(|param| {
+                // Safety: the param is loaded, as it was found in the map.
+                let ids = unsafe { self.iter_param_ids(param) }?;
+                Some((param.clone(), ids.collect()))
+            })
+            .collect()
+    }
+
+    fn visit_row_idx(&self, param: &str, idx: usize, visitor: &mut dyn ParamVisitor) -> bool {
+        match (PARAM_VTABLE.get(param), unsafe { self.get_param_idx_ptr(param, idx) }) {
+            (Some(lambda), Some(ptr)) => {
+                lambda(ptr, visitor);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn fields(&self, param: &str) -> &'static [FieldMeta] {
+        PARAM_FIELDS.get(param).copied().unwrap_or_default()
+    }
+
+    // Only scans the ids of `param`.
+    fn visit_row(&self, param: &str, id: u64, visitor: &mut dyn ParamVisitor) -> bool {
+        // Safety: the param is loaded, as it was found in the map.
+        let idx =
+            unsafe { self.iter_param_ids(param) }.and_then(|mut ids| ids.position(|i| i == id));
+
+        idx.is_some_and(|idx| self.visit_row_idx(param, idx, visitor))
+    }
+}
+
+/// A change to a single field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldEdit {
+    pub param: String,
+    pub id: u64,
+    pub field: String,
+    pub old: FieldValue,
+    pub new: FieldValue,
+}
+
+/// A field whose current value differs from the one it had before the first
+/// edit to its row.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldDiff {
+    pub param: String,
+    pub id: u64,
+    pub field: String,
+    pub original: FieldValue,
+    pub current: FieldValue,
+}
+
+#[derive(Debug, Default)]
+pub struct ParamHistory {
+    // The values of the edited fields of each row before their first edit.
+    originals: BTreeMap<(String, u64), Vec<(String, FieldValue)>>,
+    undo: Vec<FieldEdit>,
+    redo: Vec<FieldEdit>,
+    // Whether the last edit is still in progress, e.g. a number being typed.
+    editing: bool,
+    generation: u64,
+}
+
+fn collect_row(rows: &impl RowAccess, param: &str, id: u64) -> Option<Vec<(String, FieldValue)>> {
+    let mut collector = FieldCollector::default();
+    rows.visit_row(param, id, &mut collector).then_some(collector.0)
+}
+
+fn write_field(rows: &impl RowAccess, param: &str, id: u64, field: &str, value: &FieldValue) {
+    let value = value.to_string();
+    rows.visit_row(param, id, &mut FieldSetter::new([(field, value.as_str())]));
+}
+
+impl ParamHistory {
+    /// Records the edits made to a row, given its values before and after
+    /// they were made, e.g. by an editing visitor.
+    pub fn record(
+        &mut self,
+        param: &str,
+        id: u64,
+        before: &[(String, FieldValue)],
+        after: &[(String, FieldValue)],
+    ) {
+        let edits = before
+            .iter()
+            .zip(after)
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| FieldEdit {
+                param: param.to_string(),
+                id,
+                field: field.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        if edits.is_empty() {
+            return;
+        }
+
+        for edit in &edits {
+            self.keep_original(param, id, &edit.field, &edit.old);
+        }
+        self.undo.extend(edits);
+        self.redo.clear();
+        self.changed();
+    }
+
+    /// Records a change to a single field, e.g. made by an input widget.
+    /// Consecutive changes to the same field are merged into one edit until
+    /// [`end_edit`](Self::end_edit), so that typing a number is undone at
+    /// once.
+    pub fn record_field(
+        &mut self,
+        param: &str,
+        id: u64,
+        field: &str,
+        old: FieldValue,
+        new: FieldValue,
+    ) {
+        if old == new {
+            return;
+        }
+
+        self.keep_original(param, id, field, &old);
+        match self.undo.last_mut() {
+            Some(last)
+                if self.editing && last.param == param && last.id == id && last.field == field =>
+            {
+                last.new = new;
+                if last.old == last.new {
+                    self.undo.pop();
+                }
+            },
+            _ => self.undo.push(FieldEdit {
+                param: param.to_string(),
+                id,
+                field: field.to_string(),
+                old,
+                new,
+            }),
+        }
+        self.redo.clear();
+        self.changed();
+        self.editing = true;
+    }
+
+    /// Ends the edit in progress: the next change starts a new one.
+    pub fn end_edit(&mut self) {
+        self.editing = false;
+    }
+
+    /// Changes whenever the history does, so that e.g. its diff can be
+    /// cached.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn changed(&mut self) {
+        self.editing = false;
+        self.generation += 1;
+    }
+
+    fn keep_original(&mut self, param: &str, id: u64, field: &str, value: &FieldValue) {
+        let fields = self.originals.entry((param.to_string(), id)).or_default();
+        if !fields.iter().any(|(name, _)| name == field) {
+            fields.push((field.to_string(), value.clone()));
+        }
+    }
+
+    /// Records the edits made to a row through `visit`.
+    pub fn edit<V: ParamVisitor>(
+        &mut self,
+        rows: &impl RowAccess,
+        param: &str,
+        id: u64,
+        visitor: &mut V,
+    ) {
+        let Some(before) = collect_row(rows, param, id) else {
+            return;
+        };
+        rows.visit_row(param, id, visitor);
+        if let Some(after) = collect_row(rows, param, id) {
+            self.record(param, id, &before, &after);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Restores the previous value of the last edited field.
+    pub fn undo(&mut self, rows: &impl RowAccess) -> Option<&FieldEdit> {
+        let edit = self.undo.pop()?;
+        write_field(rows, &edit.param, edit.id, &edit.field, &edit.old);
+        self.changed();
+        self.redo.push(edit);
+        self.redo.last()
+    }
+
+    /// Applies again the last undone edit.
+    pub fn redo(&mut self, rows: &impl RowAccess) -> Option<&FieldEdit> {
+        let edit = self.redo.pop()?;
+        write_field(rows, &edit.param, edit.id, &edit.field, &edit.new);
+        self.changed();
+        self.undo.push(edit);
+        self.undo.last()
+    }
+
+    /// Restores the original value of a field. Its edits are dropped from the
+    /// history.
+    pub fn revert_field(&mut self, rows: &impl RowAccess, param: &str, id: u64, field: &str) {
+        let key = (param.to_string(), id);
+        let Some(original) = self
+            .originals
+            .get(&key)
+            .and_then(|fields| fields.iter().find(|(name, _)| name == field))
+        else {
+            return;
+        };
+
+        write_field(rows, param, id, field, &original.1);
+        self.forget(|edit| edit.param == param && edit.id == id && edit.field == field);
+    }
+
+    /// Restores the original values of a row.
+    pub fn revert_row(&mut self, rows: &impl RowAccess, param: &str, id: u64) {
+        self.revert(rows, |p, i| p == param && i == id);
+    }
+
+    /// Restores the original values of all the edited rows of a param.
+    pub fn revert_param(&mut self, rows: &impl RowAccess, param: &str) {
+        self.revert(rows, |p, _| p == param);
+    }
+
+    /// Restores the original values of all the edited rows.
+    pub fn revert_all(&mut self, rows: &impl RowAccess) {
+        self.revert(rows, |_, _| true);
+    }
+
+    fn revert(&mut self, rows: &impl RowAccess, filter: impl Fn(&str, u64) -> bool) {
+        self.originals.retain(|(param, id), fields| {
+            if !filter(param, *id) {
+                return true;
+            }
+
+            let values =
+                fields.iter().map(|(field, value)| (field, value.to_string())).collect::<Vec<_>>();
+            rows.visit_row(
+                param,
+                *id,
+                &mut FieldSetter::new(values.iter().map(|(f, v)| (f.as_str(), v.as_str()))),
+            );
+            false
+        });
+        self.forget(|edit| filter(&edit.param, edit.id));
+    }
+
+    fn forget(&mut self, filter: impl Fn(&FieldEdit) -> bool) {
+        self.undo.retain(|edit| !filter(edit));
+        self.redo.retain(|edit| !filter(edit));
+        self.changed();
+    }
+
+    /// Lists the fields that differ from their original values, sorted by
+    /// param and row id.
+    pub fn diff(&self, rows: &impl RowAccess) -> Vec<FieldDiff> {
+        self.originals
+            .iter()
+            .flat_map(|((param, id), originals)| {
+                // In the order of the row's fields.
+                let current = collect_row(rows, param, *id).unwrap_or_default();
+                current
+                    .into_iter()
+                    .filter_map(|(field, current)| {
+                        let (_, original) = originals.iter().find(|(name, _)| *name == field)?;
+                        (*original != current).then(|| FieldDiff {
+                            param: param.clone(),
+                            id: *id,
+                            field,
+                            original: original.clone(),
+                            current,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl From<FieldValue> for PatchValue {
+    fn from(value: FieldValue) -> Self {
+        match value {
+            FieldValue::U8(v) => PatchValue::Int(v.into()),
+            FieldValue::U16(v) => PatchValue::Int(v.into()),
+            FieldValue::U32(v) => PatchValue::Int(v.into()),
+            FieldValue::I8(v) => PatchValue::Int(v.into()),
+            FieldValue::I16(v) => PatchValue::Int(v.into()),
+            FieldValue::I32(v) => PatchValue::Int(v.into()),
+            // Going through the shortest representation keeps e.g. 0.1 from
+            // becoming 0.10000000149011612.
+            FieldValue::F32(v) => PatchValue::Float(v.to_string().parse().unwrap()),
+            // TOML integers are signed, so large values are written as strings.
+            FieldValue::U64(v) => i64::try_from(v)
+                .map(PatchValue::Int)
+                .unwrap_or_else(|_| PatchValue::Str(v.to_string())),
+            FieldValue::I64(v) => PatchValue::Int(v),
+            FieldValue::F64(v) => PatchValue::Float(v),
+            FieldValue::Bool(v) => PatchValue::Bool(v),
+            FieldValue::Bytes(v) => PatchValue::Str(to_hex(&v)),
+            FieldValue::WStr(v) => PatchValue::Str(v),
+        }
+    }
+}
+
+/// Groups the changed fields by row, as patches to their current values.
+pub fn diff_to_patches(diff: &[FieldDiff]) -> Vec<ParamPatch> {
+    let mut patches: Vec<ParamPatch> = Vec::new();
+
+    for d in diff {
+        match patches.last_mut() {
+            Some(patch) if patch.param == d.param && patch.id == d.id => {
+                patch.fields.insert(d.field.clone(), d.current.clone().into());
+            },
+            _ => patches.push(ParamPatch {
+                param: d.param.clone(),
+                id: d.id,
+                fields: [(d.field.clone(), d.current.clone().into())].into_iter().collect(),
+            }),
+        }
+    }
+
+    patches
+}
+
+/// Formats the changed fields as a `param_patches` section, which can be
+/// pasted in the practice tool's configuration file.
+pub fn diff_to_patch_file(diff: &[FieldDiff]) -> Result<String, String> {
+    #[derive(Serialize)]
+    struct PatchFile {
+        param_patches: Vec<ParamPatch>,
+    }
+
+    toml::to_string(&PatchFile { param_patches: diff_to_patches(diff) }).map_err(|e| format!("{e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use macro_param::ParamStruct;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::params::fake::FakeRows;
+    use crate::prelude::*;
+
+    #[derive(ParamStruct, Debug, Default, Clone, PartialEq)]
+    #[repr(C)]
+    struct HistoryRow {
+        weight: f32,
+        icon_id: u16,
+        #[bitflag(is_enabled, 0)]
+        flags: u8,
+    }
+
+    fn rows() -> FakeRows<HistoryRow> {
+        FakeRows::new([
+            ("A", vec![(1, HistoryRow::default()), (2, HistoryRow::default())]),
+            ("B", vec![(1, HistoryRow::default())]),
+        ])
+    }
+
+    fn set(
+        history: &mut ParamHistory,
+        rows: &FakeRows<HistoryRow>,
+        param: &str,
+        id: u64,
+        field: &str,
+        value: &str,
+    ) {
+        history.edit(rows, param, id, &mut FieldSetter::new([(field, value)]));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let rows = rows();
+        let mut history = ParamHistory::default();
+
+        set(&mut history, &rows, "A", 1, "icon_id", "12");
+        set(&mut history, &rows, "A", 1, "icon_id", "13");
+        set(&mut history, &rows, "A", 1, "weight", "0.5");
+        // Not an edit.
+        set(&mut history, &rows, "A", 1, "weight", "0.5");
+        assert_eq!(rows.get("A", 1), HistoryRow { weight: 0.5, icon_id: 13, flags: 0 });
+
+        assert_eq!(history.undo(&rows).unwrap().field, "weight");
+        assert_eq!(history.undo(&rows).unwrap().new, FieldValue::U16(13));
+        assert_eq!(rows.get("A", 1), HistoryRow { weight: 0.0, icon_id: 12, flags: 0 });
+
+        assert_eq!(history.redo(&rows).unwrap().new, FieldValue::U16(13));
+        assert_eq!(rows.get("A", 1).icon_id, 13);
+
+        // A new edit drops the redo history.
+        set(&mut history, &rows, "A", 2, "is_enabled", "true");
+        assert!(!history.can_redo());
+        assert!(history.undo(&rows).is_some());
+        assert!(history.undo(&rows).is_some());
+        assert!(history.undo(&rows).is_some());
+        assert!(history.undo(&rows).is_none());
+        assert_eq!(rows.get("A", 1), HistoryRow::default());
+        assert_eq!(rows.get("A", 2), HistoryRow::default());
+    }
+
+    #[test]
+    fn test_record_field() {
+        let rows = rows();
+        let mut history = ParamHistory::default();
+        let type_icon = |history: &mut ParamHistory, old: u16, new: u16| {
+            write_field(&rows, "A", 1, "icon_id", &FieldValue::U16(new));
+            history.record_field("A", 1, "icon_id", FieldValue::U16(old), FieldValue::U16(new));
+        };
+
+        // Typing "123" is a single edit.
+        let generation = history.generation();
+        type_icon(&mut history, 0, 1);
+        type_icon(&mut history, 1, 12);
+        type_icon(&mut history, 12, 123);
+        assert!(history.generation() > generation);
+        history.end_edit();
+
+        // The next one is not merged into it.
+        type_icon(&mut history, 123, 12);
+        history.end_edit();
+        assert_eq!(history.undo(&rows).unwrap().old, FieldValue::U16(123));
+        assert_eq!(history.undo(&rows).unwrap().old, FieldValue::U16(0));
+        assert!(!history.can_undo());
+        assert_eq!(rows.get("A", 1).icon_id, 0);
+
+        // Typing back the old value leaves nothing to undo.
+        type_icon(&mut history, 0, 5);
+        type_icon(&mut history, 5, 0);
+        assert!(!history.can_undo());
+        assert!(history.diff(&rows).is_empty());
+    }
+
+    #[test]
+    fn test_revert_and_diff() {
+        let rows = rows();
+        let mut history = ParamHistory::default();
+
+        set(&mut history, &rows, "A", 1, "icon_id", "12");
+        set(&mut history, &rows, "A", 1, "weight", "0.1");
+        set(&mut history, &rows, "A", 2, "is_enabled", "true");
+        set(&mut history, &rows, "B", 1, "icon_id", "7");
+
+        let diff = history.diff(&rows);
+        assert_eq!(diff.len(), 4);
+        assert_eq!(diff[0], FieldDiff {
+            param: "A".to_string(),
+            id: 1,
+            field: "weight".to_string(),
+            original: FieldValue::F32(0.0),
+            current: FieldValue::F32(0.1),
+        });
+
+        history.revert_field(&rows, "A", 1, "weight");
+        assert_eq!(rows.get("A", 1), HistoryRow { weight: 0.0, icon_id: 12, flags: 0 });
+        assert_eq!(history.diff(&rows).len(), 3);
+
+        history.revert_row(&rows, "A", 1);
+        assert_eq!(rows.get("A", 1), HistoryRow::default());
+        history.revert_param(&rows, "A");
+        assert_eq!(rows.get("A", 2), HistoryRow::default());
+        assert_eq!(rows.get("B", 1).icon_id, 7);
+        history.revert_all(&rows);
+        assert_eq!(rows.get("B", 1), HistoryRow::default());
+
+        assert!(history.diff(&rows).is_empty());
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_patch_file() {
+        let rows = rows();
+        let mut history = ParamHistory::default();
+
+        set(&mut history, &rows, "A", 1, "icon_id", "12");
+        set(&mut history, &rows, "A", 1, "weight", "0.1");
+        set(&mut history, &rows, "B", 1, "is_enabled", "true");
+
+        #[derive(Deserialize)]
+        struct PatchFile {
+            param_patches: Vec<ParamPatch>,
+        }
+
+        let file = diff_to_patch_file(&history.diff(&rows)).unwrap();
+        let patches = toml::from_str::<PatchFile>(&file).unwrap().param_patches;
+        assert_eq!(patches, vec![
+            ParamPatch {
+                param: "A".to_string(),
+                id: 1,
+                fields: [
+                    ("icon_id".to_string(), PatchValue::Int(12)),
+                    ("weight".to_string(), PatchValue::Float(0.1)),
+                ]
+                .into_iter()
+                .collect(),
+            },
+            ParamPatch {
+                param: "B".to_string(),
+                id: 1,
+                fields: [("is_enabled".to_string(), PatchValue::Bool(true))].into_iter().collect(),
+            },
+        ]);
+
+        // Applying the patches to pristine rows gives the same result.
+        let pristine = self::rows();
+        for patch in &patches {
+            let errors = patch.apply_with(|v| {
+                pristine.visit_row(&patch.param, patch.id, v);
+            });
+            assert!(errors.is_empty());
+        }
+        assert_eq!(pristine.get("A", 1), rows.get("A", 1));
+        assert_eq!(pristine.get("B", 1), rows.get("B", 1));
+    }
+}
@@ -0,0 +1,160 @@
+//! Paramdex row names, stored compactly in `codegen/param_names.bin` and
+//! decoded one param at a time on first lookup.
+//!
+//! The file starts with the number of params, followed for each by its name
+//! (u8 length, UTF-8) and the offset and length of its table, sorted by name.
+//! A table holds the row count `n`, `n` ascending u32 ids, `n` u16 name
+//! lengths and the concatenated UTF-8 names. All integers are little endian.
+
+use log::error;
+use once_cell::sync::OnceCell;
+
+use super::bytes::{read_bytes, read_u16, read_u32};
+
+pub struct ParamNames {
+    params: Vec<(&'static str, &'static [u8], OnceCell<RowNames>)>,
+}
+
+impl ParamNames {
+    /// Reads the list of params. Their tables are decoded on first lookup.
+    pub fn parse(data: &'static [u8]) -> Result<Self, String> {
+        let count = read_u32(data, 0)? as usize;
+        let mut offset = 4;
+        let mut params = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let len = read_bytes(data, offset, 1)?[0] as usize;
+            let name = std::str::from_utf8(read_bytes(data, offset + 1, len)?)
+                .map_err(|e| format!("Invalid param name at {offset:#x}: {e}"))?;
+            offset += 1 + len;
+
+            let table_offset = read_u32(data, offset)? as usize;
+            let table_len = read_u32(data, offset + 4)? as usize;
+            offset += 8;
+
+            params.push((name, read_bytes(data, table_offset, table_len)?, OnceCell::new()));
+        }
+
+        if !params.is_sorted_by_key(|(name, ..)| *name) {
+            return Err("Params are not sorted".to_string());
+        }
+
+        Ok(ParamNames { params })
+    }
+
+    /// The row names of a param, or `None` if Paramdex doesn't name its rows.
+    pub fn get(&self, param: &str) -> Option<&RowNames> {
+        let idx = self.params.binary_search_by_key(&param, |(name, ..)| *name).ok()?;
+        let (_, table, rows) = &self.params[idx];
+        Some(rows.get_or_init(|| {
+            RowNames::decode(table).unwrap_or_else(|e| {
+                error!("Couldn't decode the row names of {param}: {e}");
+                RowNames::default()
+            })
+        }))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.params.iter().map(|(name, ..)| *name)
+    }
+}
+
+#[derive(Default)]
+pub struct RowNames {
+    ids: Vec<u32>,
+    names: Vec<&'static str>,
+    // Indices in `names`, sorted by name then id.
+    by_name: Vec<u32>,
+}
+
+impl RowNames {
+    fn decode(table: &'static [u8]) -> Result<Self, String> {
+        let count = read_u32(table, 0)? as usize;
+        let ids_offset = 4;
+        let lens_offset = ids_offset + 4 * count;
+        let mut name_offset = lens_offset + 2 * count;
+
+        let mut ids = Vec::with_capacity(count);
+        let mut names = Vec::with_capacity(count);
+        for i in 0..count {
+            ids.push(read_u32(table, ids_offset + 4 * i)?);
+
+            let len = read_u16(table, lens_offset + 2 * i)? as usize;
+            let name = std::str::from_utf8(read_bytes(table, name_offset, len)?)
+                .map_err(|e| format!("Invalid row name at {name_offset:#x}: {e}"))?;
+            names.push(name);
+            name_offset += len;
+        }
+
+        if !ids.is_sorted() {
+            return Err("Row ids are not sorted".to_string());
+        }
+
+        // The sort is stable, so rows sharing a name stay in id order.
+        let mut by_name = (0..count as u32).collect::<Vec<_>>();
+        by_name.sort_by_key(|&idx| names[idx as usize]);
+
+        Ok(RowNames { ids, names, by_name })
+    }
+
+    /// The name of the row `id`.
+    pub fn get(&self, id: &usize) -> Option<&'static str> {
+        let id = u32::try_from(*id).ok()?;
+        self.ids.binary_search(&id).ok().map(|idx| self.names[idx])
+    }
+
+    /// The lowest id of the rows named `name`.
+    pub fn id(&self, name: &str) -> Option<usize> {
+        let pos = self.by_name.partition_point(|&idx| self.names[idx as usize] < name);
+        let idx = *self.by_name.get(pos)? as usize;
+        (self.names[idx] == name).then_some(self.ids[idx] as usize)
+    }
+
+    /// The ids and names of the rows, in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &'static str)> + '_ {
+        self.ids.iter().zip(&self.names).map(|(&id, &name)| (id as usize, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::PARAM_NAMES;
+    use super::ParamNames;
+
+    #[test]
+    fn test_param_names() {
+        let names = PARAM_NAMES.get("EquipParamAccessory").unwrap();
+        assert_eq!(names.get(&1000), Some("Crimson Amber Medallion"));
+        assert_eq!(names.get(&1001), Some("Crimson Amber Medallion +1"));
+        assert_eq!(names.get(&999), None);
+        assert_eq!(names.get(&usize::MAX), None);
+
+        assert_eq!(names.id("Crimson Amber Medallion +1"), Some(1001));
+        assert_eq!(names.id("Crimson Amber"), None);
+        assert!(names.iter().map(|(id, _)| id).is_sorted());
+
+        assert!(PARAM_NAMES.get("SpEffectParam").is_none());
+        assert!(PARAM_NAMES.keys().any(|param| param == "EquipParamWeapon"));
+        assert!(PARAM_NAMES.keys().all(|param| PARAM_NAMES.get(param).is_some()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(ParamNames::parse(&[1, 0]).is_err());
+        // One param named "A", whose table is out of bounds.
+        assert!(ParamNames::parse(&[1, 0, 0, 0, 1, b'A', 0xff, 0, 0, 0, 4, 0, 0, 0]).is_err());
+        // Two params with empty tables, out of order.
+        #[rustfmt::skip]
+        let unsorted: &'static [u8] = &[
+            2, 0, 0, 0,
+            1, b'B', 0, 0, 0, 0, 0, 0, 0, 0,
+            1, b'A', 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        assert!(ParamNames::parse(unsorted).is_err());
+
+        // A table claiming one row, with no data.
+        let names =
+            ParamNames::parse(&[1, 0, 0, 0, 1, b'A', 14, 0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0]).unwrap();
+        assert!(names.get("A").unwrap().get(&0).is_none());
+    }
+}
@@ -0,0 +1,304 @@
+//! Edits to param rows described in configuration files, e.g.
+//!
+//! ```toml
+//! [[param_patches]]
+//! param = "EquipParamGoods"
+//! id = 130
+//! fields = { icon_id = 12 }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{encode_wstr, parse_hex, Params, PARAM_VTABLE};
+use crate::ParamVisitor;
+
+/// A field value. Strings hold hex for byte arrays, text for UTF-16 strings,
+/// and integers that don't fit in TOML's `i64`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PatchValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+/// Sets some fields of the row `id` of `param`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamPatch {
+    pub param: String,
+    pub id: u64,
+    pub fields: BTreeMap<String, PatchValue>,
+}
+
+impl ParamPatch {
+    /// Applies the patch to a row through `visit`. Returns an error for each
+    /// field that doesn't exist or can't hold its value.
+    pub fn apply_with(&self, visit: impl FnOnce(&mut PatchVisitor)) -> Vec<String> {
+        let mut visitor = PatchVisitor { fields: &self.fields, results: HashMap::new() };
+        visit(&mut visitor);
+
+        self.fields
+            .keys()
+            .filter_map(|field| {
+                let err = match visitor.results.remove(field.as_str()) {
+                    Some(Ok(())) => return None,
+                    Some(Err(e)) => e,
+                    None => "unknown field".to_string(),
+                };
+                Some(format!("{}[{}].{field}: {err}", self.param, self.id))
+            })
+            .collect()
+    }
+}
+
+/// Writes the values of a patch to the fields with the same name.
+pub struct PatchVisitor<'a> {
+    fields: &'a BTreeMap<String, PatchValue>,
+    results: HashMap<String, Result<(), String>>,
+}
+
+impl PatchVisitor<'_> {
+    fn set_int<T: TryFrom<i64> + FromStr>(&mut self, name: &str, ty: &str, v: &mut T) {
+        let result = match self.fields.get(name) {
+            None => return,
+            Some(&PatchValue::Int(value)) => T::try_from(value)
+                .map(|value| *v = value)
+                .map_err(|_| format!("{value} is out of range for {ty}")),
+            Some(PatchValue::Str(value)) => value
+                .parse()
+                .map(|value| *v = value)
+                .map_err(|_| format!("{value:?} is not a valid {ty}")),
+            Some(value) => Err(format!("expected an integer for {ty}, found {value:?}")),
+        };
+        self.results.insert(name.to_string(), result);
+    }
+
+    fn set_float<T: FromStr>(&mut self, name: &str, ty: &str, v: &mut T) {
+        // Parsing the decimal representation serves both f32 and f64.
+        let result = match self.fields.get(name) {
+            None => return,
+            Some(PatchValue::Float(value)) => value.to_string().parse().ok(),
+            Some(PatchValue::Int(value)) => value.to_string().parse().ok(),
+            Some(_) => None,
+        }
+        .map(|value| *v = value)
+        .ok_or_else(|| format!("expected a number for {ty}, found {:?}", self.fields[name]));
+        self.results.insert(name.to_string(), result);
+    }
+}
+
+impl ParamVisitor for PatchVisitor<'_> {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.set_int(name, "u8", v);
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.set_int(name, "u16", v);
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.set_int(name, "u32", v);
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.set_int(name, "i8", v);
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.set_int(name, "i16", v);
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.set_int(name, "i32", v);
+    }
+
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        self.set_float(name, "f32", v);
+    }
+
+    fn visit_u64(&mut self, name: &str, v: &mut u64) {
+        self.set_int(name, "u64", v);
+    }
+
+    fn visit_i64(&mut self, name: &str, v: &mut i64) {
+        self.set_int(name, "i64", v);
+    }
+
+    fn visit_f64(&mut self, name: &str, v: &mut f64) {
+        self.set_float(name, "f64", v);
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        let result = match self.fields.get(name) {
+            None => return,
+            Some(&PatchValue::Bool(value)) => Ok(value),
+            Some(value) => Err(format!("expected a boolean, found {value:?}")),
+        }
+        .map(|value| *v = value);
+        self.results.insert(name.to_string(), result);
+    }
+
+    fn visit_bytes(&mut self, name: &str, v: &mut [u8]) {
+        let result = match self.fields.get(name) {
+            None => return,
+            Some(PatchValue::Str(value)) => parse_hex(value)
+                .filter(|bytes| bytes.len() == v.len())
+                .ok_or_else(|| format!("expected {} hex bytes, found {value:?}", v.len())),
+            Some(value) => Err(format!("expected a hex string, found {value:?}")),
+        }
+        .map(|bytes| v.copy_from_slice(&bytes));
+        self.results.insert(name.to_string(), result);
+    }
+
+    fn visit_wstr(&mut self, name: &str, v: &mut [u16]) {
+        let result = match self.fields.get(name) {
+            None => return,
+            Some(PatchValue::Str(value)) => encode_wstr(value, v.len())
+                .ok_or_else(|| format!("{value:?} is longer than {} characters", v.len())),
+            Some(value) => Err(format!("expected a string, found {value:?}")),
+        }
+        .map(|buf| v.copy_from_slice(&buf));
+        self.results.insert(name.to_string(), result);
+    }
+}
+
+impl Params {
+    /// Applies the patches to the loaded params. Returns an error for each
+    /// patch whose param or row doesn't exist, and for each field that
+    /// couldn't be set.
+    pub fn apply_patches(&self, patches: &[ParamPatch]) -> Vec<String> {
+        patches
+            .iter()
+            .flat_map(|patch| {
+                if !PARAM_VTABLE.contains_key(&patch.param) {
+                    return vec![format!("{}: unknown param", patch.param)];
+                }
+
+                // Safety: the param is loaded, as it was found in the map.
+                let idx = unsafe { self.iter_param_ids(&patch.param) }
+                    .and_then(|mut ids| ids.position(|id| id == patch.id));
+
+                match idx {
+                    Some(idx) => patch.apply_with(|v| self.visit_param_item(&patch.param, idx, v)),
+                    None => vec![format!("{}[{}]: unknown row", patch.param, patch.id)],
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macro_param::ParamStruct;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(ParamStruct, Debug, Default)]
+    #[repr(C)]
+    struct PatchedRow {
+        weight: f32,
+        icon_id: u16,
+        sort_id: i32,
+        #[bitflag(is_enabled, 0)]
+        flags: u8,
+        pad: [u8; 3],
+        name: [u16; 4],
+        hash: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct Config {
+        param_patches: Vec<ParamPatch>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let config: Config = toml::from_str(
+            r#"
+            [[param_patches]]
+            param = "EquipParamGoods"
+            id = 130
+            fields = { icon_id = 12, weight = 0.5, is_enabled = true }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.param_patches, vec![ParamPatch {
+            param: "EquipParamGoods".to_string(),
+            id: 130,
+            fields: [
+                ("icon_id".to_string(), PatchValue::Int(12)),
+                ("weight".to_string(), PatchValue::Float(0.5)),
+                ("is_enabled".to_string(), PatchValue::Bool(true)),
+            ]
+            .into_iter()
+            .collect(),
+        }]);
+
+        assert!(toml::from_str::<Config>(
+            "[[param_patches]]\nparam = \"EquipParamGoods\"\nrow = 130\nfields = {}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let patch = |fields: &str| ParamPatch {
+            param: "FakeParam".to_string(),
+            id: 10,
+            fields: toml::from_str(fields).unwrap(),
+        };
+
+        let mut row = PatchedRow::default();
+        let errors = patch("icon_id = 12\nweight = 2\nsort_id = -1\nis_enabled = true")
+            .apply_with(|v| row.visit(v));
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!((row.icon_id, row.weight, row.sort_id), (12, 2.0, -1));
+        assert!(row.is_enabled());
+
+        let mut row = PatchedRow::default();
+        let errors =
+            patch("icon_id = 65536\nsort_id = 1.5\nis_enabled = 1\nicon = 3\nweight = 0.25")
+                .apply_with(|v| row.visit(v));
+        assert_eq!(errors, vec![
+            "FakeParam[10].icon: unknown field",
+            "FakeParam[10].icon_id: 65536 is out of range for u16",
+            "FakeParam[10].is_enabled: expected a boolean, found Int(1)",
+            "FakeParam[10].sort_id: expected an integer for i32, found Float(1.5)",
+        ]);
+        // The valid edits are still applied.
+        assert_eq!((row.icon_id, row.weight, row.sort_id), (0, 0.25, 0));
+    }
+
+    #[test]
+    fn test_apply_arrays() {
+        let patch = |fields: &str| ParamPatch {
+            param: "FakeParam".to_string(),
+            id: 10,
+            fields: toml::from_str(fields).unwrap(),
+        };
+
+        let mut row = PatchedRow::default();
+        let errors = patch("pad = \"0a0b0c\"\nname = \"Tor\"\nhash = \"18446744073709551615\"")
+            .apply_with(|v| row.visit(v));
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(row.pad, [10, 11, 12]);
+        assert_eq!(row.name, [84, 111, 114, 0]);
+        assert_eq!(row.hash, u64::MAX);
+
+        let errors =
+            patch("pad = \"0a0b\"\nname = \"Torrent\"\nhash = -1").apply_with(|v| row.visit(v));
+        assert_eq!(errors, vec![
+            "FakeParam[10].hash: -1 is out of range for u64",
+            "FakeParam[10].name: \"Torrent\" is longer than 4 characters",
+            "FakeParam[10].pad: expected 3 hex bytes, found \"0a0b\"",
+        ]);
+        assert_eq!(row.pad, [10, 11, 12]);
+    }
+}
@@ -0,0 +1,732 @@
+//! Reads params from a `regulation.bin` file, without the game running.
+//!
+//! The file is encrypted with AES-256-CBC and holds a DCX compressed BND4
+//! archive of PARAM files. The rows of each PARAM are copied into buffers laid
+//! out like the generated structs, so they can be visited like the params in
+//! the game's memory.
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::c_void;
+use std::io::Read;
+use std::path::Path;
+
+use aes::cipher::{BlockDecryptMut, KeyIvInit};
+use flate2::read::ZlibDecoder;
+use serde::Serialize;
+
+use super::bytes::{
+    read, read_bytes, read_cstr, read_u16, read_u32, read_u32_be, read_u64, read_wstr,
+};
+use super::{ExportedRow, FieldDiff, RowAccess, PARAM_FIELDS, PARAM_VTABLE};
+use crate::{FieldMeta, ParamVisitor};
+
+const REGULATION_KEY: [u8; 32] = [
+    0x99, 0xbf, 0xfc, 0x36, 0x6a, 0x6b, 0xc8, 0xc6, 0xf5, 0x82, 0x7d, 0x09, 0x36, 0x02, 0xd6, 0x76,
+    0xc4, 0x28, 0x92, 0xa0, 0x1c, 0x20, 0x7f, 0xb0, 0x24, 0xd3, 0xaf, 0x4e, 0x49, 0x3f, 0xef, 0x99,
+];
+
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+/// Visits a row through a pointer to it, as in `PARAM_VTABLE`.
+type RowVisitor = dyn Fn(*const c_void, &mut dyn ParamVisitor) + Send + Sync;
+
+/// The params of a `regulation.bin` file, by the name of their PARAM file.
+pub struct Regulation(BTreeMap<String, ParamFile>);
+
+impl Regulation {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data =
+            std::fs::read(path).map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+        Regulation::parse(&data)
+    }
+
+    /// Parses an encrypted `regulation.bin`, or its decrypted DCX or BND4
+    /// contents. Params without a generated struct are skipped.
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        Regulation::parse_with(data, generated_layout)
+    }
+
+    /// Builds a regulation holding a single param of zeroed rows, which
+    /// visitors can then fill in, e.g. to test code reading regulations.
+    /// Returns `None` if the param has no generated struct.
+    pub fn with_rows(param: &str, rows: &[(u64, Option<&str>)]) -> Option<Self> {
+        let (fields, visit) = generated_layout(param)?;
+        let stride = row_size(fields).div_ceil(8);
+
+        let file = ParamFile {
+            param_type: param.to_string(),
+            fields,
+            visit,
+            ids: rows.iter().map(|(id, _)| *id).collect(),
+            names: rows.iter().map(|(_, name)| name.map(String::from)).collect(),
+            data: vec![Cell::new(0); rows.len() * stride],
+            stride,
+        };
+        Some(Regulation(BTreeMap::from([(param.to_string(), file)])))
+    }
+
+    fn parse_with(
+        data: &[u8],
+        layout: impl Fn(&str) -> Option<(&'static [FieldMeta], &'static RowVisitor)>,
+    ) -> Result<Self, String> {
+        let decrypted;
+        let data = if data.starts_with(b"DCX\0") || data.starts_with(b"BND4") {
+            data
+        } else {
+            decrypted = decrypt_regulation(data)?;
+            &decrypted
+        };
+
+        let decompressed;
+        let data = if data.starts_with(b"DCX\0") {
+            decompressed = decompress_dcx(data)?;
+            &decompressed
+        } else {
+            data
+        };
+
+        let mut params = BTreeMap::new();
+        for (path, file) in read_bnd4(data)? {
+            // e.g. `N:\GR\data\Param\param\GameParam\EquipParamWeapon.param`
+            let name = path.rsplit(['\\', '/']).next().unwrap_or(&path);
+            let name = name.strip_suffix(".param").unwrap_or(name);
+
+            if let Some((fields, visit)) = layout(name) {
+                let param =
+                    ParamFile::parse(file, fields, visit).map_err(|e| format!("{name}: {e}"))?;
+                params.insert(name.to_string(), param);
+            }
+        }
+
+        Ok(Regulation(params))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    pub fn get(&self, param: &str) -> Option<&ParamFile> {
+        self.0.get(param)
+    }
+}
+
+impl RowAccess for Regulation {
+    fn row_ids(&self) -> Vec<(String, Vec<u64>)> {
+        self.0.iter().map(|(param, file)| (param.clone(), file.ids.clone())).collect()
+    }
+
+    fn visit_row_idx(&self, param: &str, idx: usize, visitor: &mut dyn ParamVisitor) -> bool {
+        match self.0.get(param) {
+            Some(file) if idx < file.ids.len() => {
+                file.visit_row(idx, visitor);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn fields(&self, param: &str) -> &'static [FieldMeta] {
+        self.0.get(param).map(|file| file.fields).unwrap_or_default()
+    }
+
+    fn visit_row(&self, param: &str, id: u64, visitor: &mut dyn ParamVisitor) -> bool {
+        let idx = self.0.get(param).and_then(|file| file.ids.iter().position(|&i| i == id));
+        idx.is_some_and(|idx| self.visit_row_idx(param, idx, visitor))
+    }
+}
+
+fn generated_layout(param: &str) -> Option<(&'static [FieldMeta], &'static RowVisitor)> {
+    // Structs are named after their paramdef, e.g. `SpEffect` for
+    // `SpEffectParam` and `AtkParam` for `AtkParam_Npc`.
+    let def = [Some(param), param.rsplit_once('_').map(|(p, _)| p), param.strip_suffix("Param")]
+        .into_iter()
+        .flatten()
+        .find(|def| PARAM_VTABLE.contains_key(*def))?;
+    Some((*PARAM_FIELDS.get(def)?, &**PARAM_VTABLE.get(def)?))
+}
+
+// The size of the rows laid out as `fields`.
+fn row_size(fields: &[FieldMeta]) -> usize {
+    fields.iter().map(|f| f.offset + f.size).max().unwrap_or(0)
+}
+
+/// The rows of a PARAM file.
+pub struct ParamFile {
+    pub param_type: String,
+    pub fields: &'static [FieldMeta],
+    visit: &'static RowVisitor,
+    ids: Vec<u64>,
+    names: Vec<Option<String>>,
+    // Rows are 8-byte aligned, and visitors write through shared references.
+    data: Vec<Cell<u64>>,
+    stride: usize,
+}
+
+impl ParamFile {
+    /// Parses a little endian PARAM file whose rows are laid out as `fields`.
+    fn parse(
+        data: &[u8],
+        fields: &'static [FieldMeta],
+        visit: &'static RowVisitor,
+    ) -> Result<Self, String> {
+        // Rounding up to 8 bytes covers the padding of the `repr(C)` struct.
+        let row_size = row_size(fields);
+        let stride = row_size.div_ceil(8);
+
+        if read::<1>(data, 0x2c)?[0] != 0 {
+            return Err("Big endian params are not supported".to_string());
+        }
+        let format_2d = read::<1>(data, 0x2d)?[0];
+        let format_2e = read::<1>(data, 0x2e)?[0];
+        let row_count = read_u16(data, 0x0a)? as usize;
+
+        let param_type = if format_2d & 0x80 != 0 {
+            read_cstr(data, read_u64(data, 0x10)? as usize)?
+        } else {
+            read_cstr(&data[..0x2c], 0x0c)?
+        };
+
+        let (rows_start, long_offsets) = match format_2d {
+            f if f & 0x06 != 0 => (0x40, f & 0x04 != 0),
+            f if f & 0x01 != 0 => (0x34, false),
+            _ => (0x30, false),
+        };
+
+        // The id, data offset and name offset of a row.
+        let row_header = |i: usize| -> Result<(u32, u64, u64), String> {
+            if long_offsets {
+                let offset = rows_start + i * 0x18;
+                Ok((
+                    read_u32(data, offset)?,
+                    read_u64(data, offset + 0x08)?,
+                    read_u64(data, offset + 0x10)?,
+                ))
+            } else {
+                let offset = rows_start + i * 0x0c;
+                let (data_offset, name_offset) =
+                    (read_u32(data, offset + 0x04)?, read_u32(data, offset + 0x08)?);
+                Ok((read_u32(data, offset)?, data_offset as u64, name_offset as u64))
+            }
+        };
+
+        // With an outdated paramdef, every field after the first difference
+        // would be read from the wrong place.
+        if row_count > 1 {
+            let file_row_size = row_header(1)?.1.wrapping_sub(row_header(0)?.1);
+            if file_row_size != row_size as u64 {
+                return Err(format!(
+                    "Rows of {file_row_size:#x} bytes don't match the paramdef's {row_size:#x}"
+                ));
+            }
+        }
+
+        let mut ids = Vec::with_capacity(row_count);
+        let mut names = Vec::with_capacity(row_count);
+        let mut rows = Vec::with_capacity(row_count * stride);
+
+        for i in 0..row_count {
+            let (id, data_offset, name_offset) = row_header(i)?;
+
+            let mut row = vec![0u8; stride * 8];
+            row[..row_size].copy_from_slice(read_bytes(data, data_offset as usize, row_size)?);
+            rows.extend(
+                row.chunks_exact(8).map(|b| Cell::new(u64::from_ne_bytes(b.try_into().unwrap()))),
+            );
+
+            let name = match name_offset as usize {
+                0 => None,
+                offset if format_2e & 0x01 != 0 => Some(read_wstr(data, offset)?),
+                offset => Some(read_cstr(data, offset)?),
+            };
+
+            ids.push(id as u64);
+            names.push(name.filter(|name| !name.is_empty()));
+        }
+
+        Ok(ParamFile { param_type, fields, visit, ids, names, data: rows, stride })
+    }
+
+    pub fn ids(&self) -> &[u64] {
+        &self.ids
+    }
+
+    /// The name of the row at `idx`, if the file has one.
+    pub fn name(&self, idx: usize) -> Option<&str> {
+        self.names.get(idx)?.as_deref()
+    }
+
+    pub fn visit_row(&self, idx: usize, visitor: &mut dyn ParamVisitor) {
+        if idx < self.ids.len() {
+            // Safety: the index is in bounds, and the row spans `stride` cells.
+            let ptr = unsafe { self.data.as_ptr().add(idx * self.stride) };
+            (self.visit)(ptr as *const c_void, visitor);
+        }
+    }
+
+    /// Collects all the rows, named as in the file or else in `PARAM_NAMES`.
+    pub fn export(&self, param: &str) -> Vec<ExportedRow> {
+        self.ids
+            .iter()
+            .enumerate()
+            .map(|(idx, &id)| {
+                let mut row = ExportedRow::collect(param, id, |v| self.visit_row(idx, v));
+                if let Some(name) = self.name(idx) {
+                    row.name = Some(name.to_string());
+                }
+                row
+            })
+            .collect()
+    }
+}
+
+/// The rows added, removed and changed from a regulation to another.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RegulationDiff {
+    pub added: Vec<DiffRow>,
+    pub removed: Vec<DiffRow>,
+    pub changed: Vec<FieldDiff>,
+}
+
+/// A row which was added or removed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DiffRow {
+    pub param: String,
+    #[serde(flatten)]
+    pub row: ExportedRow,
+}
+
+impl Regulation {
+    /// Compares the rows of each param, or only of `params` if not empty, by
+    /// row id.
+    pub fn diff(&self, newer: &Regulation, params: &[&str]) -> RegulationDiff {
+        let mut diff = RegulationDiff::default();
+
+        let names = self.keys().chain(newer.keys().filter(|param| !self.0.contains_key(*param)));
+        for param in names.filter(|param| params.is_empty() || params.contains(&param.as_str())) {
+            let old = self.0.get(param).map(|file| file.export(param)).unwrap_or_default();
+            let new = newer.0.get(param).map(|file| file.export(param)).unwrap_or_default();
+            let old_rows = old.iter().map(|row| (row.id, row)).collect::<BTreeMap<_, _>>();
+            let new_ids = new.iter().map(|row| row.id).collect::<HashSet<_>>();
+
+            diff.removed.extend(
+                old.iter()
+                    .filter(|row| !new_ids.contains(&row.id))
+                    .map(|row| DiffRow { param: param.clone(), row: row.clone() }),
+            );
+
+            for row in new {
+                let Some(old_row) = old_rows.get(&row.id) else {
+                    diff.added.push(DiffRow { param: param.clone(), row });
+                    continue;
+                };
+
+                diff.changed.extend(
+                    old_row
+                        .fields
+                        .iter()
+                        .zip(row.fields)
+                        .filter(|((_, original), (_, current))| original != current)
+                        .map(|((field, original), (_, current))| FieldDiff {
+                            param: param.clone(),
+                            id: row.id,
+                            field: field.clone(),
+                            original: original.clone(),
+                            current,
+                        }),
+                );
+            }
+        }
+
+        diff
+    }
+}
+
+/// Decrypts a `regulation.bin`; the first 16 bytes are the IV.
+pub fn decrypt_regulation(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 16 || !data.len().is_multiple_of(16) {
+        return Err(format!("Invalid encrypted length {:#x}", data.len()));
+    }
+
+    let (iv, data) = data.split_at(16);
+    let mut decrypted = data.to_vec();
+    let mut decryptor = Aes256CbcDec::new(&REGULATION_KEY.into(), iv.into());
+    for block in decrypted.chunks_exact_mut(16) {
+        decryptor.decrypt_block_mut(block.into());
+    }
+
+    Ok(decrypted)
+}
+
+/// Decompresses a `DFLT` DCX file. Its header is big endian.
+pub fn decompress_dcx(data: &[u8]) -> Result<Vec<u8>, String> {
+    if !data.starts_with(b"DCX\0") {
+        return Err("Not a DCX file".to_string());
+    }
+
+    let dcs_offset = read_u32_be(data, 0x08)? as usize;
+    let dcp_offset = read_u32_be(data, 0x0c)? as usize;
+    let dca_offset = read_u32_be(data, 0x10)? as usize;
+
+    if read_bytes(data, dcs_offset, 4)? != b"DCS\0"
+        || read_bytes(data, dcp_offset, 4)? != b"DCP\0"
+        || read_bytes(data, dca_offset, 4)? != b"DCA\0"
+    {
+        return Err("Invalid DCX header".to_string());
+    }
+
+    let compression = read_bytes(data, dcp_offset + 4, 4)?;
+    if compression != b"DFLT" {
+        return Err(format!(
+            "Unsupported DCX compression {}",
+            String::from_utf8_lossy(compression)
+        ));
+    }
+
+    let size = read_u32_be(data, dcs_offset + 4)? as usize;
+    let compressed_size = read_u32_be(data, dcs_offset + 8)? as usize;
+    let start = dca_offset + read_u32_be(data, dca_offset + 4)? as usize;
+
+    let mut decompressed = Vec::with_capacity(size);
+    ZlibDecoder::new(read_bytes(data, start, compressed_size)?)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| format!("Couldn't decompress DCX: {e}"))?;
+
+    if decompressed.len() != size {
+        return Err(format!("Expected {size:#x} bytes in DCX, found {:#x}", decompressed.len()));
+    }
+
+    Ok(decompressed)
+}
+
+/// Lists the names and contents of the files in a little endian BND4.
+pub fn read_bnd4(data: &[u8]) -> Result<Vec<(String, &[u8])>, String> {
+    if !data.starts_with(b"BND4") {
+        return Err("Not a BND4 file".to_string());
+    }
+    if read::<1>(data, 0x09)?[0] != 0 {
+        return Err("Big endian BND4 files are not supported".to_string());
+    }
+
+    let file_count = read_u32(data, 0x0c)? as usize;
+    let header_size = read_u64(data, 0x20)? as usize;
+    let unicode = read::<1>(data, 0x30)?[0] != 0;
+
+    // Flags are stored with their bits reversed, unless big endian.
+    let bit_big_endian = read::<1>(data, 0x0a)?[0] == 0;
+    let raw_format = read::<1>(data, 0x31)?[0];
+    let format = if bit_big_endian || (raw_format & 0x01 != 0 && raw_format & 0x80 == 0) {
+        raw_format
+    } else {
+        raw_format.reverse_bits()
+    };
+
+    if format & 0x0c == 0 {
+        return Err("BND4 without file names".to_string());
+    }
+
+    (0..file_count)
+        .map(|i| {
+            let mut offset = 0x40 + i * header_size + 0x08;
+            let size = read_u64(data, offset)? as usize;
+            offset += 8;
+            if format & 0x20 != 0 {
+                // Uncompressed size
+                offset += 8;
+            }
+            let data_offset = if format & 0x10 != 0 {
+                offset += 8;
+                read_u64(data, offset - 8)? as usize
+            } else {
+                offset += 4;
+                read_u32(data, offset - 4)? as usize
+            };
+            if format & 0x02 != 0 {
+                // ID
+                offset += 4;
+            }
+            let name_offset = read_u32(data, offset)? as usize;
+            let name =
+                if unicode { read_wstr(data, name_offset)? } else { read_cstr(data, name_offset)? };
+
+            Ok((name, read_bytes(data, data_offset, size)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::cipher::BlockEncryptMut;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use macro_param::ParamStruct;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(ParamStruct, Debug, Default)]
+    #[repr(C)]
+    struct RegulationRow {
+        hp: u32,
+        #[bitflag(is_enemy, 1)]
+        flags: u8,
+        scale: f32,
+        name: [u16; 3],
+        pad: [u8; 2],
+    }
+
+    fn visit_regulation_row(ptr: *const c_void, v: &mut dyn ParamVisitor) {
+        unsafe { (*(ptr as *mut RegulationRow)).visit(v) }
+    }
+
+    fn layout(param: &str) -> Option<(&'static [FieldMeta], &'static RowVisitor)> {
+        (param == "RegulationRow").then_some((RegulationRow::FIELDS, &visit_regulation_row))
+    }
+
+    fn fields(row: &ExportedRow) -> Vec<String> {
+        row.fields.iter().map(|(name, value)| format!("{name}={value}")).collect()
+    }
+
+    fn wstr(s: &str) -> Vec<u8> {
+        s.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect()
+    }
+
+    // An Elden Ring style PARAM: 64-bit offsets and UTF-16 row names.
+    fn build_param(rows: &[(u32, RegulationRow, &str)]) -> Vec<u8> {
+        let row_size = 0x14;
+        let data_start = 0x40 + rows.len() * 0x18;
+        let names_start = data_start + rows.len() * row_size;
+
+        let mut param = vec![0u8; 0x40];
+        param[0x0a..0x0c].copy_from_slice(&(rows.len() as u16).to_le_bytes());
+        param[0x0c..0x1a].copy_from_slice(b"REGULATION_ROW");
+        param[0x2d] = 0x07;
+        param[0x2e] = 0x01;
+
+        let mut data = Vec::new();
+        let mut names = Vec::new();
+        for (i, (id, row, name)) in rows.iter().enumerate() {
+            param.extend(id.to_le_bytes());
+            param.extend([0; 4]);
+            param.extend(((data_start + i * row_size) as u64).to_le_bytes());
+            param.extend(((names_start + names.len()) as u64).to_le_bytes());
+
+            data.extend(row.hp.to_le_bytes());
+            data.extend([row.flags, 0, 0, 0]);
+            data.extend(row.scale.to_le_bytes());
+            data.extend(row.name.iter().flat_map(|c| c.to_le_bytes()));
+            data.extend([0; 2]);
+            names.extend(wstr(name));
+        }
+
+        param[0..4].copy_from_slice(&(names_start as u32).to_le_bytes());
+        param.extend(data);
+        param.extend(names);
+        param
+    }
+
+    fn build_bnd4(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let header_size = 0x24;
+        let names_start = 0x40 + files.len() * header_size;
+        let names = files.iter().map(|(name, _)| wstr(name)).collect::<Vec<_>>();
+        let mut data_offset = names_start + names.iter().map(Vec::len).sum::<usize>();
+        let mut name_offset = names_start;
+
+        let mut bnd = b"BND4".to_vec();
+        bnd.extend([0, 0, 0, 0, 0, 0, 1, 0]);
+        bnd.extend((files.len() as u32).to_le_bytes());
+        bnd.extend(0x40u64.to_le_bytes());
+        bnd.extend(b"00000000");
+        bnd.extend((header_size as u64).to_le_bytes());
+        bnd.extend((data_offset as u64).to_le_bytes());
+        // Unicode, and IDs | Names1 | Names2 | Compression with reversed bits.
+        bnd.extend([1, 0x74, 0, 0, 0, 0, 0, 0]);
+        bnd.extend([0; 8]);
+
+        for (i, (_, file)) in files.iter().enumerate() {
+            bnd.extend([0x40, 0, 0, 0]);
+            bnd.extend((-1i32).to_le_bytes());
+            bnd.extend((file.len() as u64).to_le_bytes());
+            bnd.extend((file.len() as u64).to_le_bytes());
+            bnd.extend((data_offset as u32).to_le_bytes());
+            bnd.extend((i as u32).to_le_bytes());
+            bnd.extend((name_offset as u32).to_le_bytes());
+            data_offset += file.len();
+            name_offset += names[i].len();
+        }
+
+        bnd.extend(names.concat());
+        bnd.extend(files.iter().flat_map(|(_, file)| file.iter().copied()));
+        bnd
+    }
+
+    fn build_dcx(data: &[u8], compression: &[u8; 4]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        std::io::Write::write_all(&mut encoder, data).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut dcx = b"DCX\0".to_vec();
+        for v in [0x11000u32, 0x18, 0x24, 0x44, 0x4c] {
+            dcx.extend(v.to_be_bytes());
+        }
+        dcx.extend(b"DCS\0");
+        dcx.extend((data.len() as u32).to_be_bytes());
+        dcx.extend((compressed.len() as u32).to_be_bytes());
+        dcx.extend(b"DCP\0");
+        dcx.extend(compression);
+        for v in [0x20u32, 0x09000000, 0, 0, 0, 0x00010100] {
+            dcx.extend(v.to_be_bytes());
+        }
+        dcx.extend(b"DCA\0");
+        dcx.extend(8u32.to_be_bytes());
+        dcx.extend(compressed);
+        dcx
+    }
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let iv = [0x42u8; 16];
+        let mut encrypted = data.to_vec();
+        encrypted.resize(data.len().div_ceil(16) * 16, 0);
+        let mut encryptor = cbc::Encryptor::<aes::Aes256>::new(&REGULATION_KEY.into(), &iv.into());
+        for block in encrypted.chunks_exact_mut(16) {
+            encryptor.encrypt_block_mut(block.into());
+        }
+        [iv.to_vec(), encrypted].concat()
+    }
+
+    fn row(hp: u32, flags: u8, scale: f32, name: &str) -> RegulationRow {
+        let mut row = RegulationRow { hp, flags, scale, ..Default::default() };
+        row.name.copy_from_slice(&encode_wstr(name, 3).unwrap());
+        row
+    }
+
+    fn regulation_bin(rows: &[(u32, RegulationRow, &str)]) -> Vec<u8> {
+        let param = build_param(rows);
+        let bnd = build_bnd4(&[
+            ("N:\\GR\\data\\Param\\param\\GameParam\\RegulationRow.param", param),
+            ("N:\\GR\\data\\Param\\param\\GameParam\\Unknown.param", vec![0; 0x40]),
+        ]);
+        encrypt(&build_dcx(&bnd, b"DFLT"))
+    }
+
+    #[test]
+    fn test_regulation() {
+        let regulation = Regulation::parse_with(
+            &regulation_bin(&[
+                (10, row(100, 0b10, 1.5, "abc"), "First"),
+                (20, row(200, 0, 0.5, "d"), ""),
+            ]),
+            layout,
+        )
+        .unwrap();
+
+        assert_eq!(regulation.keys().collect::<Vec<_>>(), ["RegulationRow"]);
+
+        let param = regulation.get("RegulationRow").unwrap();
+        assert_eq!(param.param_type, "REGULATION_ROW");
+        assert_eq!(param.ids(), [10, 20]);
+        assert_eq!((param.name(0), param.name(1), param.name(2)), (Some("First"), None, None));
+
+        let rows = param.export("RegulationRow");
+        assert_eq!(fields(&rows[0]), [
+            "hp=100",
+            "is_enemy=true",
+            "scale=1.5",
+            "name=abc",
+            "pad=0000"
+        ]);
+        let row = ExportedRow::collect("RegulationRow", 20, |v| {
+            regulation.visit_row_idx("RegulationRow", 1, v);
+        });
+        assert_eq!(fields(&row), ["hp=200", "is_enemy=false", "scale=0.5", "name=d", "pad=0000"]);
+
+        // Visitors write to the rows.
+        param.visit_row(0, &mut FieldSetter::new([("hp", "101")]));
+        assert_eq!(fields(&param.export("RegulationRow")[0])[0], "hp=101");
+    }
+
+    #[test]
+    fn test_history() {
+        let regulation =
+            Regulation::parse_with(&regulation_bin(&[(10, row(100, 0, 1.0, "a"), "")]), layout)
+                .unwrap();
+
+        let mut history = ParamHistory::default();
+        history.edit(&regulation, "RegulationRow", 10, &mut FieldSetter::new([("hp", "150")]));
+        assert_eq!(
+            history
+                .diff(&regulation)
+                .iter()
+                .map(|d| format!(
+                    "{} {} {}: {} -> {}",
+                    d.param, d.id, d.field, d.original, d.current
+                ))
+                .collect::<Vec<_>>(),
+            ["RegulationRow 10 hp: 100 -> 150"]
+        );
+
+        history.undo(&regulation);
+        assert!(history.diff(&regulation).is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = Regulation::parse_with(
+            &regulation_bin(&[(10, row(100, 0, 1.0, "a"), ""), (20, row(200, 0, 1.0, "b"), "")]),
+            layout,
+        )
+        .unwrap();
+        let new = Regulation::parse_with(
+            &regulation_bin(&[(10, row(150, 0b10, 1.0, "a"), ""), (30, row(300, 0, 1.0, "c"), "")]),
+            layout,
+        )
+        .unwrap();
+
+        let diff = old.diff(&new, &[]);
+        assert_eq!(diff.removed.iter().map(|d| d.row.id).collect::<Vec<_>>(), [20]);
+        assert_eq!(diff.added.iter().map(|d| d.row.id).collect::<Vec<_>>(), [30]);
+        assert_eq!(
+            diff.changed
+                .iter()
+                .map(|d| format!(
+                    "{} {} {}: {} -> {}",
+                    d.param, d.id, d.field, d.original, d.current
+                ))
+                .collect::<Vec<_>>(),
+            ["RegulationRow 10 hp: 100 -> 150", "RegulationRow 10 is_enemy: false -> true"]
+        );
+
+        assert_eq!(old.diff(&new, &["Other"]), RegulationDiff::default());
+    }
+
+    #[test]
+    fn test_errors() {
+        let bnd = build_bnd4(&[]);
+        assert!(read_bnd4(&bnd).unwrap().is_empty());
+        assert_eq!(
+            decompress_dcx(&build_dcx(&bnd, b"KRAK")).err(),
+            Some("Unsupported DCX compression KRAK".to_string())
+        );
+        assert_eq!(
+            Regulation::parse(&[0; 15]).err(),
+            Some("Invalid encrypted length 0xf".to_string())
+        );
+
+        let param = build_param(&[(1, RegulationRow::default(), "")]);
+        assert_eq!(
+            ParamFile::parse(&param[..0x60], RegulationRow::FIELDS, &visit_regulation_row).err(),
+            Some("Unexpected end of data at 0x58".to_string())
+        );
+
+        // The second row starts 0x10 bytes after the first one.
+        let mut param =
+            build_param(&[(1, RegulationRow::default(), ""), (2, RegulationRow::default(), "")]);
+        let data_offset = u64::from_le_bytes(param[0x48..0x50].try_into().unwrap());
+        param[0x60..0x68].copy_from_slice(&(data_offset + 0x10).to_le_bytes());
+        assert_eq!(
+            ParamFile::parse(&param, RegulationRow::FIELDS, &visit_regulation_row).err(),
+            Some("Rows of 0x10 bytes don't match the paramdef's 0x14".to_string())
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Finds the param rows holding a value, e.g. the users of a SpEffect id, and
+//! follows the references between params.
+
+use std::ops::RangeInclusive;
+
+use serde::Serialize;
+
+use super::{RowAccess, PARAM_NAMES};
+use crate::ParamVisitor;
+
+/// A value or range of values to look for, in any field or in a named one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchQuery {
+    pub field: Option<String>,
+    pub range: RangeInclusive<f64>,
+}
+
+impl SearchQuery {
+    /// Parses `value` or `min..max`, optionally prefixed by `field=`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (field, value) = match s.split_once('=') {
+            Some((field, value)) => (Some(field.trim().to_string()), value),
+            None => (None, s),
+        };
+
+        let parse = |v: &str| v.trim().parse::<f64>().map_err(|_| format!("Invalid value: {v}"));
+        let range = match value.split_once("..") {
+            Some((min, max)) => parse(min)?..=parse(max)?,
+            None => {
+                let v = parse(value)?;
+                v..=v
+            },
+        };
+
+        Ok(SearchQuery { field: field.filter(|f| !f.is_empty()), range })
+    }
+}
+
+/// A field holding a searched value.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub param: String,
+    pub id: u64,
+    /// The index of the row in its param.
+    pub idx: usize,
+    pub name: Option<String>,
+    pub field: String,
+}
+
+struct SearchVisitor<'a> {
+    range: &'a RangeInclusive<f64>,
+    // All the fields when `None`.
+    fields: Option<Vec<&'a str>>,
+    flags: bool,
+    hits: Vec<String>,
+}
+
+impl SearchVisitor<'_> {
+    fn is_searched(&self, name: &str) -> bool {
+        match &self.fields {
+            // Array elements are visited as `name[i]`.
+            Some(fields) => fields.contains(&name.split_once('[').map(|(n, _)| n).unwrap_or(name)),
+            None => true,
+        }
+    }
+
+    fn check(&mut self, name: &str, v: f64) {
+        if self.is_searched(name) && self.range.contains(&v) {
+            self.hits.push(name.to_string());
+        }
+    }
+}
+
+impl ParamVisitor for SearchVisitor<'_> {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.check(name, *v as f64);
+    }
+
+    // Compared at f32 precision, so that searching 0.1 finds 0.1f32.
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        let (min, max) = (*self.range.start() as f32, *self.range.end() as f32);
+        if self.is_searched(name) && (min..=max).contains(v) {
+            self.hits.push(name.to_string());
+        }
+    }
+
+    fn visit_u64(&mut self, name: &str, v: &mut u64) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_i64(&mut self, name: &str, v: &mut i64) {
+        self.check(name, *v as f64);
+    }
+
+    fn visit_f64(&mut self, name: &str, v: &mut f64) {
+        self.check(name, *v);
+    }
+
+    // Flags only match when named, or any search for 0 or 1 would list them.
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        if self.flags {
+            self.check(name, *v as u8 as f64);
+        }
+    }
+
+    fn visit_bytes(&mut self, _: &str, _: &mut [u8]) {}
+
+    fn visit_wstr(&mut self, _: &str, _: &mut [u16]) {}
+}
+
+/// Searches every row of every param, stopping after `limit` hits.
+pub fn search_params(rows: &impl RowAccess, query: &SearchQuery, limit: usize) -> Vec<SearchHit> {
+    search_rows(rows, limit, |_| {
+        Some(SearchVisitor {
+            range: &query.range,
+            fields: query.field.as_deref().map(|field| vec![field]),
+            flags: query.field.is_some(),
+            hits: Vec::new(),
+        })
+    })
+}
+
+/// Finds the fields that reference the row `id` of `param`, according to their
+/// Paramdex refs.
+pub fn find_references(
+    rows: &impl RowAccess,
+    param: &str,
+    id: u64,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let range = id as f64..=id as f64;
+    search_rows(rows, limit, |other| {
+        let fields = rows
+            .fields(other)
+            .iter()
+            .filter(|field| field.refs.contains(&param))
+            .map(|field| field.name)
+            .collect::<Vec<_>>();
+
+        (!fields.is_empty()).then(|| SearchVisitor {
+            range: &range,
+            fields: Some(fields),
+            flags: false,
+            hits: Vec::new(),
+        })
+    })
+}
+
+/// Finds the row `id` in the first of `params` that has it, returning the
+/// param and the index of the row.
+pub fn find_row(rows: &impl RowAccess, params: &[&str], id: u64) -> Option<(String, usize)> {
+    let row_ids = rows.row_ids();
+    params.iter().find_map(|param| {
+        let (param, ids) = row_ids.iter().find(|(p, _)| p == param)?;
+        Some((param.clone(), ids.iter().position(|&i| i == id)?))
+    })
+}
+
+// Visits every row of the params for which `visitor` gives a visitor.
+fn search_rows<'a>(
+    rows: &impl RowAccess,
+    limit: usize,
+    visitor: impl Fn(&str) -> Option<SearchVisitor<'a>>,
+) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+
+    for (param, ids) in rows.row_ids() {
+        let Some(mut visitor) = visitor(&param) else {
+            continue;
+        };
+        let names = PARAM_NAMES.get(&param);
+
+        for (idx, id) in ids.into_iter().enumerate() {
+            rows.visit_row_idx(&param, idx, &mut visitor);
+
+            for field in visitor.hits.drain(..) {
+                if hits.len() >= limit {
+                    return hits;
+                }
+                hits.push(SearchHit {
+                    param: param.clone(),
+                    id,
+                    idx,
+                    name: names.and_then(|names| names.get(&(id as usize))).map(String::from),
+                    field,
+                });
+            }
+        }
+    }
+
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use macro_param::ParamStruct;
+
+    use super::*;
+    use crate::params::fake::FakeRows;
+    use crate::prelude::*;
+
+    #[derive(ParamStruct, Debug, Default, Clone)]
+    #[repr(C)]
+    struct SearchRow {
+        #[paramdex(refs = "B, C")]
+        sp_effect_id: i32,
+        sp_effect_ids: [i32; 2],
+        speed: f32,
+        #[bitflag(is_enabled, 0)]
+        flags: u8,
+    }
+
+    fn params() -> FakeRows<SearchRow> {
+        let row = |sp_effect_id, sp_effect_ids, speed, flags| SearchRow {
+            sp_effect_id,
+            sp_effect_ids,
+            speed,
+            flags,
+        };
+
+        FakeRows::new([
+            ("A", vec![(10, row(100, [0, 0], 0.1, 0)), (20, row(0, [100, 101], 1.0, 1))]),
+            ("B", vec![(30, row(150, [0, 0], 0.0, 1))]),
+        ])
+    }
+
+    fn hits(query: &str) -> Vec<(String, u64, usize, String)> {
+        search_params(&params(), &SearchQuery::parse(query).unwrap(), 100)
+            .into_iter()
+            .map(|hit| (hit.param, hit.id, hit.idx, hit.field))
+            .collect()
+    }
+
+    fn hit(param: &str, id: u64, idx: usize, field: &str) -> (String, u64, usize, String) {
+        (param.to_string(), id, idx, field.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(SearchQuery::parse("100").unwrap(), SearchQuery {
+            field: None,
+            range: 100.0..=100.0
+        });
+        assert_eq!(SearchQuery::parse("sp_effect_id = -5..5").unwrap(), SearchQuery {
+            field: Some("sp_effect_id".to_string()),
+            range: -5.0..=5.0
+        });
+        assert!(SearchQuery::parse("abc").is_err());
+        assert!(SearchQuery::parse("1..").is_err());
+    }
+
+    #[test]
+    fn test_search() {
+        assert_eq!(hits("100"), vec![
+            hit("A", 10, 0, "sp_effect_id"),
+            hit("A", 20, 1, "sp_effect_ids[0]"),
+        ]);
+        assert_eq!(hits("100..200"), vec![
+            hit("A", 10, 0, "sp_effect_id"),
+            hit("A", 20, 1, "sp_effect_ids[0]"),
+            hit("A", 20, 1, "sp_effect_ids[1]"),
+            hit("B", 30, 0, "sp_effect_id"),
+        ]);
+        assert_eq!(hits("sp_effect_ids=101"), vec![hit("A", 20, 1, "sp_effect_ids[1]")]);
+        assert_eq!(hits("0.1"), vec![hit("A", 10, 0, "speed")]);
+
+        // Flags only match when named.
+        assert!(!hits("1").iter().any(|(.., field)| field == "is_enabled"));
+        assert_eq!(hits("is_enabled=1"), vec![
+            hit("A", 20, 1, "is_enabled"),
+            hit("B", 30, 0, "is_enabled")
+        ]);
+
+        let query = SearchQuery::parse("0").unwrap();
+        assert_eq!(search_params(&params(), &query, 3).len(), 3);
+    }
+
+    #[test]
+    fn test_references() {
+        let params = params();
+
+        let refs = |param, id| {
+            find_references(&params, param, id, 100)
+                .into_iter()
+                .map(|hit| (hit.param, hit.id, hit.idx, hit.field))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(refs("B", 100), vec![hit("A", 10, 0, "sp_effect_id")]);
+        assert_eq!(refs("C", 150), vec![hit("B", 30, 0, "sp_effect_id")]);
+        // Not a reference to A.
+        assert!(refs("A", 100).is_empty());
+
+        assert_eq!(find_row(&params, &["C", "B", "A"], 30), Some(("B".to_string(), 0)));
+        assert_eq!(find_row(&params, &["B", "A"], 20), Some(("A".to_string(), 1)));
+        assert_eq!(find_row(&params, &["B"], 20), None);
+    }
+
+    // Runs on the generated params, which only get their refs from a codegen
+    // run with a Paramdex checkout.
+    #[test]
+    #[ignore = "param_data.rs has not been regenerated with the Paramdex attributes yet"]
+    fn test_paramdex_refs() {
+        let refs = |field: &str| FieldMeta::find(EquipParamWeapon::FIELDS, field).unwrap().refs;
+
+        assert!(refs("sp_effect_behavior_id0").contains(&"SpEffectParam"));
+        assert!(refs("resident_sp_effect_id").contains(&"SpEffectParam"));
+        assert!(refs("origin_equip_wep").contains(&"EquipParamWeapon"));
+        for i in 1..16 {
+            assert!(refs(&format!("origin_equip_wep{i}")).contains(&"EquipParamWeapon"));
+        }
+        assert!(!refs("behavior_variation_id").is_empty());
+
+        // Any reference found through them names an existing param.
+        for field in EquipParamWeapon::FIELDS {
+            for param in field.refs {
+                assert!(PARAM_VTABLE.contains_key(*param), "{}: {param}", field.name);
+            }
+        }
+    }
+}
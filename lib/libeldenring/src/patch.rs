@@ -0,0 +1,242 @@
+//! Byte patches to the game's code. Each patch is only applied if the bytes in
+//! memory are the expected ones, and applied patches are tracked so that they
+//! can be reverted, e.g. before ejecting.
+
+use std::fmt::Display;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::backend::{InProcess, MemoryBackend};
+
+/// The patches applied to the current process.
+pub static PATCHES: Lazy<Mutex<Patches>> = Lazy::new(|| Mutex::new(Patches::new()));
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch {
+    pub name: &'static str,
+    pub address: usize,
+    pub original: Vec<u8>,
+    pub replacement: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatchStatus {
+    /// The replacement bytes were written.
+    Applied,
+    /// The replacement bytes were already in memory, e.g. written by another
+    /// module. The patch won't be reverted.
+    AlreadyApplied,
+    /// The bytes in memory were neither the original nor the replacement ones.
+    Mismatch(Vec<u8>),
+    /// The memory couldn't be read or written.
+    Failed,
+    /// The original bytes were restored.
+    Reverted,
+}
+
+impl PatchStatus {
+    fn is_applied(&self) -> bool {
+        matches!(self, PatchStatus::Applied | PatchStatus::AlreadyApplied)
+    }
+}
+
+impl Display for PatchStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchStatus::Applied => write!(f, "applied"),
+            PatchStatus::AlreadyApplied => write!(f, "already applied"),
+            PatchStatus::Mismatch(found) => write!(f, "unexpected bytes {found:02x?}"),
+            PatchStatus::Failed => write!(f, "memory access failed"),
+            PatchStatus::Reverted => write!(f, "reverted"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Patches<B: MemoryBackend = InProcess> {
+    backend: B,
+    patches: Vec<(Patch, PatchStatus)>,
+}
+
+impl Patches {
+    pub fn new() -> Self {
+        Self::with_backend(InProcess)
+    }
+}
+
+impl Default for Patches {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: MemoryBackend> Patches<B> {
+    pub fn with_backend(backend: B) -> Self {
+        Patches { backend, patches: Vec::new() }
+    }
+
+    /// Verifies that the memory at the patch's address holds the original
+    /// bytes and writes the replacement. Applying a patch that is already
+    /// applied at the same address does nothing.
+    ///
+    /// Retrying a patch that failed or was reverted replaces it in the list.
+    pub fn apply(&mut self, patch: Patch) -> Result<(), String> {
+        if self.patches.iter().any(|(p, status)| p.address == patch.address && status.is_applied())
+        {
+            return Ok(());
+        }
+
+        let status = self.apply_status(&patch);
+        let result = match &status {
+            PatchStatus::Applied | PatchStatus::AlreadyApplied => Ok(()),
+            status => Err(format!("Couldn't apply patch \"{}\": {status}", patch.name)),
+        };
+
+        match self
+            .patches
+            .iter_mut()
+            .find(|(p, status)| p.name == patch.name && !status.is_applied())
+        {
+            Some(entry) => *entry = (patch, status),
+            None => self.patches.push((patch, status)),
+        }
+        result
+    }
+
+    fn apply_status(&self, patch: &Patch) -> PatchStatus {
+        let mut current = vec![0u8; patch.original.len()];
+        if self.backend.read(patch.address, &mut current).is_none() {
+            return PatchStatus::Failed;
+        }
+
+        if current == patch.replacement {
+            PatchStatus::AlreadyApplied
+        } else if current != patch.original {
+            PatchStatus::Mismatch(current)
+        } else if self.backend.write(patch.address, &patch.replacement).is_none() {
+            PatchStatus::Failed
+        } else {
+            PatchStatus::Applied
+        }
+    }
+
+    /// Restores the original bytes of all the patches that were applied, as
+    /// long as the replacement bytes are still in memory.
+    pub fn revert_all(&mut self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        for (patch, status) in self.patches.iter_mut().rev() {
+            if *status != PatchStatus::Applied {
+                continue;
+            }
+
+            let mut current = vec![0u8; patch.replacement.len()];
+            *status = if self.backend.read(patch.address, &mut current).is_none() {
+                PatchStatus::Failed
+            } else if current != patch.replacement {
+                PatchStatus::Mismatch(current)
+            } else if self.backend.write(patch.address, &patch.original).is_none() {
+                PatchStatus::Failed
+            } else {
+                PatchStatus::Reverted
+            };
+
+            if *status != PatchStatus::Reverted {
+                errors.push(format!("Couldn't revert patch \"{}\": {status}", patch.name));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Patch, &PatchStatus)> {
+        self.patches.iter().map(|(patch, status)| (patch, status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryImage;
+
+    fn patch(address: usize) -> Patch {
+        Patch { name: "test", address, original: vec![0x74, 0x53], replacement: vec![0x90, 0x90] }
+    }
+
+    #[test]
+    fn test_apply_revert() {
+        let image = MemoryImage::from_bytes(0x1000, vec![0xcc, 0x74, 0x53, 0xcc, 0x74, 0x53]);
+        let mut patches = Patches::with_backend(image.clone());
+
+        patches.apply(patch(0x1001)).unwrap();
+        patches.apply(patch(0x1004)).unwrap();
+        // Applying twice doesn't track the patch again.
+        patches.apply(patch(0x1001)).unwrap();
+        assert_eq!(image.snapshot(), vec![0xcc, 0x90, 0x90, 0xcc, 0x90, 0x90]);
+        assert_eq!(patches.iter().count(), 2);
+        assert!(patches.iter().all(|(_, status)| *status == PatchStatus::Applied));
+
+        patches.revert_all().unwrap();
+        assert_eq!(image.snapshot(), vec![0xcc, 0x74, 0x53, 0xcc, 0x74, 0x53]);
+        assert!(patches.iter().all(|(_, status)| *status == PatchStatus::Reverted));
+    }
+
+    #[test]
+    fn test_verify() {
+        let image = MemoryImage::from_bytes(0x1000, vec![0x90, 0x90, 0x12, 0x34]);
+        let mut patches = Patches::with_backend(image.clone());
+
+        // Patched by someone else: tracked, but left alone on revert.
+        patches.apply(patch(0x1000)).unwrap();
+        assert!(patches.apply(Patch { name: "mismatch", ..patch(0x1002) }).is_err());
+        assert!(patches.apply(Patch { name: "unmapped", ..patch(0x2000) }).is_err());
+        assert_eq!(image.snapshot(), vec![0x90, 0x90, 0x12, 0x34]);
+
+        let statuses = patches.iter().map(|(_, status)| status.clone()).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![
+            PatchStatus::AlreadyApplied,
+            PatchStatus::Mismatch(vec![0x12, 0x34]),
+            PatchStatus::Failed
+        ]);
+
+        patches.revert_all().unwrap();
+        assert_eq!(image.snapshot(), vec![0x90, 0x90, 0x12, 0x34]);
+    }
+
+    #[test]
+    fn test_retry() {
+        let image = MemoryImage::from_bytes(0x1000, vec![0x12, 0x34]);
+        let mut patches = Patches::with_backend(image.clone());
+
+        assert!(patches.apply(patch(0x1000)).is_err());
+        assert!(patches.apply(patch(0x1000)).is_err());
+        assert_eq!(patches.iter().count(), 1);
+
+        image.write(0x1000, &[0x74, 0x53]).unwrap();
+        patches.apply(patch(0x1000)).unwrap();
+        patches.revert_all().unwrap();
+        patches.apply(patch(0x1000)).unwrap();
+
+        let statuses = patches.iter().map(|(_, status)| status.clone()).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![PatchStatus::Applied]);
+        assert_eq!(image.snapshot(), vec![0x90, 0x90]);
+    }
+
+    #[test]
+    fn test_revert_modified() {
+        let image = MemoryImage::from_bytes(0x1000, vec![0x74, 0x53]);
+        let mut patches = Patches::with_backend(image.clone());
+
+        patches.apply(patch(0x1000)).unwrap();
+        image.write(0x1000, &[0xeb, 0xfe]).unwrap();
+
+        assert!(patches.revert_all().is_err());
+        assert_eq!(image.snapshot(), vec![0xeb, 0xfe]);
+        assert_eq!(patches.iter().next().unwrap().1, &PatchStatus::Mismatch(vec![0xeb, 0xfe]));
+    }
+}
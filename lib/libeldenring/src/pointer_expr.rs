@@ -0,0 +1,179 @@
+//! Pointer chains written as text, so that they can be given in config files
+//! instead of being added to [`Pointers`](crate::pointers::Pointers).
+//!
+//! An expression is a base address followed by offsets, like
+//! `WorldChrMan -> 0x1E508 -> 0x190 + 0x68`. The base is the name of a
+//! [`BaseAddresses`] field, either as in the struct (`world_chr_man`) or as
+//! in the signatures (`WorldChrMan`). Each `->` reads a pointer, to which the
+//! offsets that follow are added. Offsets are decimal or `0x` hexadecimal
+//! numbers, and can be subtracted with `-` as long as the sum is positive.
+//! Only the base address can be offset by a negative amount.
+
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::codegen::base_addresses::BaseAddresses;
+use crate::memedit::{MemoryBackend, PointerChain};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct PointerExpr {
+    base: String,
+    // The first offset is added to the base address, the others to the
+    // pointers read along the chain.
+    offsets: Vec<isize>,
+}
+
+impl PointerExpr {
+    /// Builds the pointer chain, with the base address looked up in
+    /// `base_addresses`.
+    pub fn resolve<T, B: MemoryBackend>(
+        &self,
+        backend: B,
+        base_addresses: &BaseAddresses,
+    ) -> Result<PointerChain<T, B>, String> {
+        let base = base_addresses
+            .get(&self.base)
+            .ok_or_else(|| format!("\"{}\" is not a valid base address", self.base))?;
+
+        let mut chain = self.offsets.iter().map(|&offset| offset as usize).collect::<Vec<_>>();
+        chain[0] = base.wrapping_add_signed(self.offsets[0]);
+
+        Ok(PointerChain::with_backend(backend, &chain))
+    }
+}
+
+impl FromStr for PointerExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = s.split("->").map(terms);
+
+        let mut first = segments.next().unwrap()?.into_iter();
+        let base = match first.next() {
+            Some((1, name)) if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') => {
+                if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(format!("\"{name}\" is not a valid base address"));
+                }
+                name.to_string()
+            },
+            _ => return Err(format!("\"{s}\" doesn't start with a base address")),
+        };
+
+        let mut offsets = vec![sum(first)?];
+        for segment in segments {
+            let offset = sum(segment?.into_iter())?;
+            // Pointer chains add their offsets without wrapping.
+            if offset < 0 {
+                return Err(format!("Negative offset after a pointer in \"{s}\""));
+            }
+            offsets.push(offset);
+        }
+
+        Ok(PointerExpr { base, offsets })
+    }
+}
+
+impl TryFrom<String> for PointerExpr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+// Splits a segment of an expression into signed terms.
+fn terms(segment: &str) -> Result<Vec<(isize, &str)>, String> {
+    let mut terms = Vec::new();
+    let mut sign = 1;
+    let mut rest = segment;
+
+    loop {
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let term = rest[..end].trim();
+        if term.is_empty() {
+            return Err(format!("Missing offset in \"{}\"", segment.trim()));
+        }
+        terms.push((sign, term));
+
+        match rest.as_bytes().get(end) {
+            Some(b'+') => sign = 1,
+            Some(_) => sign = -1,
+            None => break,
+        }
+        rest = &rest[end + 1..];
+    }
+
+    Ok(terms)
+}
+
+fn sum<'a>(terms: impl Iterator<Item = (isize, &'a str)>) -> Result<isize, String> {
+    terms.into_iter().try_fold(0isize, |acc, (sign, term)| {
+        let value = match term.strip_prefix("0x").or_else(|| term.strip_prefix("0X")) {
+            Some(hex) => isize::from_str_radix(hex, 16),
+            None => term.parse(),
+        }
+        .map_err(|e| format!("\"{term}\" is not a valid offset: {e}"))?;
+
+        acc.checked_add(sign * value).ok_or_else(|| format!("Offset overflow at \"{term}\""))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::base_addresses::Version;
+    use crate::memedit::MemoryImage;
+
+    #[test]
+    fn test_parse() {
+        let expr: PointerExpr = "WorldChrMan -> 0x1E508 -> 0x190 + 0x68".parse().unwrap();
+        assert_eq!(expr, PointerExpr {
+            base: "WorldChrMan".to_string(),
+            offsets: vec![0, 0x1E508, 0x1F8]
+        });
+
+        let expr: PointerExpr = "chr_dbg_flags+5".parse().unwrap();
+        assert_eq!(expr, PointerExpr { base: "chr_dbg_flags".to_string(), offsets: vec![5] });
+
+        let expr: PointerExpr = "GroupMask - 0x10 + 2 -> 16 - 0x1".parse().unwrap();
+        assert_eq!(expr.offsets, vec![-14, 15]);
+        assert!("GroupMask -> 1 - 2".parse::<PointerExpr>().is_err());
+
+        assert!("0x10 -> 0x8".parse::<PointerExpr>().is_err());
+        assert!("WorldChrMan ->".parse::<PointerExpr>().is_err());
+        assert!("WorldChrMan -> 0x8 + WorldChrMan".parse::<PointerExpr>().is_err());
+        assert!("WorldChrMan -> 0xZZ".parse::<PointerExpr>().is_err());
+        assert!("World.ChrMan".parse::<PointerExpr>().is_err());
+        assert!("".parse::<PointerExpr>().is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let base_addresses =
+            BaseAddresses { world_chr_man: 0x1000, ..BaseAddresses::from(Version::V2_06_0) };
+
+        // WorldChrMan points to 0x1040, which points to 0x1080.
+        let image = MemoryImage::new(0x1000, 0x100);
+        image.write(0x1000, &0x1040usize.to_ne_bytes()).unwrap();
+        image.write(0x1048, &0x1080usize.to_ne_bytes()).unwrap();
+
+        let expr: PointerExpr = "WorldChrMan -> 0x8 -> 0x10 + 0x4".parse().unwrap();
+        let chain = expr.resolve::<u32, _>(image.clone(), &base_addresses).unwrap();
+        assert_eq!(chain.eval(), Some(0x1094 as *mut u32));
+
+        chain.write(42).unwrap();
+        let mut buf = [0u8; 4];
+        image.read(0x1094, &mut buf).unwrap();
+        assert_eq!(u32::from_ne_bytes(buf), 42);
+
+        // The field name works as well.
+        let expr: PointerExpr = "world_chr_man + 0x10".parse().unwrap();
+        let chain = expr.resolve::<u8, _>(image.clone(), &base_addresses).unwrap();
+        assert_eq!(chain.eval(), Some(0x1010 as *mut u8));
+
+        let expr: PointerExpr = "NoSuchMan -> 0x8".parse().unwrap();
+        assert!(expr.resolve::<u8, _>(image, &base_addresses).is_err());
+    }
+}
@@ -2,104 +2,106 @@
 
 use std::fmt::Display;
 
+#[cfg(windows)]
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
 
 use crate::memedit::*;
+use crate::offsets::Offsets;
 use crate::prelude::base_addresses::BaseAddresses;
-use crate::prelude::Version;
-use crate::version;
+#[cfg(windows)]
+use crate::{offsets, version};
 
 #[derive(Debug)]
-pub struct Pointers {
-    pub one_shot: Bitflag<u8>,
-    pub no_damage: Bitflag<u8>,
-    pub no_dead: Bitflag<u8>,
-    pub no_hit: Bitflag<u8>,
-    pub no_goods_consume: Bitflag<u8>,
-    pub no_stamina_consume: Bitflag<u8>,
-    pub no_fp_consume: Bitflag<u8>,
-    pub no_arrows_consume: Bitflag<u8>,
-    pub no_attack: Bitflag<u8>,
-    pub no_move: Bitflag<u8>,
-    pub no_update_ai: Bitflag<u8>,
-    pub no_trigger_event: Bitflag<u8>,
-    pub no_ashes_of_war_fp_consume: Bitflag<u8>,
-
-    pub collision: Bitflag<u8>,
-
-    pub torrent_no_dead: Bitflag<u8>,
-    pub torrent_gravity: Bitflag<u8>,
-    pub torrent_collision: Bitflag<u8>,
-
-    pub all_no_dead: Bitflag<u8>,
-
-    pub current_target: PointerChain<u64>,
-
-    pub character_stats: PointerChain<CharacterStats>,
-    pub character_points: PointerChain<CharacterPoints>,
-    pub character_blessings: Option<PointerChain<CharacterBlessings>>,
-
-    pub runes: PointerChain<u32>,
-    pub igt: PointerChain<usize>,
-
-    pub fps: PointerChain<f32>,
-
-    pub runearc: Bitflag<u8>,
-
-    pub cur_anim: PointerChain<u32>,
-    pub cur_anim_time: PointerChain<f32>,
-    pub cur_anim_length: PointerChain<f32>,
-
-    pub weapon_hitbox1: Bitflag<u8>, // debug sphere 1
-    pub weapon_hitbox2: Bitflag<u8>, // debug sphere 2
-    pub weapon_hitbox3: Bitflag<u8>, // damipoli
-
-    pub quitout: PointerChain<u8>,
-    pub cursor_show: Bitflag<u8>,
-
-    pub gravity: Bitflag<u8>,
-    pub display_stable_pos: Bitflag<u8>,
-    pub global_position: Position,
-    pub stable_position: Position,
-    pub chunk_position: Position,
-    pub torrent_chunk_position: Position,
-    pub animation_speed: PointerChain<f32>,
-    pub torrent_animation_speed: PointerChain<f32>,
+pub struct Pointers<B: MemoryBackend = InProcess> {
+    pub one_shot: Bitflag<u8, B>,
+    pub no_damage: Bitflag<u8, B>,
+    pub no_dead: Bitflag<u8, B>,
+    pub no_hit: Bitflag<u8, B>,
+    pub no_goods_consume: Bitflag<u8, B>,
+    pub no_stamina_consume: Bitflag<u8, B>,
+    pub no_fp_consume: Bitflag<u8, B>,
+    pub no_arrows_consume: Bitflag<u8, B>,
+    pub no_attack: Bitflag<u8, B>,
+    pub no_move: Bitflag<u8, B>,
+    pub no_update_ai: Bitflag<u8, B>,
+    pub no_trigger_event: Bitflag<u8, B>,
+    pub no_ashes_of_war_fp_consume: Bitflag<u8, B>,
+
+    pub collision: Bitflag<u8, B>,
+
+    pub torrent_no_dead: Bitflag<u8, B>,
+    pub torrent_gravity: Bitflag<u8, B>,
+    pub torrent_collision: Bitflag<u8, B>,
+
+    pub all_no_dead: Bitflag<u8, B>,
+
+    pub current_target: PointerChain<u64, B>,
+
+    pub character_stats: PointerChain<CharacterStats, B>,
+    pub character_points: PointerChain<CharacterPoints, B>,
+    pub character_blessings: Option<PointerChain<CharacterBlessings, B>>,
+
+    pub runes: PointerChain<u32, B>,
+    pub igt: PointerChain<usize, B>,
+
+    pub fps: PointerChain<f32, B>,
+
+    pub runearc: Bitflag<u8, B>,
+
+    pub cur_anim: PointerChain<u32, B>,
+    pub cur_anim_time: PointerChain<f32, B>,
+    pub cur_anim_length: PointerChain<f32, B>,
+
+    pub weapon_hitbox1: Bitflag<u8, B>, // debug sphere 1
+    pub weapon_hitbox2: Bitflag<u8, B>, // debug sphere 2
+    pub weapon_hitbox3: Bitflag<u8, B>, // damipoli
+
+    pub quitout: PointerChain<u8, B>,
+    pub cursor_show: Bitflag<u8, B>,
+
+    pub gravity: Bitflag<u8, B>,
+    pub display_stable_pos: Bitflag<u8, B>,
+    pub global_position: Position<B>,
+    pub stable_position: Position<B>,
+    pub chunk_position: Position<B>,
+    pub torrent_chunk_position: Position<B>,
+    pub animation_speed: PointerChain<f32, B>,
+    pub torrent_animation_speed: PointerChain<f32, B>,
 
     // CSLuaEventManager
     pub func_warp: usize,
-    pub warp1: PointerChain<u64>,
-    pub warp2: PointerChain<u64>,
+    pub warp1: PointerChain<u64, B>,
+    pub warp2: PointerChain<u64, B>,
 
-    pub deathcam: (Bitflag<u8>, Bitflag<u8>, PointerChain<u8>),
+    pub deathcam: (Bitflag<u8, B>, Bitflag<u8, B>, PointerChain<u8, B>),
 
     // HitIns
-    pub hitbox_high: Bitflag<u8>,
-    pub hitbox_low: Bitflag<u8>,
-    pub hitbox_f: Bitflag<u8>,
-    pub hitbox_character: Bitflag<u8>,
+    pub hitbox_high: Bitflag<u8, B>,
+    pub hitbox_low: Bitflag<u8, B>,
+    pub hitbox_f: Bitflag<u8, B>,
+    pub hitbox_character: Bitflag<u8, B>,
 
-    pub hitbox_event: Bitflag<u8>,
+    pub hitbox_event: Bitflag<u8, B>,
 
-    pub mesh_color: PointerChain<i32>,
+    pub mesh_color: PointerChain<i32, B>,
 
     // FieldArea
-    pub field_area_direction: Bitflag<u8>,
-    pub field_area_altimeter: Bitflag<u8>,
-    pub field_area_compass: Bitflag<u8>,
+    pub field_area_direction: Bitflag<u8, B>,
+    pub field_area_altimeter: Bitflag<u8, B>,
+    pub field_area_compass: Bitflag<u8, B>,
 
     // GroupMask
-    // pub show_map: Bitflag<u8>,
-    pub show_geom: Vec<Bitflag<u8>>,
-    pub show_chr: Bitflag<u8>,
+    // pub show_map: Bitflag<u8, B>,
+    pub show_geom: Vec<Bitflag<u8, B>>,
+    pub show_chr: Bitflag<u8, B>,
 
     // Functions
     pub func_item_spawn: usize,
     pub func_item_inject: usize,
-    pub func_dbg_action_force: PointerChain<u8>,
+    pub func_dbg_action_force: PointerChain<u8, B>,
     pub func_dbg_action_force_state_values: (u8, u8),
-    pub show_all_map_layers: Bitflag<u8>,
-    pub show_all_graces: Bitflag<u8>,
+    pub show_all_map_layers: Bitflag<u8, B>,
+    pub show_all_graces: Bitflag<u8, B>,
 
     pub base_addresses: BaseAddresses,
 }
@@ -109,16 +111,16 @@ pub struct Pointers {
 
 /// Encodes the position vector and two rotation angles.
 #[derive(Debug, Clone)]
-pub struct Position {
-    pub x: PointerChain<f32>,
-    pub y: PointerChain<f32>,
-    pub z: PointerChain<f32>,
-    pub angle1: PointerChain<f32>,
-    pub angle2: PointerChain<f32>,
-    pub map_id: Option<PointerChain<u32>>,
+pub struct Position<B: MemoryBackend = InProcess> {
+    pub x: PointerChain<f32, B>,
+    pub y: PointerChain<f32, B>,
+    pub z: PointerChain<f32, B>,
+    pub angle1: PointerChain<f32, B>,
+    pub angle2: PointerChain<f32, B>,
+    pub map_id: Option<PointerChain<u32, B>>,
 }
 
-impl Position {
+impl<B: MemoryBackend> Position<B> {
     pub fn read(&self) -> Option<[f32; 5]> {
         match (self.x.read(), self.y.read(), self.z.read(), self.angle1.read(), self.angle2.read())
         {
@@ -205,15 +207,23 @@ impl Display for CharacterBlessings {
     }
 }
 
+#[cfg(windows)]
 impl Pointers {
     pub fn new() -> Self {
         let version = version::get_version();
-        use Version::*;
 
         let base_module_address = unsafe { GetModuleHandleA(None).unwrap() }.0 as usize;
         let base_addresses =
-            BaseAddresses::from(version).with_module_base_addr(base_module_address);
+            version::get_base_addresses().with_module_base_addr(base_module_address);
 
+        Self::with_backend(InProcess, &offsets::get_offsets(version), base_addresses)
+    }
+}
+
+impl<B: MemoryBackend> Pointers<B> {
+    /// Builds the pointer chains with the offsets of a game version. The base
+    /// addresses must already be relocated to the module's base address.
+    pub fn with_backend(backend: B, offsets: &Offsets, base_addresses: BaseAddresses) -> Self {
         let BaseAddresses {
             chr_dbg_flags,
             cs_menu_man_imp,
@@ -235,202 +245,116 @@ impl Pointers {
             ..
         } = base_addresses;
 
-        // Special cases
-
-        let map_id_offset = {
-            match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6c8,
-                V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => 0x6c0,
-                V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3
-                | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x6d0,
-            }
-        };
-
-        let global_position_offset = {
-            match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6b8,
-                V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => 0x6b0,
-                V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3
-                | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x6c0,
-            }
-        };
-
-        let group_mask = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 => group_mask,
-            V1_05_0 => group_mask - 8,
-            V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1
-            | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => group_mask,
-        };
-
-        let show_geom = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0
-            | V2_00_1 | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => {
-                vec![
-                    bitflag!(0b1; group_mask + 2),
-                    bitflag!(0b1; group_mask + 3),
-                    bitflag!(0b1; group_mask + 4),
-                    bitflag!(0b1; group_mask + 5),
-                    bitflag!(0b1; group_mask + 6),
-                    bitflag!(0b1; group_mask + 7),
-                    bitflag!(0b1; group_mask + 8),
-                    bitflag!(0b1; group_mask),
-                    bitflag!(0b1; group_mask + 0xa),
-                    bitflag!(0b1; group_mask + 0xb),
-                    bitflag!(0b1; group_mask + 0xc),
-                    bitflag!(0b1; group_mask + 0xd),
-                    bitflag!(0b1; group_mask + 0xf),
-                    bitflag!(0b1; group_mask + 0x10),
-                    bitflag!(0b1; group_mask + 0x11),
-                    bitflag!(0b1; group_mask + 0x12),
-                ]
-            },
-            V1_05_0 => vec![
-                bitflag!(0b1; group_mask),
-                bitflag!(0b1; group_mask + 1),
-                bitflag!(0b1; group_mask + 2),
-                bitflag!(0b1; group_mask + 3),
-                bitflag!(0b1; group_mask + 5),
-                bitflag!(0b1; group_mask + 6),
-                bitflag!(0b1; group_mask + 8),
-                bitflag!(0b1; group_mask + 0xa),
-                bitflag!(0b1; group_mask + 0xb),
-                bitflag!(0b1; group_mask + 0xc),
-                bitflag!(0b1; group_mask + 0xd),
-                bitflag!(0b1; group_mask + 0xe),
-                bitflag!(0b1; group_mask + 0xf),
-            ],
-        };
-
-        let show_chr = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0
-            | V2_00_1 | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => {
-                bitflag!(0b1; group_mask + 0xe)
-            },
-            V1_05_0 => bitflag!(0b1; group_mask + 4),
-        };
-
-        let net_players_ins = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_05_0 | V1_06_0 => 0xB658,
-            V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0
-            | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x10EF8,
-        };
-
-        let player_ins = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_05_0 | V1_06_0 => 0x18468,
-            V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0
-            | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x1E508,
-        };
-
-        let torrent_enemy_ins = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_05_0 => 0x18390,
-            V1_06_0 => 0x18378,
-            V1_07_0 => 0x1E1A0,
-            V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 => 0x1e1b8,
-            V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x1cc90,
-        };
+        let Offsets {
+            map_id: map_id_offset,
+            global_position: global_position_offset,
+            display_stable_pos,
+            net_players_ins,
+            player_ins,
+            torrent_enemy_ins,
+            group_mask_delta,
+            show_geom,
+            show_chr,
+            character_blessings,
+            func_dbg_action_force: func_dbg_action_force_state_values,
+        } = offsets.clone();
+
+        let group_mask = group_mask.wrapping_add_signed(group_mask_delta);
+
+        let show_geom = show_geom
+            .into_iter()
+            .map(|offset| bitflag!(0b1; backend => group_mask + offset))
+            .collect();
+        let show_chr = bitflag!(0b1; backend => group_mask + show_chr);
 
         // TODO 1.08.x
         // - show stable position is broken
         Self {
-            one_shot: bitflag!(0b1; chr_dbg_flags + 0x3),
-            no_damage: bitflag!(0b1; chr_dbg_flags + 0xC),
-            no_dead: bitflag!(0b1; chr_dbg_flags + 0x1),
-            no_hit: bitflag!(0b1; chr_dbg_flags + 0xD),
-            no_goods_consume: bitflag!(0b1; chr_dbg_flags + 0x4),
-            no_stamina_consume: bitflag!(0b1; chr_dbg_flags + 0x5),
-            no_fp_consume: bitflag!(0b1; chr_dbg_flags + 0x6),
-            no_arrows_consume: bitflag!(0b1; chr_dbg_flags + 0x7),
-            no_attack: bitflag!(0b1; chr_dbg_flags + 0xE),
-            no_move: bitflag!(0b1; chr_dbg_flags + 0xF),
-            no_update_ai: bitflag!(0b1; chr_dbg_flags + 0x10),
-            no_trigger_event: bitflag!(0b1; dbg_event_man_off, 0x28),
-            no_ashes_of_war_fp_consume: bitflag!(0b1; chr_dbg_flags + 0x12),
-
-            all_no_dead: bitflag!(0b1; chr_dbg_flags + 0xB),
-
-            torrent_no_dead: bitflag!(0b1; chr_dbg_flags + 0x2),
-            torrent_gravity: bitflag!(0b1; world_chr_man, torrent_enemy_ins, 0x18, 0, 0x190, 0x68, 0x1d3),
+            one_shot: bitflag!(0b1; backend => chr_dbg_flags + 0x3),
+            no_damage: bitflag!(0b1; backend => chr_dbg_flags + 0xC),
+            no_dead: bitflag!(0b1; backend => chr_dbg_flags + 0x1),
+            no_hit: bitflag!(0b1; backend => chr_dbg_flags + 0xD),
+            no_goods_consume: bitflag!(0b1; backend => chr_dbg_flags + 0x4),
+            no_stamina_consume: bitflag!(0b1; backend => chr_dbg_flags + 0x5),
+            no_fp_consume: bitflag!(0b1; backend => chr_dbg_flags + 0x6),
+            no_arrows_consume: bitflag!(0b1; backend => chr_dbg_flags + 0x7),
+            no_attack: bitflag!(0b1; backend => chr_dbg_flags + 0xE),
+            no_move: bitflag!(0b1; backend => chr_dbg_flags + 0xF),
+            no_update_ai: bitflag!(0b1; backend => chr_dbg_flags + 0x10),
+            no_trigger_event: bitflag!(0b1; backend => dbg_event_man_off, 0x28),
+            no_ashes_of_war_fp_consume: bitflag!(0b1; backend => chr_dbg_flags + 0x12),
+
+            all_no_dead: bitflag!(0b1; backend => chr_dbg_flags + 0xB),
+
+            torrent_no_dead: bitflag!(0b1; backend => chr_dbg_flags + 0x2),
+            torrent_gravity: bitflag!(0b1; backend => world_chr_man, torrent_enemy_ins, 0x18, 0, 0x190, 0x68, 0x1d3),
 
             // WorldChrMan -> Player
-            collision: bitflag!(0b1000; world_chr_man, player_ins, 0x58, 0xf0),
+            collision: bitflag!(0b1000; backend => world_chr_man, player_ins, 0x58, 0xf0),
 
             // WorldChrMan -> Torrent
-            torrent_collision: bitflag!(0b1000; world_chr_man, torrent_enemy_ins, 0x18, 0, 0x58, 0xf0),
+            torrent_collision: bitflag!(0b1000; backend => world_chr_man, torrent_enemy_ins, 0x18, 0, 0x58, 0xf0),
 
-            character_stats: pointer_chain!(game_data_man, 0x8, 0x3c),
+            character_stats: pointer_chain!(backend => game_data_man, 0x8, 0x3c),
             character_points: pointer_chain!(
-                world_chr_man,
+                backend => world_chr_man,
                 net_players_ins,
                 0, // 0 * 0x10, first net player
                 0x190,
                 0,
                 0x138
             ),
-            character_blessings: match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-                | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1
-                | V2_00_0 | V2_00_1 => None,
-                V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => {
-                    Some(pointer_chain!(game_data_man, 0x8, 0xfc))
-                },
-            },
-            runes: pointer_chain!(game_data_man, 0x8, 0x6C),
-            igt: pointer_chain!(game_data_man, 0xA0),
-
-            fps: pointer_chain!(base_fps, 0x98, 0x8, 0x770),
-
-            cur_anim: pointer_chain!(base_anim, 0x0, 0x190, 0x18, 0x20),
-            cur_anim_time: pointer_chain!(base_anim, 0x0, 0x190, 0x18, 0x24),
-            cur_anim_length: pointer_chain!(base_anim, 0x0, 0x190, 0x18, 0x2C),
-
-            runearc: bitflag!(0b1; game_data_man, 0x8, 0xFF),
-
-            quitout: pointer_chain!(cs_menu_man_imp, 0x8, 0x5d),
-            cursor_show: bitflag!(0b1; cs_menu_man_imp, 0xAC),
-            gravity: bitflag!(0b1; world_chr_man, player_ins, 0x190, 0x68, 0x1d3),
-            display_stable_pos: bitflag!(0b1; world_chr_man, player_ins,
-                match version {
-                    V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6FD,
-                    V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => 0x6F5,
-                    V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1  | V2_00_0| V2_00_1 | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0  => 0x735
-                }
-            ),
+            character_blessings: (!character_blessings.is_empty()).then(|| {
+                PointerChain::with_backend(
+                    backend.clone(),
+                    &[&[game_data_man][..], &character_blessings].concat(),
+                )
+            }),
+            runes: pointer_chain!(backend => game_data_man, 0x8, 0x6C),
+            igt: pointer_chain!(backend => game_data_man, 0xA0),
+
+            fps: pointer_chain!(backend => base_fps, 0x98, 0x8, 0x770),
+
+            cur_anim: pointer_chain!(backend => base_anim, 0x0, 0x190, 0x18, 0x20),
+            cur_anim_time: pointer_chain!(backend => base_anim, 0x0, 0x190, 0x18, 0x24),
+            cur_anim_length: pointer_chain!(backend => base_anim, 0x0, 0x190, 0x18, 0x2C),
+
+            runearc: bitflag!(0b1; backend => game_data_man, 0x8, 0xFF),
+
+            quitout: pointer_chain!(backend => cs_menu_man_imp, 0x8, 0x5d),
+            cursor_show: bitflag!(0b1; backend => cs_menu_man_imp, 0xAC),
+            gravity: bitflag!(0b1; backend => world_chr_man, player_ins, 0x190, 0x68, 0x1d3),
+            display_stable_pos: bitflag!(0b1; backend => world_chr_man, player_ins, display_stable_pos),
             global_position: Position {
-                x: pointer_chain!(world_chr_man, player_ins, global_position_offset),
-                y: pointer_chain!(world_chr_man, player_ins, global_position_offset + 0x4),
-                z: pointer_chain!(world_chr_man, player_ins, global_position_offset + 0x8),
-                angle1: pointer_chain!(world_chr_man, player_ins, 0x6bc),
-                angle2: pointer_chain!(world_chr_man, player_ins, 0x6cc),
-                map_id: Some(pointer_chain!(world_chr_man, player_ins, map_id_offset)),
+                x: pointer_chain!(backend => world_chr_man, player_ins, global_position_offset),
+                y: pointer_chain!(backend => world_chr_man, player_ins, global_position_offset + 0x4),
+                z: pointer_chain!(backend => world_chr_man, player_ins, global_position_offset + 0x8),
+                angle1: pointer_chain!(backend => world_chr_man, player_ins, 0x6bc),
+                angle2: pointer_chain!(backend => world_chr_man, player_ins, 0x6cc),
+                map_id: Some(pointer_chain!(backend => world_chr_man, player_ins, map_id_offset)),
             },
             stable_position: Position {
-                x: pointer_chain!(world_chr_man, player_ins, global_position_offset + 0x14),
-                y: pointer_chain!(world_chr_man, player_ins, global_position_offset + 0x18),
-                z: pointer_chain!(world_chr_man, player_ins, global_position_offset + 0x1C),
-                angle1: pointer_chain!(world_chr_man, player_ins, 0x6d8),
-                angle2: pointer_chain!(world_chr_man, player_ins, 0x6e8),
+                x: pointer_chain!(backend => world_chr_man, player_ins, global_position_offset + 0x14),
+                y: pointer_chain!(backend => world_chr_man, player_ins, global_position_offset + 0x18),
+                z: pointer_chain!(backend => world_chr_man, player_ins, global_position_offset + 0x1C),
+                angle1: pointer_chain!(backend => world_chr_man, player_ins, 0x6d8),
+                angle2: pointer_chain!(backend => world_chr_man, player_ins, 0x6e8),
                 map_id: None,
             },
             chunk_position: Position {
-                x: pointer_chain!(world_chr_man, player_ins, 0x190, 0x68, 0x70),
-                y: pointer_chain!(world_chr_man, player_ins, 0x190, 0x68, 0x74),
-                z: pointer_chain!(world_chr_man, player_ins, 0x190, 0x68, 0x78),
-                angle1: pointer_chain!(world_chr_man, player_ins, 0x190, 0x68, 0x54),
-                angle2: pointer_chain!(world_chr_man, player_ins, 0x190, 0x68, 0x64),
-                map_id: Some(pointer_chain!(world_chr_man, player_ins, map_id_offset)),
+                x: pointer_chain!(backend => world_chr_man, player_ins, 0x190, 0x68, 0x70),
+                y: pointer_chain!(backend => world_chr_man, player_ins, 0x190, 0x68, 0x74),
+                z: pointer_chain!(backend => world_chr_man, player_ins, 0x190, 0x68, 0x78),
+                angle1: pointer_chain!(backend => world_chr_man, player_ins, 0x190, 0x68, 0x54),
+                angle2: pointer_chain!(backend => world_chr_man, player_ins, 0x190, 0x68, 0x64),
+                map_id: Some(pointer_chain!(backend => world_chr_man, player_ins, map_id_offset)),
             },
             torrent_chunk_position: Position {
-                x: pointer_chain!(world_chr_man, torrent_enemy_ins, 0x18, 0x0, 0x190, 0x68, 0x70),
-                y: pointer_chain!(world_chr_man, torrent_enemy_ins, 0x18, 0x0, 0x190, 0x68, 0x74),
-                z: pointer_chain!(world_chr_man, torrent_enemy_ins, 0x18, 0x0, 0x190, 0x68, 0x78),
+                x: pointer_chain!(backend => world_chr_man, torrent_enemy_ins, 0x18, 0x0, 0x190, 0x68, 0x70),
+                y: pointer_chain!(backend => world_chr_man, torrent_enemy_ins, 0x18, 0x0, 0x190, 0x68, 0x74),
+                z: pointer_chain!(backend => world_chr_man, torrent_enemy_ins, 0x18, 0x0, 0x190, 0x68, 0x78),
                 angle1: pointer_chain!(
-                    world_chr_man,
+                    backend => world_chr_man,
                     torrent_enemy_ins,
                     0x18,
                     0x0,
@@ -439,7 +363,7 @@ impl Pointers {
                     0x54
                 ),
                 angle2: pointer_chain!(
-                    world_chr_man,
+                    backend => world_chr_man,
                     torrent_enemy_ins,
                     0x18,
                     0x0,
@@ -448,16 +372,16 @@ impl Pointers {
                     0x64
                 ),
                 map_id: Some(pointer_chain!(
-                    world_chr_man,
+                    backend => world_chr_man,
                     torrent_enemy_ins,
                     0x18,
                     0x0,
                     map_id_offset
                 )),
             },
-            animation_speed: pointer_chain!(world_chr_man, player_ins, 0x190, 0x28, 0x17C8),
+            animation_speed: pointer_chain!(backend => world_chr_man, player_ins, 0x190, 0x28, 0x17C8),
             torrent_animation_speed: pointer_chain!(
-                world_chr_man,
+                backend => world_chr_man,
                 torrent_enemy_ins,
                 0x18,
                 0,
@@ -467,43 +391,77 @@ impl Pointers {
             ),
 
             deathcam: (
-                bitflag!(0b100; world_chr_man, player_ins, 0x1c8),
-                bitflag!(0b100; world_chr_man, torrent_enemy_ins, 0x18, 0, 0x1c8),
-                pointer_chain!(field_area, 0x98, 0x7c),
+                bitflag!(0b100; backend => world_chr_man, player_ins, 0x1c8),
+                bitflag!(0b100; backend => world_chr_man, torrent_enemy_ins, 0x18, 0, 0x1c8),
+                pointer_chain!(backend => field_area, 0x98, 0x7c),
             ),
 
-            field_area_direction: bitflag!(0b1; field_area + 0x9),
-            field_area_altimeter: bitflag!(0b1; field_area + 0xA),
-            field_area_compass: bitflag!(0b1; field_area + 0xB),
-            weapon_hitbox1: bitflag!(0b1; damage_ctrl, 0xA0),
-            weapon_hitbox2: bitflag!(0b1; damage_ctrl, 0xA1),
-            weapon_hitbox3: bitflag!(0b1; damage_ctrl, 0xA4),
-            hitbox_high: bitflag!(0b1; hit_ins_hitbox_offset),
-            hitbox_low: bitflag!(0b1; hit_ins_hitbox_offset + 0x1),
-            hitbox_f: bitflag!(0b1; hit_ins_hitbox_offset + 0x4),
-            hitbox_character: bitflag!(0b1; hit_ins_hitbox_offset + 0x3),
-            hitbox_event: bitflag!(0b1; dbg_event_man_off, 0x4),
-            mesh_color: pointer_chain!(hit_ins_hitbox_offset + 0x8),
+            field_area_direction: bitflag!(0b1; backend => field_area + 0x9),
+            field_area_altimeter: bitflag!(0b1; backend => field_area + 0xA),
+            field_area_compass: bitflag!(0b1; backend => field_area + 0xB),
+            weapon_hitbox1: bitflag!(0b1; backend => damage_ctrl, 0xA0),
+            weapon_hitbox2: bitflag!(0b1; backend => damage_ctrl, 0xA1),
+            weapon_hitbox3: bitflag!(0b1; backend => damage_ctrl, 0xA4),
+            hitbox_high: bitflag!(0b1; backend => hit_ins_hitbox_offset),
+            hitbox_low: bitflag!(0b1; backend => hit_ins_hitbox_offset + 0x1),
+            hitbox_f: bitflag!(0b1; backend => hit_ins_hitbox_offset + 0x4),
+            hitbox_character: bitflag!(0b1; backend => hit_ins_hitbox_offset + 0x3),
+            hitbox_event: bitflag!(0b1; backend => dbg_event_man_off, 0x4),
+            mesh_color: pointer_chain!(backend => hit_ins_hitbox_offset + 0x8),
             show_geom,
             show_chr,
 
             func_warp: lua_warp + 2,
-            warp1: pointer_chain!(cs_lua_event_manager, 0x18),
-            warp2: pointer_chain!(cs_lua_event_manager, 0x08),
+            warp1: pointer_chain!(backend => cs_lua_event_manager, 0x18),
+            warp2: pointer_chain!(backend => cs_lua_event_manager, 0x08),
 
             func_item_spawn,
             func_item_inject,
-            func_dbg_action_force: pointer_chain!(base_addresses.func_dbg_action_force + 7),
-            func_dbg_action_force_state_values: match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-                | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => (0xB1, 0xB2),
-                V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3
-                | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => (0xC1, 0xC2),
-            },
-            current_target: pointer_chain!(current_target),
-            show_all_map_layers: bitflag!(0b1; func_check_graces),
-            show_all_graces: bitflag!(0b1; func_check_graces + 0x1),
+            func_dbg_action_force: pointer_chain!(backend => base_addresses.func_dbg_action_force + 7),
+            func_dbg_action_force_state_values,
+            current_target: pointer_chain!(backend => current_target),
+            show_all_map_layers: bitflag!(0b1; backend => func_check_graces),
+            show_all_graces: bitflag!(0b1; backend => func_check_graces + 0x1),
             base_addresses,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::base_addresses::BASE_ADDRESSES_2_06_0;
+    use crate::version::Version;
+
+    #[test]
+    fn test_player_position() {
+        let base_addresses = BASE_ADDRESSES_2_06_0.with_module_base_addr(0x140000000);
+        let world_chr_man = base_addresses.world_chr_man;
+
+        // WorldChrMan and the player instance, laid out after the static pointer.
+        let image = MemoryImage::new(world_chr_man, 0x30000);
+        let world_chr_man_ins = world_chr_man + 0x100;
+        let player_ins = world_chr_man + 0x20000;
+        image.write(world_chr_man, &world_chr_man_ins.to_ne_bytes()).unwrap();
+        image.write(world_chr_man_ins + 0x1E508, &player_ins.to_ne_bytes()).unwrap();
+
+        let pointers = Pointers::with_backend(
+            image.clone(),
+            &crate::offsets::get_offsets(Version::V2_06_0),
+            base_addresses,
+        );
+
+        pointers.global_position.write([1., 2., 3., 4., 5.]);
+        pointers.global_position.write_map_id(0x3c2c2400);
+
+        assert_eq!(pointers.global_position.read(), Some([1., 2., 3., 4., 5.]));
+        assert_eq!(pointers.global_position.read_map_id(), Some(0x3c2c2400));
+
+        let mut x = [0u8; 4];
+        image.read(player_ins + 0x6c0, &mut x).unwrap();
+        assert_eq!(f32::from_ne_bytes(x), 1.);
+
+        // The chunk position goes through pointers that are still null.
+        assert_eq!(pointers.chunk_position.read(), None);
+    }
+}
@@ -1,9 +1,109 @@
 use heck::AsSnakeCase;
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 use syn::*;
 
-#[proc_macro_derive(ParamStruct, attributes(bitflag))]
+/// Documentation of a field or bit flag from a `#[paramdex(...)]` attribute.
+#[derive(Default)]
+struct Paramdex {
+    offset: Option<LitInt>,
+    display_name: Option<LitStr>,
+    description: Option<LitStr>,
+    enum_type: Option<LitStr>,
+    refs: Vec<String>,
+}
+
+impl Paramdex {
+    /// Parses the attributes of a field, returning its documentation and the
+    /// documentation of its bit flags.
+    fn parse_field(field: &Field) -> (Self, Vec<(Ident, Self)>) {
+        let mut field_paramdex = Paramdex::default();
+        let mut flags = Vec::new();
+
+        for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("paramdex")) {
+            let meta_list = match attr.parse_meta() {
+                Ok(Meta::List(meta_list)) => meta_list,
+                other => panic!("Wrong paramdex attribute: {:#?}", other),
+            };
+
+            let mut flag = None;
+            let mut paramdex = Paramdex::default();
+            for nested in &meta_list.nested {
+                let (key, lit) = match nested {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) => {
+                        (path.get_ident().map(|i| i.to_string()).unwrap_or_default(), lit)
+                    },
+                    other => panic!("Wrong paramdex parameter: {:#?}", other),
+                };
+
+                match (key.as_str(), lit) {
+                    ("flag", Lit::Str(s)) => flag = Some(format_ident!("{}", s.value())),
+                    ("offset", Lit::Int(i)) => paramdex.offset = Some(i.clone()),
+                    ("display_name", Lit::Str(s)) => paramdex.display_name = Some(s.clone()),
+                    ("description", Lit::Str(s)) => paramdex.description = Some(s.clone()),
+                    ("enum_type", Lit::Str(s)) => paramdex.enum_type = Some(s.clone()),
+                    ("refs", Lit::Str(s)) => {
+                        paramdex.refs = s.value().split(',').map(|r| r.trim().to_string()).collect()
+                    },
+                    (other, _) => panic!("Unrecognized paramdex parameter {:#?}", other),
+                }
+            }
+
+            match flag {
+                Some(flag) => flags.push((flag, paramdex)),
+                None => field_paramdex = paramdex,
+            }
+        }
+
+        (field_paramdex, flags)
+    }
+
+    fn field_meta(
+        &self,
+        name: &Ident,
+        offset: &TokenStream2,
+        ty: &Type,
+        bit: Option<u8>,
+    ) -> TokenStream2 {
+        let opt = |s: &Option<LitStr>| match s {
+            Some(s) => quote!(Some(#s)),
+            None => quote!(None),
+        };
+        let display_name = opt(&self.display_name);
+        let description = opt(&self.description);
+        let enum_type = opt(&self.enum_type);
+        let refs = &self.refs;
+        let bit = match bit {
+            Some(bit) => quote!(Some(#bit)),
+            None => quote!(None),
+        };
+
+        quote! {
+            FieldMeta {
+                name: stringify!(#name),
+                offset: #offset,
+                size: ::std::mem::size_of::<#ty>(),
+                ty: stringify!(#ty),
+                bit: #bit,
+                display_name: #display_name,
+                description: #description,
+                enum_type: #enum_type,
+                refs: &[#(#refs),*],
+            }
+        }
+    }
+}
+
+/// Implements `ParamStruct` by visiting each field with the `ParamVisitor`
+/// method for its type. `u8` fields with `#[bitflag(name, bit)]` attributes are
+/// visited as booleans, `[u8; N]` as bytes, `[u16; N]` as UTF-16 strings and
+/// other arrays element by element.
+///
+/// `#[paramdex(...)]` attributes document a field, or one of its bit flags
+/// with `flag = "name"`. An `offset` is checked against the `repr(C)` layout at
+/// compile time.
+#[proc_macro_derive(ParamStruct, attributes(bitflag, paramdex))]
 pub fn macro_param(t: TokenStream) -> TokenStream {
     let input = parse_macro_input!(t as DeriveInput);
     let name = input.ident;
@@ -20,6 +120,7 @@ pub fn macro_param(t: TokenStream) -> TokenStream {
                 field
                     .attrs
                     .iter()
+                    .filter(|attr| !attr.path.is_ident("paramdex"))
                     .map(|attr| {
                         let meta_list = match attr.parse_meta() {
                             Ok(Meta::List(meta_list)) if meta_list.path.is_ident("bitflag") => {
@@ -80,7 +181,7 @@ pub fn macro_param(t: TokenStream) -> TokenStream {
 
     let field_visit = fields_with_bitfields
         .iter()
-        .filter_map(|(field, bitfield_spec)| match field {
+        .map(|(field, bitfield_spec)| match field {
             &Field { ident: Some(ident), ty: Type::Path(TypePath { path, .. }), .. } => {
                 let ty_ident = path.segments[0].ident.to_string();
                 match ty_ident.as_str() {
@@ -95,32 +196,54 @@ pub fn macro_param(t: TokenStream) -> TokenStream {
                             },
                         );
 
-                        Some(quote! {
+                        quote! {
                             #(#bitfield_visit)*
-                        })
+                        }
                     },
-                    "u8" | "u16" | "u32" | "i8" | "i16" | "i32" | "f32" => {
+                    "u8" | "u16" | "u32" | "i8" | "i16" | "i32" | "f32" | "u64" | "i64" | "f64" => {
                         let ident = format_ident!("{}", ident);
                         let visit_ty = format_ident!("visit_{}", ty_ident);
-                        Some(quote! {
+                        quote! {
                             t.#visit_ty(stringify!(#ident), &mut self.#ident);
-                        })
+                        }
                     },
                     other => panic!("Unrecognized type {:#?}", other),
                 }
             },
             &Field {
-                ident: Some(_),
+                ident: Some(ident),
                 ty:
                     Type::Array(TypeArray {
-                        elem: _,
+                        elem,
                         len: Expr::Lit(ExprLit { lit: Lit::Int(_), .. }),
                         ..
                     }),
                 ..
             } => {
-                // Just ignore array fields
-                None
+                let elem_ident = match elem.as_ref() {
+                    Type::Path(TypePath { path, .. }) => path.segments[0].ident.to_string(),
+                    other => panic!("Unrecognized array element {:#?}", other),
+                };
+                let ident = format_ident!("{}", ident);
+
+                match elem_ident.as_str() {
+                    "u8" => quote! {
+                        t.visit_bytes(stringify!(#ident), &mut self.#ident);
+                    },
+                    // The codegen maps `fixstrW` fields to `[u16; N]`.
+                    "u16" => quote! {
+                        t.visit_wstr(stringify!(#ident), &mut self.#ident);
+                    },
+                    "u32" | "i8" | "i16" | "i32" | "f32" | "u64" | "i64" | "f64" => {
+                        let visit_ty = format_ident!("visit_{}", elem_ident);
+                        quote! {
+                            for (i, v) in self.#ident.iter_mut().enumerate() {
+                                t.#visit_ty(&format!("{}[{}]", stringify!(#ident), i), v);
+                            }
+                        }
+                    },
+                    other => panic!("Unrecognized array element type {:#?}", other),
+                }
             },
             field => {
                 panic!("Unrecognized field {:#?}", field);
@@ -128,7 +251,38 @@ pub fn macro_param(t: TokenStream) -> TokenStream {
         })
         .collect::<Vec<_>>();
 
+    let mut field_metas = Vec::new();
+    let mut offset_checks = Vec::new();
+    for (field, bitfield_spec) in &fields_with_bitfields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let offset = quote!(::std::mem::offset_of!(#name, #ident));
+        let (field_paramdex, flags_paramdex) = Paramdex::parse_field(field);
+
+        if let Some(expected) = &field_paramdex.offset {
+            let msg = format!("{name}::{ident} is not at offset {expected}");
+            offset_checks.push(quote! {
+                const _: () = assert!(#offset == #expected, #msg);
+            });
+        }
+
+        if bitfield_spec.is_empty() {
+            field_metas.push(field_paramdex.field_meta(ident, &offset, ty, None));
+        }
+
+        for (bitfield_name, bit, ..) in bitfield_spec {
+            let paramdex = flags_paramdex
+                .iter()
+                .find(|(flag, _)| flag == bitfield_name)
+                .map(|(_, paramdex)| paramdex)
+                .unwrap_or(&field_paramdex);
+            field_metas.push(paramdex.field_meta(bitfield_name, &offset, ty, Some(*bit)));
+        }
+    }
+
     let visit = quote! {
+        const FIELDS: &'static [FieldMeta] = &[#(#field_metas),*];
+
         fn visit<T: ParamVisitor + ?Sized>(&mut self, t: &mut T) {
             #(#field_visit)*
         }
@@ -144,6 +298,8 @@ pub fn macro_param(t: TokenStream) -> TokenStream {
             #visit
         }
 
+        #(#offset_checks)*
+
         impl Params {
             pub unsafe fn #get_name_snake_case(&self) -> Option<impl Iterator<Item = Param<#name>>> {
                 self.iter_param::<#name>(stringify!(#name))
@@ -0,0 +1,158 @@
+//! Runs the derive over the structs of the example and checks what the
+//! generated `visit` does with each field.
+
+#[allow(dead_code)]
+#[path = "../examples/example.rs"]
+mod example;
+
+use example::*;
+
+/// Overwrites every field it visits.
+struct Filler;
+
+impl ParamVisitor for Filler {
+    fn visit_u8(&mut self, _: &str, v: &mut u8) {
+        *v = 1;
+    }
+
+    fn visit_u16(&mut self, _: &str, v: &mut u16) {
+        *v = 2;
+    }
+
+    fn visit_u32(&mut self, _: &str, v: &mut u32) {
+        *v = 3;
+    }
+
+    fn visit_i8(&mut self, _: &str, v: &mut i8) {
+        *v = -4;
+    }
+
+    fn visit_i16(&mut self, _: &str, v: &mut i16) {
+        *v = -5;
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        *v = name.len() as i32;
+    }
+
+    fn visit_f32(&mut self, _: &str, v: &mut f32) {
+        *v = 7.5;
+    }
+
+    fn visit_u64(&mut self, _: &str, v: &mut u64) {
+        *v = u64::MAX;
+    }
+
+    fn visit_i64(&mut self, _: &str, v: &mut i64) {
+        *v = i64::MIN;
+    }
+
+    fn visit_f64(&mut self, _: &str, v: &mut f64) {
+        *v = 0.1;
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        *v = name == "isConsume";
+    }
+
+    fn visit_bytes(&mut self, _: &str, v: &mut [u8]) {
+        v.fill(0xff);
+    }
+
+    fn visit_wstr(&mut self, _: &str, v: &mut [u16]) {
+        v.fill(0);
+        for (c, w) in "Ranni".encode_utf16().zip(v.iter_mut()) {
+            *w = c;
+        }
+    }
+}
+
+#[test]
+fn test_visit_order() {
+    let mut printer = Printer::default();
+    let mut npc = NpcName { text_id: 10, anim_ids: [1, 2], ..Default::default() };
+    npc.name[..2].copy_from_slice(&[0x49, 0x69]);
+    npc.visit(&mut printer);
+
+    assert_eq!(printer.0, [
+        "name = \"Ii\"",
+        "text_id = 10",
+        "offset = 0",
+        "scale = 0.0",
+        "anim_ids[0] = 1",
+        "anim_ids[1] = 2",
+        "unk = [0, 0, 0]",
+    ]);
+}
+
+#[test]
+fn test_bitflags() {
+    let mut printer = Printer::default();
+    let mut goods = EquipParamGoods { bitfield2: 0b1000_0001, ..Default::default() };
+    goods.visit(&mut printer);
+
+    // Bit fields are visited bit by bit, in place of the byte holding them.
+    let lines = &printer.0;
+    let start = lines.iter().position(|l| l == "replace_category = 0").unwrap() + 1;
+    assert_eq!(lines[start + 16], "enable_live = true");
+    assert_eq!(lines[start + 23], "isConsume = true");
+    assert!(!lines.iter().any(|l| l.starts_with("bitfield")));
+    assert!(lines.contains(&"pad2 = [0, 0, 0, 0, 0, 0, 0, 0]".to_string()));
+}
+
+#[test]
+fn test_write_through() {
+    let mut npc = NpcName::default();
+    npc.visit(&mut Filler);
+    assert_eq!(&npc.name[..6], &[0x52, 0x61, 0x6e, 0x6e, 0x69, 0]);
+    assert_eq!((npc.text_id, npc.offset, npc.scale), (u64::MAX, i64::MIN, 0.1));
+    // Array elements are visited with their index in the name.
+    assert_eq!(npc.anim_ids, ["anim_ids[0]".len() as i32; 2]);
+    assert_eq!(npc.unk, [0xff; 3]);
+
+    let mut goods = EquipParamGoods::default();
+    goods.visit(&mut Filler);
+    assert_eq!(goods.bitfield2, 0b1000_0000);
+    assert_eq!(goods.bitfield0, 0);
+    assert_eq!((goods.weight, goods.icon_id, goods.goods_use_anim), (7.5, -5, -4));
+    assert_eq!(goods.pad2, [0xff; 8]);
+}
+
+#[test]
+fn test_field_meta() {
+    let fields = EquipParamGoods::FIELDS;
+    let field = |name| fields.iter().find(|f| f.name == name).unwrap();
+
+    assert_eq!(field("weight"), &FieldMeta {
+        name: "weight",
+        offset: 8,
+        size: 4,
+        ty: "f32",
+        bit: None,
+        display_name: Some("Weight"),
+        description: Some("Weight [kg]"),
+        enum_type: None,
+        refs: &[],
+    });
+    assert_eq!(field("ref_id1").refs, ["BulletParam", "SpEffectParam"]);
+    assert_eq!(field("goods_type").enum_type, Some("GOODS_TYPE"));
+
+    // Bit flags share the offset of their byte.
+    let (enable_live, is_consume) = (field("enable_live"), field("isConsume"));
+    assert_eq!(
+        (enable_live.offset, enable_live.bit, enable_live.display_name),
+        (0x44, Some(0), None)
+    );
+    assert_eq!((is_consume.offset, is_consume.bit), (0x44, Some(7)));
+    assert_eq!(is_consume.display_name, Some("Consumable"));
+    assert!(!fields.iter().any(|f| f.name.starts_with("bitfield")));
+
+    // Fields are listed in the order they are visited.
+    let mut printer = Printer::default();
+    EquipParamGoods::default().visit(&mut printer);
+    let visited = printer.0.iter().map(|l| l.split(" = ").next().unwrap()).collect::<Vec<_>>();
+    assert_eq!(visited, fields.iter().map(|f| f.name).collect::<Vec<_>>());
+
+    let name = &NpcName::FIELDS[0];
+    assert_eq!((name.ty, name.size, NpcName::FIELDS[4].offset), ("[u16; 16]", 32, 56));
+}
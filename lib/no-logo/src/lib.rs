@@ -2,6 +2,7 @@ use std::ffi::c_void;
 use std::mem;
 use std::ptr::null_mut;
 
+use libeldenring::patch::{Patch, PATCHES};
 use libeldenring::prelude::*;
 use libeldenring::version;
 use once_cell::sync::Lazy;
@@ -10,9 +11,6 @@ use widestring::U16CString;
 use windows::core::*;
 use windows::Win32::Foundation::*;
 use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress, LoadLibraryW};
-use windows::Win32::System::Memory::{
-    VirtualProtect, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS,
-};
 use windows::Win32::System::SystemInformation::GetSystemDirectoryW;
 use windows::Win32::System::SystemServices::DLL_PROCESS_ATTACH;
 
@@ -68,19 +66,21 @@ fn dinput8_path() -> U16CString {
 
 unsafe fn apply_patch() {
     let module_base = GetModuleHandleW(PCWSTR(null_mut())).unwrap();
-    let Ok(version) = version::check_version() else {
+    if version::check_version().is_err() {
         return;
-    };
+    }
 
-    let offset = base_addresses::BaseAddresses::from(version).func_remove_intro_screens;
+    let offset = version::get_base_addresses().func_remove_intro_screens;
 
-    let ptr = (module_base.0 as usize + offset) as *mut [u8; 2];
-    let mut old = PAGE_PROTECTION_FLAGS(0);
-    if *ptr == [0x74, 0x53] {
-        VirtualProtect(ptr as _, 2, PAGE_EXECUTE_READWRITE, &mut old).ok();
-        (*ptr) = [0x90, 0x90];
-        VirtualProtect(ptr as _, 2, old, &mut old).ok();
-    }
+    PATCHES
+        .lock()
+        .apply(Patch {
+            name: "Remove intro screens",
+            address: module_base.0 as usize + offset,
+            original: vec![0x74, 0x53],
+            replacement: vec![0x90, 0x90],
+        })
+        .ok();
 }
 
 /// # Safety
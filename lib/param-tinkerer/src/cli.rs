@@ -0,0 +1,285 @@
+//! Queries the params of `regulation.bin` files, without the game running.
+
+use std::env;
+use std::fmt::Write;
+use std::process::ExitCode;
+
+use libeldenring::prelude::*;
+
+const USAGE: &str = r#"Usage: param-tinkerer-cli [--json] [--regulation <path>] <command>
+
+Commands:
+
+dump <param> ................ print all the rows of a param
+get <param> <row> ........... print the fields of a row
+search <query> .............. find the fields holding a value: `100`,
+                              `10..20` or `field=100`
+diff <old> <new> [param...] . compare two regulation files, e.g. only
+                              `EquipParamWeapon SpEffectParam`
+
+The regulation defaults to `regulation.bin` in the current directory.
+"#;
+
+const SEARCH_LIMIT: usize = 1000;
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1), |path| Regulation::open(path)) {
+        Ok(out) => {
+            print!("{out}");
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(
+    mut args: impl Iterator<Item = String>,
+    open: impl Fn(&str) -> Result<Regulation, String>,
+) -> Result<String, String> {
+    let mut json = false;
+    let mut regulation = "regulation.bin".to_string();
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--regulation" => {
+                regulation =
+                    args.next().ok_or_else(|| "Missing path for --regulation".to_string())?
+            },
+            "-h" | "--help" => return Ok(USAGE.to_string()),
+            _ => positional.push(arg),
+        }
+    }
+
+    let positional = positional.iter().map(String::as_str).collect::<Vec<_>>();
+    match positional.as_slice() {
+        ["dump", param] => dump(&open(&regulation)?, param, json),
+        ["get", param, id] => {
+            let id = id.parse().map_err(|_| format!("Invalid row id {id}"))?;
+            get(&open(&regulation)?, param, id, json)
+        },
+        ["search", query @ ..] if !query.is_empty() => {
+            search(&open(&regulation)?, &SearchQuery::parse(&query.join(" "))?, json)
+        },
+        ["diff", old, new, params @ ..] => diff(&open(old)?, &open(new)?, params, json),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn dump(regulation: &Regulation, param: &str, json: bool) -> Result<String, String> {
+    let rows = regulation.get(param).ok_or_else(|| format!("Unknown param {param}"))?.export(param);
+    if json {
+        return Ok(to_json(&rows) + "\n");
+    }
+
+    let mut header = vec!["id", "name"];
+    if let Some(row) = rows.first() {
+        header.extend(row.fields.iter().map(|(field, _)| field.as_str()));
+    }
+
+    Ok(table(
+        &header,
+        rows.iter().map(|row| {
+            [row.id.to_string(), row.name.clone().unwrap_or_default()]
+                .into_iter()
+                .chain(row.fields.iter().map(|(_, value)| value.to_string()))
+                .collect()
+        }),
+    ))
+}
+
+fn get(regulation: &Regulation, param: &str, id: u64, json: bool) -> Result<String, String> {
+    let row = regulation
+        .get(param)
+        .ok_or_else(|| format!("Unknown param {param}"))?
+        .export(param)
+        .into_iter()
+        .find(|row| row.id == id)
+        .ok_or_else(|| format!("Row {id} not found in {param}"))?;
+
+    if json {
+        return Ok(serde_json::to_string_pretty(&row).unwrap() + "\n");
+    }
+
+    let mut out = format!("{param} {id}");
+    if let Some(name) = &row.name {
+        write!(out, " ({name})").ok();
+    }
+    out.push_str("\n\n");
+    out.push_str(&table(
+        &["field", "value"],
+        row.fields.iter().map(|(field, value)| vec![field.clone(), value.to_string()]),
+    ));
+    Ok(out)
+}
+
+fn search(regulation: &Regulation, query: &SearchQuery, json: bool) -> Result<String, String> {
+    let hits = search_params(regulation, query, SEARCH_LIMIT);
+    if json {
+        return Ok(serde_json::to_string_pretty(&hits).unwrap() + "\n");
+    }
+
+    let mut out = table(
+        &["param", "id", "name", "field"],
+        hits.iter().map(|hit| {
+            vec![
+                hit.param.clone(),
+                hit.id.to_string(),
+                hit.name.clone().unwrap_or_default(),
+                hit.field.clone(),
+            ]
+        }),
+    );
+    if hits.len() >= SEARCH_LIMIT {
+        writeln!(out, "\nStopped after {SEARCH_LIMIT} results").ok();
+    }
+    Ok(out)
+}
+
+fn diff(old: &Regulation, new: &Regulation, params: &[&str], json: bool) -> Result<String, String> {
+    if let Some(param) = params.iter().find(|p| old.get(p).is_none() && new.get(p).is_none()) {
+        return Err(format!("Unknown param {param}"));
+    }
+
+    let diff = old.diff(new, params);
+    if json {
+        return Ok(serde_json::to_string_pretty(&diff).unwrap() + "\n");
+    }
+
+    let mut lines = Vec::new();
+    for (change, rows) in [("removed", &diff.removed), ("added", &diff.added)] {
+        lines.extend(rows.iter().map(|DiffRow { param, row }| {
+            vec![
+                change.to_string(),
+                param.clone(),
+                row.id.to_string(),
+                row.name.clone().unwrap_or_default(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]
+        }));
+    }
+    lines.extend(diff.changed.iter().map(|d| {
+        vec![
+            "changed".to_string(),
+            d.param.clone(),
+            d.id.to_string(),
+            row_name(new, &d.param, d.id),
+            d.field.clone(),
+            d.original.to_string(),
+            d.current.to_string(),
+        ]
+    }));
+
+    Ok(table(&["change", "param", "id", "name", "field", "old", "new"], lines))
+}
+
+fn row_name(regulation: &Regulation, param: &str, id: u64) -> String {
+    let file_name = regulation.get(param).and_then(|file| {
+        let idx = file.ids().iter().position(|&i| i == id)?;
+        file.name(idx)
+    });
+
+    file_name
+        .map(String::from)
+        .or_else(|| PARAM_NAMES.get(param)?.get(&(id as usize)).map(String::from))
+        .unwrap_or_default()
+}
+
+/// Left-aligns each column to its widest cell.
+fn table(header: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let rows = rows.into_iter().collect::<Vec<_>>();
+    let mut widths = header.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let header = header.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(out, "{}", line.trim_end()).ok();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two versions of EquipParamGoods: row 20 is removed, row 30 added and
+    // the weight of row 10 changed.
+    fn open(path: &str) -> Result<Regulation, String> {
+        let (rows, weight): (&[_], _) = match path {
+            "old.bin" => (&[(10, Some("Flask")), (20, None)], "0.5"),
+            "new.bin" => (&[(10, Some("Flask")), (30, None)], "1.5"),
+            _ => return Err(format!("Couldn't read {path}")),
+        };
+
+        let regulation = Regulation::with_rows("EquipParamGoods", rows).unwrap();
+        let param = regulation.get("EquipParamGoods").unwrap();
+        param.visit_row(0, &mut FieldSetter::new([("weight", weight), ("sell_value", "100")]));
+        Ok(regulation)
+    }
+
+    fn run_args(args: &str) -> Result<String, String> {
+        run(args.split_whitespace().map(String::from), open)
+    }
+
+    #[test]
+    fn test_get() {
+        let out = run_args("--regulation old.bin get EquipParamGoods 10").unwrap();
+        assert!(out.starts_with("EquipParamGoods 10 (Flask)\n\nfield "));
+        assert!(out.lines().any(|line| line.split_whitespace().eq(["weight", "0.5"])));
+        assert!(out.lines().any(|line| line.split_whitespace().eq(["sell_value", "100"])));
+
+        let out = run_args("--json --regulation old.bin get EquipParamGoods 10").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["name"], "Flask");
+        assert_eq!(json["fields"]["weight"], 0.5);
+
+        assert_eq!(
+            run_args("--regulation old.bin get EquipParamGoods 30").err(),
+            Some("Row 30 not found in EquipParamGoods".to_string())
+        );
+        assert_eq!(
+            run_args("--regulation old.bin get NoSuchParam 10").err(),
+            Some("Unknown param NoSuchParam".to_string())
+        );
+        assert!(run_args("get EquipParamGoods 10").is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let out = run_args("diff old.bin new.bin").unwrap();
+        let lines =
+            out.lines().map(|line| line.split_whitespace().collect()).collect::<Vec<Vec<_>>>();
+        assert_eq!(lines, [
+            vec!["change", "param", "id", "name", "field", "old", "new"],
+            vec!["removed", "EquipParamGoods", "20"],
+            vec!["added", "EquipParamGoods", "30"],
+            vec!["changed", "EquipParamGoods", "10", "Flask", "weight", "0.5", "1.5"],
+        ]);
+
+        let out = run_args("--json diff old.bin new.bin EquipParamGoods").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["changed"][0]["field"], "weight");
+
+        assert_eq!(
+            run_args("diff old.bin new.bin NoSuchParam").err(),
+            Some("Unknown param NoSuchParam".to_string())
+        );
+    }
+}
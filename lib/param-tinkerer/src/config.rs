@@ -0,0 +1,121 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use practice_tool_core::key::Key;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::filter::LevelFilter;
+
+/// Name of the config file, next to the DLL and the injector.
+pub const CONFIG_FILE: &str = "jdsd_er_param_tinkerer.toml";
+
+/// Name of the offsets override file, shared with the practice tool.
+pub const OFFSETS_FILE: &str = "jdsd_er_offsets.toml";
+
+/// Name of the file remembering the selection, next to the DLL.
+pub const STATE_FILE: &str = "param-tinkerer-state.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub toggle: Key,
+    pub window_size: [f32; 2],
+    pub log_level: LevelFilterSerde,
+    pub process: String,
+}
+
+impl Config {
+    pub fn parse(cfg: &str) -> Result<Self, String> {
+        let de = &mut toml::de::Deserializer::new(cfg);
+        serde_path_to_error::deserialize(de)
+            .map_err(|e| format!("TOML config error at {}: {}", e.path(), e.inner()))
+    }
+
+    /// Reads the config file, writing the default one first if there is none.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            std::fs::write(path, include_str!("../jdsd_er_param_tinkerer.toml"))
+                .map_err(|e| format!("Couldn't write default config file: {}", e))?;
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read config file: {}", e))?;
+        Config::parse(&content)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            toggle: "p".parse().unwrap(),
+            window_size: [500., 450.],
+            log_level: LevelFilterSerde(LevelFilter::DEBUG),
+            process: "eldenring.exe".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(try_from = "String")]
+pub struct LevelFilterSerde(LevelFilter);
+
+impl LevelFilterSerde {
+    pub fn inner(&self) -> LevelFilter {
+        self.0
+    }
+}
+
+impl TryFrom<String> for LevelFilterSerde {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(LevelFilterSerde(
+            LevelFilter::from_str(&value)
+                .map_err(|e| format!("Couldn't parse log level filter: {}", e))?,
+        ))
+    }
+}
+
+/// The param and row selected when the tinkerer was last closed.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    pub param: String,
+    pub id: u64,
+}
+
+impl Selection {
+    /// A missing or unreadable state file means there is nothing to restore.
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        toml::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string(self).map_err(|e| format!("{e}"))?;
+        std::fs::write(path, content).map_err(|e| format!("Couldn't write state file: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Selection};
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(include_str!("../jdsd_er_param_tinkerer.toml")).unwrap();
+        assert_eq!(config.window_size, [500., 450.]);
+        assert_eq!(config.process, "eldenring.exe");
+
+        let config = Config::parse("window_size = [600, 900]").unwrap();
+        assert_eq!(config.window_size, [600., 900.]);
+        assert_eq!(config.process, Config::default().process);
+
+        assert!(Config::parse(r#"log_level = "LOUD""#).is_err());
+    }
+
+    #[test]
+    fn test_selection_roundtrip() {
+        let selection = Selection { param: "SpEffectParam".to_string(), id: 100 };
+        let content = toml::to_string(&selection).unwrap();
+        assert_eq!(toml::from_str::<Selection>(&content).unwrap(), selection);
+    }
+}
@@ -8,8 +8,8 @@ use hudhook::hooks::dx12::ImguiDx12Hooks;
 use hudhook::tracing::{error, info};
 use hudhook::ImguiRenderLoop;
 use imgui::*;
+use libeldenring::offsets;
 use libeldenring::prelude::*;
-use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::prelude::*;
 use windows::core::PCSTR;
 use windows::Win32::Foundation::{GetLastError, HMODULE, MAX_PATH};
@@ -18,6 +18,10 @@ use windows::Win32::System::LibraryLoader::{
     GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
 };
 
+use crate::config::{Config, Selection, CONFIG_FILE, OFFSETS_FILE, STATE_FILE};
+
+pub mod config;
+
 /// Returns the path of the implementor's DLL.
 pub fn get_dll_path() -> Option<PathBuf> {
     let mut hmodule = HMODULE(0);
@@ -43,11 +47,31 @@ pub fn get_dll_path() -> Option<PathBuf> {
     Some(OsString::from_wide(&sz_filename[..len]).into())
 }
 
+const SEARCH_LIMIT: usize = 1000;
+
 struct ParamTinkerer {
+    config: Config,
     shown: bool,
     selected_param: usize,
     selected_param_id: usize,
+    export_status: String,
+    history: ParamHistory,
+    // The changes of the history at a given generation.
+    diff: Option<(u64, Vec<FieldDiff>)>,
+    search_query: String,
+    search_status: String,
+    search_hits: Vec<SearchHit>,
     pointers: Pointers,
+    param_filter: String,
+    row_filter: String,
+    field_filter: String,
+    // Set when the selection moves by other means than a click, so the lists
+    // scroll to it.
+    scroll_to_selection: bool,
+    // Saved in the state file whenever it changes.
+    selection: Option<Selection>,
+    // Applied once the params are loaded.
+    restore: Option<Selection>,
 }
 
 impl ParamTinkerer {
@@ -55,13 +79,14 @@ impl ParamTinkerer {
         println!("Initializing");
         hudhook::alloc_console().ok();
 
-        let log_file = get_dll_path()
-            .map(|mut path| {
-                path.pop();
-                path.push("param-tinkerer.log");
-                path
-            })
-            .map(std::fs::File::create);
+        let (config, config_err) = match dll_dir().map(|dir| Config::load(&dir.join(CONFIG_FILE))) {
+            Some(Ok(config)) => (config, None),
+            Some(Err(e)) => (Config::default(), Some(e)),
+            None => (Config::default(), Some("Couldn't find config file".to_string())),
+        };
+
+        let log_file =
+            dll_dir().map(|dir| dir.join("param-tinkerer.log")).map(std::fs::File::create);
 
         match log_file {
             Some(Ok(log_file)) => {
@@ -82,14 +107,14 @@ impl ParamTinkerer {
                     .boxed();
 
                 tracing_subscriber::registry()
-                    .with(LevelFilter::DEBUG)
+                    .with(config.log_level.inner())
                     .with(file_layer)
                     .with(stdout_layer)
                     .init();
             },
             e => {
                 tracing_subscriber::fmt()
-                    .with_max_level(LevelFilter::DEBUG)
+                    .with_max_level(config.log_level.inner())
                     .with_thread_ids(true)
                     .with_file(true)
                     .with_line_number(true)
@@ -105,19 +130,44 @@ impl ParamTinkerer {
             },
         }
 
+        if let Some(e) = config_err {
+            error!("{e}");
+        }
+
+        // The same override as the practice tool's, when they share a folder.
+        if let Some(path) = dll_dir().map(|dir| dir.join(OFFSETS_FILE)).filter(|p| p.exists()) {
+            if let Err(e) = offsets::load_override(&path) {
+                error!("Offsets error, please review your {OFFSETS_FILE} file: {e}");
+            }
+        }
+
+        let restore = dll_dir().and_then(|dir| Selection::load(&dir.join(STATE_FILE)));
+
         ParamTinkerer {
+            config,
             shown: true,
             selected_param: 0,
             selected_param_id: 0,
+            export_status: String::new(),
+            history: ParamHistory::default(),
+            diff: None,
+            search_query: String::new(),
+            search_status: String::new(),
+            search_hits: Vec::new(),
             pointers: Pointers::new(),
+            param_filter: String::new(),
+            row_filter: String::new(),
+            field_filter: String::new(),
+            scroll_to_selection: false,
+            selection: restore.clone(),
+            restore,
         }
     }
 }
 
 impl ImguiRenderLoop for ParamTinkerer {
     fn render(&mut self, ui: &mut imgui::Ui) {
-        if ui.is_key_index_released(0x50) {
-            // P key
+        if self.config.toggle.is_pressed(ui) {
             self.shown = !self.shown;
             self.pointers.cursor_show.set(self.shown);
         }
@@ -151,28 +201,68 @@ impl ParamTinkerer {
     pub fn render_params(&mut self, ui: &imgui::Ui) {
         let params = PARAMS.write();
 
+        if params.keys().next().is_some() {
+            if let Some(Selection { param, id }) = self.restore.take() {
+                let idx = unsafe { params.iter_param_ids(&param) }
+                    .and_then(|mut ids| ids.position(|i| i == id));
+                if let Some(idx) = idx {
+                    self.select(&params, &param, idx);
+                }
+            }
+        }
+
+        let [width, height] = self.config.window_size;
+        let column1 = (width - 20.) / 2.;
+        let column2 = column1;
+        let column3 = width;
+
         ui.child_window("##param_child_wnd")
             .flags(WindowFlags::NO_SCROLLBAR)
-            .size([500., 450.])
+            .size([width, height])
             .build(|| {
-                const COLUMN1: f32 = 240.;
-                const COLUMN2: f32 = 240.;
-                const COLUMN3: f32 = 500.;
-
                 ui.columns(2, "##param_columns", false);
                 ui.set_column_offset(0, 0.);
-                ui.set_column_offset(1, 250.);
+                ui.set_column_offset(1, column1 + 10.);
 
                 let param_entries = {
-                    ui.set_current_column_width(COLUMN1 + 10.);
+                    ui.set_current_column_width(column1 + 10.);
+
+                    let _tok = ui.push_item_width(column1);
+                    let filter_changed = ui
+                        .input_text("##param_filter", &mut self.param_filter)
+                        .hint("Filter params")
+                        .build();
+                    let focused = ui.is_item_focused();
+
+                    let visible = params
+                        .keys()
+                        .enumerate()
+                        .filter(|(_, k)| string_match(&self.param_filter, k))
+                        .collect::<Vec<_>>();
+
+                    let moved =
+                        navigate(ui, focused, &visible, self.selected_param).or_else(|| {
+                            let hidden = visible.iter().all(|&(idx, _)| idx != self.selected_param);
+                            visible
+                                .first()
+                                .map(|&(idx, _)| idx)
+                                .filter(|_| filter_changed && hidden)
+                        });
+                    if let Some(idx) = moved {
+                        self.selected_param = idx;
+                        self.selected_param_id = 0;
+                        self.scroll_to_selection = true;
+                    }
 
-                    let _ = ui.push_item_width(-1.);
-                    ListBox::new("##param_names").size([COLUMN1, 220.]).build(ui, || {
-                        for (idx, k) in params.keys().enumerate() {
-                            if ui.selectable_config(k).selected(idx == self.selected_param).build()
-                            {
+                    ListBox::new("##param_names").size([column1, 220.]).build(ui, || {
+                        for &(idx, k) in &visible {
+                            let selected = idx == self.selected_param;
+                            if ui.selectable_config(k).selected(selected).build() {
                                 self.selected_param = idx;
                             }
+                            if selected && self.scroll_to_selection {
+                                ui.set_scroll_here_y();
+                            }
                         }
                     });
 
@@ -184,28 +274,56 @@ impl ParamTinkerer {
 
                 let param_item = param_entries.map(|(param_name, param_entries)| {
                     ui.next_column();
-                    ui.set_current_column_width(COLUMN2 + 10.);
+                    ui.set_current_column_width(column2 + 10.);
+
+                    let _tok = ui.push_item_width(column2);
+                    let filter_changed = ui
+                        .input_text("##row_filter", &mut self.row_filter)
+                        .hint("Filter rows by id or name")
+                        .build();
+                    let focused = ui.is_item_focused();
+
+                    let names = PARAM_NAMES.get(param_name);
+                    let name = |id: u64| names.and_then(|names| names.get(&(id as usize)));
+                    let visible = param_entries
+                        .enumerate()
+                        .filter(|&(_, id)| {
+                            self.row_filter.is_empty()
+                                || string_match(&self.row_filter, &id.to_string())
+                                || name(id).is_some_and(|name| string_match(&self.row_filter, name))
+                        })
+                        .collect::<Vec<_>>();
+
+                    let moved =
+                        navigate(ui, focused, &visible, self.selected_param_id).or_else(|| {
+                            let hidden =
+                                visible.iter().all(|&(idx, _)| idx != self.selected_param_id);
+                            visible
+                                .first()
+                                .map(|&(idx, _)| idx)
+                                .filter(|_| filter_changed && hidden)
+                        });
+                    if let Some(idx) = moved {
+                        self.selected_param_id = idx;
+                        self.scroll_to_selection = true;
+                    }
 
                     let mut buf = String::new();
-                    let _ = ui.push_item_width(-1.);
-                    ListBox::new("##param_ids").size([COLUMN2, 220.]).build(ui, || {
-                        for (idx, id) in param_entries.enumerate() {
-                            let param_repr = PARAM_NAMES
-                                .get(param_name)
-                                .and_then(|param_id_names| param_id_names.get(&(id as usize)))
-                                .unwrap_or_else(|| {
-                                    buf.clear();
-                                    write!(buf, "{}", id).ok();
-                                    &buf
-                                });
-                            if ui
-                                .selectable_config(param_repr)
-                                .selected(idx == self.selected_param_id)
-                                .build()
-                            {
+                    ListBox::new("##param_ids").size([column2, 220.]).build(ui, || {
+                        for &(idx, id) in &visible {
+                            let param_repr = name(id).unwrap_or_else(|| {
+                                buf.clear();
+                                write!(buf, "{}", id).ok();
+                                buf.as_str()
+                            });
+                            let selected = idx == self.selected_param_id;
+                            if ui.selectable_config(param_repr).selected(selected).build() {
                                 info!("Selected {idx}: {id}");
                                 self.selected_param_id = idx;
                             }
+                            if selected && self.scroll_to_selection {
+                                ui.set_scroll_here_y();
+                            }
                         }
                     });
 
@@ -213,62 +331,563 @@ impl ParamTinkerer {
                 });
 
                 if let Some((param_name, param_idx)) = param_item {
-                    struct ImguiParamVisitor<'a>(&'a imgui::Ui);
+                    // The refs and value of a clicked reference field.
+                    type Jump = Option<(&'static [&'static str], u64)>;
+                    // The fields changed this frame, with their old and new values.
+                    type Edits = Vec<(String, FieldValue, FieldValue)>;
+
+                    struct ImguiParamVisitor<'a> {
+                        ui: &'a imgui::Ui,
+                        fields: &'static [FieldMeta],
+                        // The row to go to when a ref link is clicked.
+                        jump: Jump,
+                        filter: &'a str,
+                        edits: Edits,
+                        // Set when an input is done being edited.
+                        edit_done: bool,
+                    }
+
+                    impl ImguiParamVisitor<'_> {
+                        // Skips the fields whose name or display name don't match the filter.
+                        fn hidden(&self, name: &str) -> bool {
+                            !string_match(self.filter, name)
+                                && !FieldMeta::find(self.fields, name)
+                                    .and_then(|field| field.display_name)
+                                    .is_some_and(|d| string_match(self.filter, d))
+                        }
+
+                        // Keeps the change made by the last input, if any.
+                        fn record(
+                            &mut self,
+                            name: &str,
+                            old: FieldValue,
+                            new: FieldValue,
+                            changed: bool,
+                        ) {
+                            if changed {
+                                self.edits.push((name.to_string(), old, new));
+                            }
+                            if self.ui.is_item_deactivated_after_edit() {
+                                self.edit_done = true;
+                            }
+                        }
+
+                        // Shows the field's layout and Paramdex documentation.
+                        fn tooltip(&self, name: &str) {
+                            if !self.ui.is_item_hovered() {
+                                return;
+                            }
+                            let Some(field) = FieldMeta::find(self.fields, name) else {
+                                return;
+                            };
+
+                            let mut text = field.display_name.unwrap_or(field.name).to_string();
+                            write!(text, "\n{} at {:#x}", field.ty, field.offset).ok();
+                            if let Some(bit) = field.bit {
+                                write!(text, ", bit {bit}").ok();
+                            }
+                            if let Some(enum_type) = field.enum_type {
+                                write!(text, "\nEnum: {enum_type}").ok();
+                            }
+                            if !field.refs.is_empty() {
+                                write!(text, "\nRefs: {}", field.refs.join(", ")).ok();
+                            }
+                            if let Some(description) = field.description {
+                                write!(text, "\n\n{description}").ok();
+                            }
+                            self.ui.tooltip_text(text);
+                        }
+
+                        // Adds a link to the referenced row after a field with refs.
+                        fn ref_link(&mut self, name: &str, value: i64) {
+                            let Some(field) = FieldMeta::find(self.fields, name) else {
+                                return;
+                            };
+                            if field.refs.is_empty() || value < 0 {
+                                return;
+                            }
+
+                            self.ui.same_line();
+                            if self.ui.small_button(format!("->##{name}")) {
+                                self.jump = Some((field.refs, value as u64));
+                            }
+                            if self.ui.is_item_hovered() {
+                                self.ui.tooltip_text(field.refs.join(", "));
+                            }
+                        }
+                    }
 
                     impl<'a> ParamVisitor for ImguiParamVisitor<'a> {
                         fn visit_u8(&mut self, name: &str, v: &mut u8) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
                             let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
+                            let changed = self.ui.input_int(name, &mut i).build();
                             *v = i as _;
+                            self.record(name, FieldValue::U8(old), FieldValue::U8(*v), changed);
+                            self.tooltip(name);
+                            self.ref_link(name, *v as i64);
                         }
 
                         fn visit_u16(&mut self, name: &str, v: &mut u16) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
                             let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
+                            let changed = self.ui.input_int(name, &mut i).build();
                             *v = i as _;
+                            self.record(name, FieldValue::U16(old), FieldValue::U16(*v), changed);
+                            self.tooltip(name);
+                            self.ref_link(name, *v as i64);
                         }
 
                         fn visit_u32(&mut self, name: &str, v: &mut u32) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
                             let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
+                            let changed = self.ui.input_int(name, &mut i).build();
                             *v = i as _;
+                            self.record(name, FieldValue::U32(old), FieldValue::U32(*v), changed);
+                            self.tooltip(name);
+                            self.ref_link(name, *v as i64);
                         }
 
                         fn visit_i8(&mut self, name: &str, v: &mut i8) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
                             let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
+                            let changed = self.ui.input_int(name, &mut i).build();
                             *v = i as _;
+                            self.record(name, FieldValue::I8(old), FieldValue::I8(*v), changed);
+                            self.tooltip(name);
+                            self.ref_link(name, *v as i64);
                         }
 
                         fn visit_i16(&mut self, name: &str, v: &mut i16) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
                             let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
+                            let changed = self.ui.input_int(name, &mut i).build();
                             *v = i as _;
+                            self.record(name, FieldValue::I16(old), FieldValue::I16(*v), changed);
+                            self.tooltip(name);
+                            self.ref_link(name, *v as i64);
                         }
 
                         fn visit_i32(&mut self, name: &str, v: &mut i32) {
-                            let mut i = *v;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
+                            let changed = self.ui.input_int(name, v).build();
+                            self.record(name, FieldValue::I32(old), FieldValue::I32(*v), changed);
+                            self.tooltip(name);
+                            self.ref_link(name, *v as i64);
                         }
 
                         fn visit_f32(&mut self, name: &str, v: &mut f32) {
-                            self.0.input_float(name, v).build();
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
+                            let changed = self.ui.input_float(name, v).build();
+                            self.record(name, FieldValue::F32(old), FieldValue::F32(*v), changed);
+                            self.tooltip(name);
+                        }
+
+                        fn visit_u64(&mut self, name: &str, v: &mut u64) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
+                            let changed = self.ui.input_scalar(name, v).build();
+                            self.record(name, FieldValue::U64(old), FieldValue::U64(*v), changed);
+                            self.tooltip(name);
+                        }
+
+                        fn visit_i64(&mut self, name: &str, v: &mut i64) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
+                            let changed = self.ui.input_scalar(name, v).build();
+                            self.record(name, FieldValue::I64(old), FieldValue::I64(*v), changed);
+                            self.tooltip(name);
+                        }
+
+                        fn visit_f64(&mut self, name: &str, v: &mut f64) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
+                            let changed = self.ui.input_scalar(name, v).build();
+                            self.record(name, FieldValue::F64(old), FieldValue::F64(*v), changed);
+                            self.tooltip(name);
                         }
 
                         fn visit_bool(&mut self, name: &str, v: &mut bool) {
-                            self.0.checkbox(name, v);
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = *v;
+                            let changed = self.ui.checkbox(name, v);
+                            self.record(name, FieldValue::Bool(old), FieldValue::Bool(*v), changed);
+                            self.tooltip(name);
+                        }
+
+                        fn visit_bytes(&mut self, name: &str, v: &mut [u8]) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = v.to_vec();
+                            let mut hex = to_hex(v);
+                            let changed = self.ui.input_text(name, &mut hex).build();
+                            if changed {
+                                if let Some(bytes) =
+                                    parse_hex(&hex).filter(|bytes| bytes.len() == v.len())
+                                {
+                                    v.copy_from_slice(&bytes);
+                                }
+                            }
+                            self.record(
+                                name,
+                                FieldValue::Bytes(old),
+                                FieldValue::Bytes(v.to_vec()),
+                                changed,
+                            );
+                            self.tooltip(name);
+                        }
+
+                        fn visit_wstr(&mut self, name: &str, v: &mut [u16]) {
+                            if self.hidden(name) {
+                                return;
+                            }
+                            let old = decode_wstr(v);
+                            let mut s = old.clone();
+                            let changed = self.ui.input_text(name, &mut s).build();
+                            if changed {
+                                // Drop what doesn't fit rather than the whole edit.
+                                while encode_wstr(&s, v.len()).is_none() {
+                                    s.pop();
+                                }
+                                v.copy_from_slice(&encode_wstr(&s, v.len()).unwrap());
+                            }
+                            self.record(name, FieldValue::WStr(old), FieldValue::WStr(s), changed);
+                            self.tooltip(name);
                         }
                     }
 
                     ui.columns(1, "##param_columns2", false);
 
-                    ListBox::new("##param_detail").size([COLUMN3, 220.]).build(ui, || {
+                    let param_id = unsafe { params.iter_param_ids(param_name) }
+                        .and_then(|mut ids| ids.nth(param_idx));
+
+                    let fields = PARAM_FIELDS.get(param_name).copied().unwrap_or_default();
+
+                    let _tok = ui.push_item_width(column3);
+                    ui.input_text("##field_filter", &mut self.field_filter)
+                        .hint("Filter fields")
+                        .build();
+
+                    ListBox::new("##param_detail").size([column3, 220.]).build(ui, || {
                         let _tok = ui.push_item_width(120.);
-                        params.visit_param_item(param_name, param_idx, &mut ImguiParamVisitor(ui));
+                        if let Some(param_id) = param_id {
+                            let mut visitor = ImguiParamVisitor {
+                                ui,
+                                fields,
+                                jump: None,
+                                filter: &self.field_filter,
+                                edits: Vec::new(),
+                                edit_done: false,
+                            };
+                            params.visit_param_item(param_name, param_idx, &mut visitor);
+
+                            for (field, old, new) in visitor.edits {
+                                self.history.record_field(param_name, param_id, &field, old, new);
+                            }
+                            if visitor.edit_done {
+                                self.history.end_edit();
+                            }
+
+                            if let Some((refs, id)) = visitor.jump {
+                                match find_row(&*params, refs, id) {
+                                    Some((param, idx)) => self.select(&params, &param, idx),
+                                    None => self.search_status = format!("Row {id} not found"),
+                                }
+                            }
+                        }
                     });
+
+                    if ui.button("Undo") {
+                        self.history.undo(&*params);
+                    }
+                    ui.same_line();
+                    if ui.button("Redo") {
+                        self.history.redo(&*params);
+                    }
+                    ui.same_line();
+                    if ui.button("Revert row") {
+                        if let Some(param_id) = param_id {
+                            self.history.revert_row(&*params, param_name, param_id);
+                        }
+                    }
+                    ui.same_line();
+                    if ui.button("Revert param") {
+                        self.history.revert_param(&*params, param_name);
+                    }
+                    ui.same_line();
+                    if ui.button("References") {
+                        if let Some(param_id) = param_id {
+                            self.search_hits =
+                                find_references(&*params, param_name, param_id, SEARCH_LIMIT);
+                            self.search_status = format!(
+                                "{} references to {param_name}[{param_id}]",
+                                self.search_hits.len()
+                            );
+                        }
+                    }
+
+                    for format in [ExportFormat::Csv, ExportFormat::Json] {
+                        let ext = format.extension();
+                        if ui.button(format!("Export {ext}")) {
+                            self.export_status = export_param(&params, param_name, format);
+                        }
+                        ui.same_line();
+                    }
                 };
+
+                if ui.button("Export all") {
+                    self.export_status = export_all(&params, ExportFormat::Csv);
+                }
+                ui.same_line();
+                ui.text(&self.export_status);
+
+                // Collecting the edited rows is too slow to do every frame.
+                let generation = self.history.generation();
+                if self.diff.as_ref().map(|(g, _)| *g) != Some(generation) {
+                    self.diff = Some((generation, self.history.diff(&*params)));
+                }
+                let diff = self.diff.as_ref().map(|(_, diff)| diff.as_slice()).unwrap_or_default();
+                ui.text(format!("Changes ({})", diff.len()));
+                ui.same_line();
+                if ui.button("Revert all") {
+                    self.history.revert_all(&*params);
+                }
+                ui.same_line();
+                if ui.button("Export changes") {
+                    self.export_status = export_diff(&diff);
+                }
+
+                ListBox::new("##param_diff").size([column3, 150.]).build(ui, || {
+                    for (idx, d) in diff.iter().enumerate() {
+                        if ui.small_button(format!("Revert##diff{idx}")) {
+                            self.history.revert_field(&*params, &d.param, d.id, &d.field);
+                        }
+                        ui.same_line();
+                        ui.text(format!(
+                            "{}[{}].{}: {} -> {}",
+                            d.param, d.id, d.field, d.original, d.current
+                        ));
+                    }
+                });
+
+                let _tok = ui.push_item_width(240.);
+                let entered = ui
+                    .input_text("##param_search", &mut self.search_query)
+                    .hint("value, min..max, field=value")
+                    .enter_returns_true(true)
+                    .build();
+                ui.same_line();
+                if ui.button("Search") || entered {
+                    self.search(&params);
+                }
+                ui.same_line();
+                ui.text(&self.search_status);
+
+                let mut jump = None;
+                ListBox::new("##param_search_hits").size([column3, 120.]).build(ui, || {
+                    for (idx, hit) in self.search_hits.iter().enumerate() {
+                        let label = format!(
+                            "{}[{}] {}.{}##hit{idx}",
+                            hit.param,
+                            hit.id,
+                            hit.name.as_deref().unwrap_or(""),
+                            hit.field
+                        );
+                        if ui.selectable(label) {
+                            jump = Some((hit.param.clone(), hit.idx));
+                        }
+                    }
+                });
+                if let Some((param, idx)) = jump {
+                    self.select(&params, &param, idx);
+                }
             });
+
+        self.scroll_to_selection = false;
+        self.save_selection(&params);
+    }
+
+    fn search(&mut self, params: &Params) {
+        match SearchQuery::parse(&self.search_query) {
+            Ok(query) => {
+                self.search_hits = search_params(params, &query, SEARCH_LIMIT);
+                self.search_status = if self.search_hits.len() == SEARCH_LIMIT {
+                    format!("First {SEARCH_LIMIT} hits")
+                } else {
+                    format!("{} hits", self.search_hits.len())
+                };
+            },
+            Err(e) => self.search_status = e,
+        }
+    }
+
+    fn select(&mut self, params: &Params, param: &str, idx: usize) {
+        if let Some(param_idx) = params.keys().position(|k| k == param) {
+            self.selected_param = param_idx;
+            self.selected_param_id = idx;
+            // The filters could hide the selection.
+            self.param_filter.clear();
+            self.row_filter.clear();
+            self.scroll_to_selection = true;
+        }
+    }
+
+    fn save_selection(&mut self, params: &Params) {
+        // Don't overwrite the saved selection before it has been restored.
+        if self.restore.is_some() {
+            return;
+        }
+
+        let selection = params.keys().nth(self.selected_param).and_then(|param| {
+            let id = unsafe { params.iter_param_ids(param) }?.nth(self.selected_param_id)?;
+            Some(Selection { param: param.to_string(), id })
+        });
+        if selection.is_none() || selection == self.selection {
+            return;
+        }
+
+        self.selection = selection;
+        if let (Some(selection), Some(dir)) = (&self.selection, dll_dir()) {
+            if let Err(e) = selection.save(&dir.join(STATE_FILE)) {
+                error!("{e}");
+            }
+        }
+    }
+}
+
+fn dll_dir() -> Option<PathBuf> {
+    let mut path = get_dll_path()?;
+    path.pop();
+    Some(path)
+}
+
+/// Moves the selection among the `visible` entries of a list with the arrow
+/// and page keys, while the filter above the list is focused.
+fn navigate<T>(
+    ui: &imgui::Ui,
+    focused: bool,
+    visible: &[(usize, T)],
+    selected: usize,
+) -> Option<usize> {
+    if !focused || visible.is_empty() {
+        return None;
+    }
+
+    let pos = visible.iter().position(|&(idx, _)| idx == selected);
+    let step =
+        |delta: isize| pos.map_or(0, |pos| pos.saturating_add_signed(delta).min(visible.len() - 1));
+    let pos = if ui.is_key_pressed(Key::DownArrow) {
+        step(1)
+    } else if ui.is_key_pressed(Key::UpArrow) {
+        step(-1)
+    } else if ui.is_key_pressed(Key::PageDown) {
+        step(10)
+    } else if ui.is_key_pressed(Key::PageUp) {
+        step(-10)
+    } else {
+        return None;
+    };
+
+    Some(visible[pos].0)
+}
+
+/// Exports go to a `param-export` directory next to the DLL.
+fn export_dir() -> Option<PathBuf> {
+    Some(dll_dir()?.join("param-export"))
+}
+
+fn export_param(params: &Params, param_name: &str, format: ExportFormat) -> String {
+    let result =
+        export_dir().ok_or_else(|| "Could not construct export path".to_string()).and_then(|dir| {
+            let rows = params
+                .export_param(param_name)
+                .ok_or_else(|| format!("Could not export {param_name}"))?;
+            std::fs::create_dir_all(&dir).map_err(|e| format!("{e}"))?;
+
+            let path = dir.join(format!("{param_name}.{}", format.extension()));
+            std::fs::write(&path, format.format(&rows)).map_err(|e| format!("{e}"))?;
+            Ok(path)
+        });
+
+    match result {
+        Ok(path) => {
+            info!("Exported {param_name} to {path:?}");
+            format!("Exported {param_name}")
+        },
+        Err(e) => {
+            error!("{e}");
+            e
+        },
+    }
+}
+
+fn export_diff(diff: &[FieldDiff]) -> String {
+    let result =
+        export_dir().ok_or_else(|| "Could not construct export path".to_string()).and_then(|dir| {
+            let patches = diff_to_patch_file(diff)?;
+            std::fs::create_dir_all(&dir).map_err(|e| format!("{e}"))?;
+
+            let path = dir.join("param_patches.toml");
+            std::fs::write(&path, patches).map_err(|e| format!("{e}"))?;
+            Ok(path)
+        });
+
+    match result {
+        Ok(path) => {
+            info!("Exported {} changes to {path:?}", diff.len());
+            format!("Exported {} changes", diff.len())
+        },
+        Err(e) => {
+            error!("{e}");
+            e
+        },
+    }
+}
+
+fn export_all(params: &Params, format: ExportFormat) -> String {
+    let result = export_dir()
+        .ok_or_else(|| "Could not construct export path".to_string())
+        .and_then(|dir| params.export_all(&dir, format).map(|count| (dir, count)));
+
+    match result {
+        Ok((dir, count)) => {
+            info!("Exported {count} params to {dir:?}");
+            format!("Exported {count} params")
+        },
+        Err(e) => {
+            error!("{e}");
+            e
+        },
     }
 }
 
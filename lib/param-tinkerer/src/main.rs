@@ -1,12 +1,12 @@
 use hudhook::inject::Process;
-use hudhook::tracing::trace;
-use tracing_subscriber::filter::LevelFilter;
+use hudhook::tracing::{error, trace};
+use jdsd_er_param_tinkerer::config::{Config, CONFIG_FILE};
 
 fn err_to_string<T: std::fmt::Display>(e: T) -> String {
     format!("Error: {}", e)
 }
 
-fn perform_injection() -> Result<(), String> {
+fn perform_injection(config: &Config) -> Result<(), String> {
     let mut dll_path = std::env::current_exe().unwrap();
     dll_path.pop();
     dll_path.push("jdsd_er_param_tinkerer.dll");
@@ -20,7 +20,7 @@ fn perform_injection() -> Result<(), String> {
     let dll_path = dll_path.canonicalize().map_err(err_to_string)?;
     trace!("Injecting {:?}", dll_path);
 
-    Process::by_title("ELDEN RING™")
+    Process::by_name(&config.process)
         .map_err(|e| e.to_string())?
         .inject(dll_path)
         .map_err(|e| e.to_string())?;
@@ -29,13 +29,25 @@ fn perform_injection() -> Result<(), String> {
 }
 
 fn main() {
+    let mut config_path = std::env::current_exe().unwrap();
+    config_path.pop();
+    config_path.push(CONFIG_FILE);
+    let (config, config_err) = match Config::load(&config_path) {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+
     tracing_subscriber::fmt()
-        .with_max_level(LevelFilter::TRACE)
+        .with_max_level(config.log_level.inner())
         .with_thread_ids(true)
         .with_file(true)
         .with_line_number(true)
         .with_thread_names(true)
         .with_ansi(true)
         .init();
-    perform_injection().unwrap();
+
+    if let Some(e) = config_err {
+        error!("{e}");
+    }
+    perform_injection(&config).unwrap();
 }
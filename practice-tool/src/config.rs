@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::str::FromStr;
 
 use hudhook::tracing::error;
@@ -16,20 +17,47 @@ use crate::widgets::flag::flag_widget;
 use crate::widgets::group::group;
 use crate::widgets::item_spawn::ItemSpawner;
 use crate::widgets::label::label_widget;
+use crate::widgets::macro_cmd::{macro_widget, MacroStep};
 use crate::widgets::multiflag::multi_flag;
 use crate::widgets::nudge_pos::nudge_position;
-use crate::widgets::position::save_position;
+use crate::widgets::on_load::on_load;
+use crate::widgets::position::{save_position, slot_name};
 use crate::widgets::quitout::quitout;
 use crate::widgets::runes::runes;
 use crate::widgets::savefile_manager::savefile_manager;
 use crate::widgets::target::Target;
+use crate::widgets::value::value_edit;
 use crate::widgets::warp::Warp;
 
 #[cfg_attr(test, derive(Debug))]
 #[derive(Deserialize)]
 pub(crate) struct Config {
     pub(crate) settings: Settings,
+    #[serde(default)]
     commands: Vec<CfgCommand>,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    #[serde(default = "default_param_patches")]
+    pub(crate) param_patches: Vec<ParamPatch>,
+}
+
+// A named list of commands, selected at runtime. The top-level `commands` are
+// the "Default" profile.
+#[cfg_attr(test, derive(Debug))]
+#[derive(Deserialize)]
+struct Profile {
+    name: String,
+    commands: Vec<CfgCommand>,
+}
+
+// Applied when the config file has no `param_patches`: gives the Spectral
+// Steed Whistle an icon.
+fn default_param_patches() -> Vec<ParamPatch> {
+    vec![ParamPatch {
+        param: "EquipParamGoods".to_string(),
+        id: 130,
+        fields: [("icon_id".to_string(), PatchValue::Int(12))].into_iter().collect(),
+    }]
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -37,6 +65,12 @@ pub(crate) struct Settings {
     pub(crate) log_level: LevelFilterSerde,
     pub(crate) display: Key,
     pub(crate) hide: Option<Key>,
+    pub(crate) reload: Option<Key>,
+    pub(crate) cycle_profile: Option<Key>,
+    // The profile selected on startup, saved when it changes.
+    pub(crate) profile: Option<String>,
+    #[serde(default)]
+    pub(crate) watch_config: bool,
     #[serde(default)]
     pub(crate) dxgi_debug: bool,
     #[serde(default)]
@@ -59,6 +93,22 @@ pub(crate) enum IndicatorType {
     Animation,
 }
 
+impl IndicatorType {
+    // The name of the indicator in the config file.
+    fn name(&self) -> &'static str {
+        match self {
+            IndicatorType::Igt => "igt",
+            IndicatorType::Position => "position",
+            IndicatorType::PositionChange => "position_change",
+            IndicatorType::GameVersion => "game_version",
+            IndicatorType::ImguiDebug => "imgui_debug",
+            IndicatorType::Fps => "fps",
+            IndicatorType::FrameCount => "framecount",
+            IndicatorType::Animation => "animation",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(try_from = "IndicatorConfig")]
 pub(crate) struct Indicator {
@@ -118,7 +168,7 @@ impl TryFrom<IndicatorConfig> for Indicator {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
 enum PlaceholderOption<T> {
     Data(T),
@@ -136,7 +186,7 @@ impl<T> PlaceholderOption<T> {
 }
 
 #[cfg_attr(test, derive(Debug))]
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(untagged)]
 enum CfgCommand {
     SavefileManager {
@@ -150,10 +200,18 @@ enum CfgCommand {
     Flag {
         flag: FlagSpec,
         hotkey: Option<Key>,
+        #[serde(default)]
+        on_load: bool,
+        #[serde(default)]
+        persist: bool,
     },
     MultiFlag {
         flag: MultiFlagSpec,
         hotkey: Option<Key>,
+        #[serde(default)]
+        on_load: bool,
+        #[serde(default)]
+        persist: bool,
     },
     SpecialFlag {
         flag: String,
@@ -163,6 +221,35 @@ enum CfgCommand {
         flags: Vec<FlagSpec>,
         hotkey: Option<Key>,
         label: String,
+        #[serde(default)]
+        on_load: bool,
+        #[serde(default)]
+        persist: bool,
+    },
+    // Before `Label`, which would match them too.
+    CustomFlag {
+        pointer: PointerExpr,
+        mask: u8,
+        label: String,
+        hotkey: Option<Key>,
+        #[serde(default)]
+        on_load: bool,
+        #[serde(default)]
+        persist: bool,
+    },
+    CustomValue {
+        pointer: PointerExpr,
+        #[serde(rename = "type")]
+        value_type: ValueType,
+        label: String,
+        hotkey: Option<Key>,
+        value: Option<f64>,
+    },
+    // Custom commands that didn't match above, rejected by `Config::parse`
+    // instead of falling through to `Label`.
+    BadCustom {
+        pointer: String,
+        label: String,
     },
     Label {
         #[serde(rename = "label")]
@@ -171,6 +258,7 @@ enum CfgCommand {
     Position {
         position: PlaceholderOption<Key>,
         save: Option<Key>,
+        slot: Option<String>,
     },
     NudgePosition {
         nudge: f32,
@@ -213,24 +301,126 @@ enum CfgCommand {
         #[serde(rename = "quitout")]
         hotkey: PlaceholderOption<Key>,
     },
+    Macro {
+        #[serde(rename = "macro")]
+        label: String,
+        steps: Vec<CfgMacroStep>,
+        hotkey: Option<Key>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ValueType {
+    U8,
+    I32,
+    F32,
+}
+
+// A step of a `macro` command. Position slots are named after the hotkey that
+// loads them, or their `slot`.
+#[cfg_attr(test, derive(Debug))]
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+enum CfgMacroStep {
+    Flag { flag: FlagSpec, state: bool },
+    ToggleFlag { toggle: FlagSpec },
+    LoadPosition { load_position: String },
+    SavePosition { save_position: String },
+    Speed { speed: f32 },
+    Runes { runes: u32 },
+    Quitout { quitout: bool },
+    Wait { wait: u64 },
+}
+
+impl CfgMacroStep {
+    fn into_step(self, chains: &Pointers) -> MacroStep {
+        match self {
+            CfgMacroStep::Flag { flag, state } => MacroStep::Flag {
+                label: flag.label,
+                flag: (flag.getter)(chains).clone(),
+                state: Some(state),
+            },
+            CfgMacroStep::ToggleFlag { toggle: flag } => MacroStep::Flag {
+                label: flag.label,
+                flag: (flag.getter)(chains).clone(),
+                state: None,
+            },
+            CfgMacroStep::LoadPosition { load_position } => MacroStep::LoadPosition(load_position),
+            CfgMacroStep::SavePosition { save_position } => MacroStep::SavePosition(save_position),
+            CfgMacroStep::Speed { speed } => MacroStep::Speed(speed),
+            CfgMacroStep::Runes { runes } => MacroStep::Runes(runes),
+            CfgMacroStep::Quitout { .. } => MacroStep::Quitout,
+            CfgMacroStep::Wait { wait } => MacroStep::Wait(wait),
+        }
+    }
 }
 
 impl CfgCommand {
     fn into_widget(self, settings: &Settings, chains: &Pointers) -> Option<Box<dyn Widget>> {
         let widget = match self {
-            CfgCommand::Flag { flag, hotkey } => {
-                flag_widget(&flag.label, (flag.getter)(chains).clone(), hotkey)
+            CfgCommand::Flag { flag, hotkey, on_load: load, persist } => {
+                let bitflag = (flag.getter)(chains).clone();
+                on_load(
+                    flag_widget(&flag.label, bitflag.clone(), hotkey),
+                    vec![bitflag],
+                    load,
+                    persist,
+                )
+            },
+            CfgCommand::MultiFlag { flag, hotkey, on_load: load, persist } => {
+                let bitflags =
+                    flag.items.iter().map(|flag| flag(chains).clone()).collect::<Vec<_>>();
+                on_load(multi_flag(&flag.label, bitflags.clone(), hotkey), bitflags, load, persist)
+            },
+            CfgCommand::MultiFlagUser { flags, hotkey, label, on_load: load, persist } => {
+                let bitflags =
+                    flags.iter().map(|flag| (flag.getter)(chains).clone()).collect::<Vec<_>>();
+                on_load(
+                    multi_flag(label.as_str(), bitflags.clone(), hotkey),
+                    bitflags,
+                    load,
+                    persist,
+                )
+            },
+            CfgCommand::CustomFlag { pointer, mask, label, hotkey, on_load: load, persist } => {
+                match pointer.resolve(InProcess, &chains.base_addresses) {
+                    Ok(ptr) => {
+                        let bitflag = Bitflag::new(ptr, mask);
+                        on_load(
+                            flag_widget(&label, bitflag.clone(), hotkey),
+                            vec![bitflag],
+                            load,
+                            persist,
+                        )
+                    },
+                    Err(e) => {
+                        error!("Invalid flag {}: {}", label, e);
+                        return None;
+                    },
+                }
+            },
+            CfgCommand::CustomValue { pointer, value_type, label, hotkey, value } => {
+                let base_addresses = &chains.base_addresses;
+                let widget = match value_type {
+                    ValueType::U8 => pointer
+                        .resolve(InProcess, base_addresses)
+                        .map(|ptr| value_edit::<u8>(&label, ptr, hotkey, value)),
+                    ValueType::I32 => pointer
+                        .resolve(InProcess, base_addresses)
+                        .map(|ptr| value_edit::<i32>(&label, ptr, hotkey, value)),
+                    ValueType::F32 => pointer
+                        .resolve(InProcess, base_addresses)
+                        .map(|ptr| value_edit::<f32>(&label, ptr, hotkey, value)),
+                };
+                match widget {
+                    Ok(widget) => widget,
+                    Err(e) => {
+                        error!("Invalid value {}: {}", label, e);
+                        return None;
+                    },
+                }
             },
-            CfgCommand::MultiFlag { flag, hotkey } => multi_flag(
-                &flag.label,
-                flag.items.iter().map(|flag| flag(chains).clone()).collect(),
-                hotkey,
-            ),
-            CfgCommand::MultiFlagUser { flags, hotkey, label } => multi_flag(
-                label.as_str(),
-                flags.iter().map(|flag| (flag.getter)(chains).clone()).collect(),
-                hotkey,
-            ),
             CfgCommand::SpecialFlag { flag, hotkey } if flag == "deathcam" => deathcam(
                 chains.deathcam.0.clone(),
                 chains.deathcam.1.clone(),
@@ -246,6 +436,7 @@ impl CfgCommand {
                 error!("Invalid flag {}", flag);
                 return None;
             },
+            CfgCommand::BadCustom { .. } => return None,
             CfgCommand::Label { label } => label_widget(label.as_str()),
             CfgCommand::SavefileManager { hotkey_load } => {
                 savefile_manager(hotkey_load.into_option(), settings.display)
@@ -257,12 +448,13 @@ impl CfgCommand {
                 hotkey_load.into_option(),
                 settings.display,
             )),
-            CfgCommand::Position { position, save } => save_position(
+            CfgCommand::Position { position, save, slot } => save_position(
                 chains.global_position.clone(),
                 chains.chunk_position.clone(),
                 chains.torrent_chunk_position.clone(),
                 position.into_option(),
                 save,
+                slot.as_deref(),
             ),
             CfgCommand::NudgePosition { nudge, nudge_up, nudge_down } => nudge_position(
                 chains.global_position.clone(),
@@ -300,6 +492,12 @@ impl CfgCommand {
                 hotkey.into_option(),
             )),
             CfgCommand::Quitout { hotkey } => quitout(chains.quitout.clone(), hotkey.into_option()),
+            CfgCommand::Macro { label, steps, hotkey } => macro_widget(
+                label.as_str(),
+                steps.into_iter().map(|step| step.into_step(chains)).collect(),
+                chains,
+                hotkey,
+            ),
             CfgCommand::Group { label, commands } => group(
                 label.as_str(),
                 commands.into_iter().filter_map(|c| c.into_widget(settings, chains)).collect(),
@@ -332,15 +530,189 @@ impl TryFrom<String> for LevelFilterSerde {
     }
 }
 
+impl Settings {
+    /// Writes the settings that can be changed from the UI, the indicators and
+    /// the profile, into the contents of a config file. Everything else,
+    /// comments and ordering included, is left as it is.
+    pub(crate) fn write_to(&self, cfg: &str) -> Result<String, String> {
+        let mut doc =
+            cfg.parse::<toml_edit::DocumentMut>().map_err(|e| format!("TOML config error: {e}"))?;
+        let settings = doc
+            .entry("settings")
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| "`settings` is not a table".to_string())?;
+
+        let indicators = settings
+            .entry("indicators")
+            .or_insert(toml_edit::value(toml_edit::Array::new()))
+            .as_array_mut()
+            .ok_or_else(|| "`settings.indicators` is not an array".to_string())?;
+
+        for indicator in &self.indicators {
+            let name = indicator.indicator.name();
+            let entry = indicators
+                .iter_mut()
+                .filter_map(|v| v.as_inline_table_mut())
+                .find(|t| t.get("indicator").and_then(|v| v.as_str()) == Some(name));
+
+            match entry {
+                Some(entry) => set_value(entry, "enabled", indicator.enabled.into()),
+                None => {
+                    let mut entry = toml_edit::InlineTable::new();
+                    entry.insert("indicator", name.into());
+                    entry.insert("enabled", indicator.enabled.into());
+                    indicators.push(entry);
+                },
+            }
+        }
+
+        match &self.profile {
+            Some(profile) => set_value(settings, "profile", profile.as_str().into()),
+            None => {
+                settings.remove("profile");
+            },
+        }
+
+        Ok(doc.to_string())
+    }
+}
+
+// Replaces a value, keeping the whitespace and comments around it.
+fn set_value(table: &mut dyn toml_edit::TableLike, key: &str, mut value: toml_edit::Value) {
+    match table.get_mut(key).and_then(|item| item.as_value_mut()) {
+        Some(old) => {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        },
+        None => {
+            table.insert(key, toml_edit::value(value));
+        },
+    }
+}
+
 impl Config {
     pub(crate) fn parse(cfg: &str) -> Result<Self, String> {
         let de = &mut toml::de::Deserializer::new(cfg);
-        serde_path_to_error::deserialize(de)
-            .map_err(|e| format!("TOML config error at {}: {}", e.path(), e.inner()))
+        let config: Config = serde_path_to_error::deserialize(de)
+            .map_err(|e| format!("TOML config error at {}: {}", e.path(), e.inner()))?;
+        config.check_custom()?;
+        config.check_slots()?;
+        Ok(config)
     }
 
-    pub(crate) fn make_commands(self, chains: &Pointers) -> Vec<Box<dyn Widget>> {
-        self.commands.into_iter().filter_map(|c| c.into_widget(&self.settings, chains)).collect()
+    // Every command of every profile, including the ones in groups.
+    fn all_commands(&self) -> Vec<&CfgCommand> {
+        fn walk<'a>(commands: &'a [CfgCommand], all: &mut Vec<&'a CfgCommand>) {
+            for command in commands {
+                all.push(command);
+                if let CfgCommand::Group { commands, .. } = command {
+                    walk(commands, all);
+                }
+            }
+        }
+
+        let mut all = Vec::new();
+        for (_, commands) in self.profiles() {
+            walk(commands, &mut all);
+        }
+        all
+    }
+
+    fn check_custom(&self) -> Result<(), String> {
+        for command in self.all_commands() {
+            if let CfgCommand::BadCustom { pointer, label } = command {
+                return Err(match pointer.parse::<PointerExpr>() {
+                    Err(e) => format!("Invalid pointer for \"{label}\": {e}"),
+                    Ok(_) => {
+                        format!(
+                            "Invalid custom command \"{label}\": check its mask, type and hotkey"
+                        )
+                    },
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Macros can only load position slots that something saves to. Slots are
+    // shared by all profiles, so any profile will do.
+    fn check_slots(&self) -> Result<(), String> {
+        let commands = self.all_commands();
+        let macro_steps = || {
+            commands.iter().flat_map(|command| match command {
+                CfgCommand::Macro { label, steps, .. } => {
+                    steps.iter().map(|step| (label.as_str(), step)).collect()
+                },
+                _ => Vec::new(),
+            })
+        };
+
+        let saved = commands
+            .iter()
+            .filter_map(|command| match command {
+                CfgCommand::Position { position, slot, .. } => slot
+                    .clone()
+                    .or_else(|| position.clone().into_option().map(|key| key.to_string())),
+                _ => None,
+            })
+            .chain(macro_steps().filter_map(|(_, step)| match step {
+                CfgMacroStep::SavePosition { save_position } => Some(save_position.clone()),
+                _ => None,
+            }))
+            .map(|slot| slot_name(&slot))
+            .collect::<HashSet<_>>();
+
+        for (label, step) in macro_steps() {
+            if let CfgMacroStep::LoadPosition { load_position } = step {
+                if !saved.contains(&slot_name(load_position)) {
+                    return Err(format!(
+                        "Macro \"{label}\" loads position slot \"{load_position}\", which nothing \
+                         saves to"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // The "Default" profile is left out if it has no commands and there are
+    // named profiles.
+    fn profiles(&self) -> impl Iterator<Item = (&str, &[CfgCommand])> {
+        let default = (!self.commands.is_empty() || self.profiles.is_empty())
+            .then_some(("Default", self.commands.as_slice()));
+        default
+            .into_iter()
+            .chain(self.profiles.iter().map(|p| (p.name.as_str(), p.commands.as_slice())))
+    }
+
+    pub(crate) fn profile_names(&self) -> Vec<String> {
+        self.profiles().map(|(name, _)| name.to_string()).collect()
+    }
+
+    /// Index of the profile called `name`, or of the first one if there's no
+    /// such profile.
+    pub(crate) fn profile_index(&self, name: Option<&str>) -> usize {
+        self.profiles().position(|(n, _)| Some(n) == name).unwrap_or(0)
+    }
+
+    /// Replaces the config with the one in `cfg`, staying on the profile of
+    /// the same name if there is still one. On errors, the current config and
+    /// profile are kept, and so are the widgets made from them.
+    pub(crate) fn reload(&mut self, cfg: &str, profile: &mut usize) -> Result<(), String> {
+        let config = Config::parse(cfg)?;
+        *profile = config.profile_index(self.profiles().nth(*profile).map(|(name, _)| name));
+        *self = config;
+        Ok(())
+    }
+
+    pub(crate) fn make_commands(&self, profile: usize, chains: &Pointers) -> Vec<Box<dyn Widget>> {
+        let Some((_, commands)) = self.profiles().nth(profile) else {
+            return Vec::new();
+        };
+        commands.iter().cloned().filter_map(|c| c.into_widget(&self.settings, chains)).collect()
     }
 }
 
@@ -351,17 +723,23 @@ impl Default for Config {
                 log_level: LevelFilterSerde(LevelFilter::DEBUG),
                 display: "0".parse().unwrap(),
                 hide: "rshift+0".parse().ok(),
+                reload: None,
+                cycle_profile: None,
+                profile: None,
+                watch_config: false,
                 dxgi_debug: false,
                 show_console: false,
                 indicators: Indicator::default_set(),
                 disable_update_prompt: false,
             },
             commands: Vec::new(),
+            profiles: Vec::new(),
+            param_patches: default_param_patches(),
         }
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(try_from = "String")]
 struct FlagSpec {
     label: String,
@@ -431,7 +809,7 @@ impl TryFrom<String> for FlagSpec {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(try_from = "String")]
 struct MultiFlagSpec {
     label: String,
@@ -480,7 +858,7 @@ impl TryFrom<String> for MultiFlagSpec {
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use super::{default_param_patches, CfgCommand, CfgMacroStep, Config, Indicator, ValueType};
 
     #[test]
     fn test_parse_ok() {
@@ -503,4 +881,272 @@ mod tests {
             )
         );
     }
+
+    // A config file with the given commands, and settings or profiles added
+    // after the required settings.
+    fn config_toml(commands: &str, extra: &str) -> String {
+        format!(
+            r#"commands = [{commands}]
+            [settings]
+            log_level = "DEBUG"
+            display = "0"
+            {extra}
+            "#
+        )
+    }
+
+    #[test]
+    fn test_param_patches() {
+        let config = Config::parse(include_str!("../../jdsd_er_practice_tool.toml")).unwrap();
+        assert_eq!(config.param_patches, default_param_patches());
+
+        let config =
+            Config::parse(&format!("param_patches = []\n{}", config_toml("", ""))).unwrap();
+        assert!(config.param_patches.is_empty());
+    }
+
+    #[test]
+    fn test_reload_settings() {
+        let config = Config::parse(include_str!("../../jdsd_er_practice_tool.toml")).unwrap();
+        assert!(config.settings.reload.is_none());
+        assert!(!config.settings.watch_config);
+
+        let config =
+            Config::parse(&config_toml("", "reload = \"rshift+r\"\nwatch_config = true")).unwrap();
+        assert!(config.settings.reload.is_some());
+        assert!(config.settings.watch_config);
+    }
+
+    #[test]
+    fn test_reload() {
+        let profiles = |names: &[&str]| {
+            let profiles = names
+                .iter()
+                .map(|name| format!("[[profiles]]\nname = \"{name}\"\ncommands = []\n"))
+                .collect::<String>();
+            config_toml(r#"{ flag = "no_damage" }"#, &profiles)
+        };
+
+        let mut config = Config::parse(&profiles(&["Bosses", "Glitches"])).unwrap();
+        let mut profile = 2;
+
+        // The profile is found by name.
+        config.reload(&profiles(&["Glitches", "Bosses"]), &mut profile).unwrap();
+        assert_eq!(config.profile_names(), ["Default", "Glitches", "Bosses"]);
+        assert_eq!(profile, 1);
+
+        // Errors keep the config and the profile.
+        assert!(config.reload(&config_toml("{ boh = 3 }", ""), &mut profile).is_err());
+        assert_eq!(config.profile_names(), ["Default", "Glitches", "Bosses"]);
+        assert_eq!(profile, 1);
+
+        // Back to the first profile when it's gone.
+        config.reload(&profiles(&["Bosses"]), &mut profile).unwrap();
+        assert_eq!(config.profile_names(), ["Default", "Bosses"]);
+        assert_eq!(profile, 0);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config = Config::parse(include_str!("../../jdsd_er_practice_tool.toml")).unwrap();
+        assert_eq!(config.profile_names(), ["Default"]);
+        assert_eq!(config.profile_index(Some("Bosses")), 0);
+
+        let profiles = r#"
+            [[profiles]]
+            name = "Bosses"
+            commands = [ { flag = "one_shot" }, { runes = 10000 } ]
+
+            [[profiles]]
+            name = "Glitches"
+            commands = [ { flag = "no_dead" } ]
+        "#;
+        let config = Config::parse(&config_toml(r#"{ flag = "no_damage" }"#, profiles)).unwrap();
+        assert_eq!(config.profile_names(), ["Default", "Bosses", "Glitches"]);
+        assert_eq!(config.profiles().nth(1).unwrap().1.len(), 2);
+        assert_eq!(config.profile_index(Some("Glitches")), 2);
+        assert_eq!(config.profile_index(Some("Default")), 0);
+        assert_eq!(config.profile_index(Some("Missing")), 0);
+        assert_eq!(config.profile_index(None), 0);
+
+        // Without top-level commands, the first profile is the first one listed.
+        let config = Config::parse(&config_toml("", profiles)).unwrap();
+        assert_eq!(config.profile_names(), ["Bosses", "Glitches"]);
+        assert_eq!(config.profile_index(Some("Glitches")), 1);
+        assert_eq!(config.profile_index(Some("Default")), 0);
+    }
+
+    #[test]
+    fn test_write_settings() {
+        let cfg = include_str!("../../jdsd_er_practice_tool.toml");
+        let mut settings = Config::parse(cfg).unwrap().settings;
+
+        // Nothing changed, nothing to write.
+        assert_eq!(settings.write_to(cfg).unwrap(), cfg);
+
+        settings.indicators[0].enabled = false;
+        settings.indicators[2].enabled = true;
+        settings.profile = Some("Boss practice".to_string());
+        let written = settings.write_to(cfg).unwrap();
+        assert!(written.contains(r#"{ indicator = "game_version", enabled = false },"#));
+        assert!(written.contains(r#"{ indicator = "position", enabled = true },"#));
+        assert!(written.contains("# Re-reads this file without restarting the game."));
+
+        let settings = Config::parse(&written).unwrap().settings;
+        assert!(!settings.indicators[0].enabled);
+        assert!(settings.indicators[2].enabled);
+        assert_eq!(settings.profile.as_deref(), Some("Boss practice"));
+
+        // Indicators missing from the file are added.
+        let cfg = "[settings]\nlog_level = \"INFO\" # comment\ndisplay = \"0\"\n";
+        let written = Config::parse(cfg).unwrap().settings.write_to(cfg).unwrap();
+        assert!(written.starts_with(cfg));
+        assert_eq!(
+            Config::parse(&written).unwrap().settings.indicators.len(),
+            Indicator::default_set().len()
+        );
+    }
+
+    #[test]
+    fn test_macro() {
+        let config = Config::parse(&config_toml(
+            r#"
+              { position = "h", save = "rshift+h" },
+              { macro = "Reset fight", hotkey = "f10", steps = [
+                { toggle = "no_damage" },
+                { flag = "one_shot", state = true },
+                { load_position = "h" },
+                { wait = 500 },
+                { speed = 2.0 },
+                { runes = 10000 },
+                { save_position = "slot" },
+                { quitout = true },
+              ]},
+            "#,
+            "",
+        ))
+        .unwrap();
+
+        let Some(CfgCommand::Macro { label, steps, hotkey }) = config.commands.get(1) else {
+            panic!("{:?}", config.commands);
+        };
+        assert_eq!(label, "Reset fight");
+        assert!(hotkey.is_some());
+        assert!(
+            matches!(&steps[0], CfgMacroStep::ToggleFlag { toggle } if toggle.label == "All no damage")
+        );
+        assert!(matches!(steps[1], CfgMacroStep::Flag { state: true, .. }));
+        assert!(
+            matches!(&steps[2], CfgMacroStep::LoadPosition { load_position } if load_position == "h")
+        );
+        assert!(matches!(steps[3], CfgMacroStep::Wait { wait: 500 }));
+        assert!(matches!(steps[7], CfgMacroStep::Quitout { .. }));
+
+        assert!(Config::parse(&config_toml(
+            r#"{ macro = "Bad", steps = [ { toggle = "no_such_flag" } ] }"#,
+            ""
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_macro_slots() {
+        let load = |slot: &str| {
+            format!(r#"{{ macro = "Load", steps = [ {{ load_position = "{slot}" }} ] }}"#)
+        };
+        let parse =
+            |commands: &str, profiles: &str| Config::parse(&config_toml(commands, profiles));
+
+        // Slots saved by position commands, by load hotkey or by name.
+        assert!(parse(&format!(r#"{{ position = "shift+h" }}, {}"#, load("shift+h")), "").is_ok());
+        assert!(
+            parse(&format!(r#"{{ position = "h", slot = "boss" }}, {}"#, load("boss")), "").is_ok()
+        );
+
+        // Slots saved by macros, in another group.
+        let save = r#"{ group = "Saves", commands = [
+            { macro = "Save", steps = [ { save_position = "boss" } ] },
+        ] }"#;
+        assert!(parse(&format!("{save}, {}", load("boss")), "").is_ok());
+
+        // Slots saved in another profile.
+        let profile = format!("[[profiles]]\nname = \"Saves\"\ncommands = [{save}]");
+        assert!(parse(&load("boss"), &profile).is_ok());
+
+        assert_eq!(
+            parse(&format!(r#"{{ position = "h", slot = "boss" }}, {}"#, load("h")), "")
+                .unwrap_err(),
+            r#"Macro "Load" loads position slot "h", which nothing saves to"#
+        );
+    }
+
+    #[test]
+    fn test_custom_commands() {
+        let config = Config::parse(&config_toml(
+            r#"
+              { pointer = "WorldChrMan -> 0x1E508 -> 0x190 + 0x68", mask = 0x8, label = "Custom", hotkey = "f11" },
+              { pointer = "ChrDbgFlags + 0x5", type = "f32", label = "Speed", value = 2.5 },
+              { label = "Just a label" },
+            "#,
+            "",
+        ))
+        .unwrap();
+
+        assert!(matches!(
+            &config.commands[0],
+            CfgCommand::CustomFlag { mask: 0x8, label, hotkey: Some(_), .. } if label == "Custom"
+        ));
+        assert!(matches!(
+            config.commands[1],
+            CfgCommand::CustomValue { value_type: ValueType::F32, value: Some(v), .. } if v == 2.5
+        ));
+        assert!(matches!(config.commands[2], CfgCommand::Label { .. }));
+
+        // Broken custom commands are errors, not labels, even in groups.
+        let parse = |command: &str| {
+            Config::parse(&config_toml(
+                &format!(r#"{{ group = "G", commands = [{command}] }}"#),
+                "",
+            ))
+        };
+        let err = parse(r#"{ pointer = "0x10 -> 0x8", mask = 1, label = "Bad" }"#).unwrap_err();
+        assert!(err.starts_with(r#"Invalid pointer for "Bad": "#), "{err}");
+        assert_eq!(
+            parse(r#"{ pointer = "ChrDbgFlags", label = "Bad" }"#).unwrap_err(),
+            r#"Invalid custom command "Bad": check its mask, type and hotkey"#
+        );
+        assert!(parse(
+            r#"{ pointer = "ChrDbgFlags", mask = 1, label = "Bad", hotkey = "no such key" }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_on_load() {
+        let config = Config::parse(&config_toml(
+            r#"
+              { flag = "no_stamina_consume", on_load = true },
+              { flags = ["show_all_map_layers", "show_all_graces"], label = "Graces", persist = true },
+              { flag = "no_dead", hotkey = "6" },
+            "#,
+            "",
+        ))
+        .unwrap();
+
+        assert!(matches!(config.commands[0], CfgCommand::Flag {
+            on_load: true,
+            persist: false,
+            ..
+        }));
+        assert!(matches!(config.commands[1], CfgCommand::MultiFlagUser {
+            on_load: false,
+            persist: true,
+            ..
+        }));
+        assert!(matches!(config.commands[2], CfgCommand::Flag {
+            on_load: false,
+            persist: false,
+            ..
+        }));
+    }
 }
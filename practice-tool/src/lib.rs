@@ -28,16 +28,13 @@ use std::{mem, ptr, thread};
 use hudhook::hooks::dx12::ImguiDx12Hooks;
 use hudhook::tracing::error;
 use hudhook::{eject, Hudhook};
-use libeldenring::codegen::base_addresses::BaseAddresses;
+use libeldenring::patch::{Patch, PATCHES};
 use libeldenring::version;
 use once_cell::sync::Lazy;
 use practice_tool::PracticeTool;
 use windows::core::{s, w, GUID, HRESULT, PCWSTR};
 use windows::Win32::Foundation::{HINSTANCE, MAX_PATH};
 use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress, LoadLibraryW};
-use windows::Win32::System::Memory::{
-    VirtualProtect, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS,
-};
 use windows::Win32::System::SystemInformation::GetSystemDirectoryW;
 use windows::Win32::System::SystemServices::DLL_PROCESS_ATTACH;
 use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_RSHIFT};
@@ -85,63 +82,41 @@ unsafe extern "stdcall" fn DirectInput8Create(
     (DIRECTINPUT8CREATE)(hinst, dwversion, riidltf, ppvout, punkouter)
 }
 
-unsafe fn apply_no_logo() {
-    let module_base = GetModuleHandleW(None).unwrap();
-    let offset = BaseAddresses::from(version::get_version()).func_remove_intro_screens;
-
-    let ptr = (module_base.0 as usize + offset) as *mut [u8; 2];
-    let mut old = PAGE_PROTECTION_FLAGS(0);
-    if *ptr == [0x74, 0x53] && VirtualProtect(ptr as _, 2, PAGE_EXECUTE_READWRITE, &mut old).is_ok()
-    {
-        (*ptr) = [0x90, 0x90];
-        VirtualProtect(ptr as _, 2, old, &mut old).ok();
+fn apply_patch(name: &'static str, offset: usize, original: &[u8], replacement: &[u8]) {
+    let module_base = unsafe { GetModuleHandleW(None).unwrap() }.0 as usize;
+    let patch = Patch {
+        name,
+        address: module_base + offset,
+        original: original.to_vec(),
+        replacement: replacement.to_vec(),
+    };
+
+    if let Err(e) = PATCHES.lock().apply(patch) {
+        error!("{e}");
     }
 }
 
-unsafe fn apply_event_patch() {
-    let module_base = GetModuleHandleW(None).unwrap();
-
-    let offset_1 = BaseAddresses::from(version::get_version()).event_patch1;
-    let offset_2 = BaseAddresses::from(version::get_version()).event_patch2;
-
-    let ptr_1 = (module_base.0 as usize + offset_1) as *mut [u8; 2];
-    let mut old_1 = PAGE_PROTECTION_FLAGS(0);
-    if *ptr_1 == [0x32, 0xC0]
-        && VirtualProtect(ptr_1 as _, 2, PAGE_EXECUTE_READWRITE, &mut old_1).is_ok()
-    {
-        (*ptr_1) = [0xB0, 0x01];
-        VirtualProtect(ptr_1 as _, 2, old_1, &mut old_1).ok();
-    }
-
-    let ptr_2 = (module_base.0 as usize + offset_2) as *mut [u8; 2];
-    let mut old_2 = PAGE_PROTECTION_FLAGS(0);
-    if *ptr_2 == [0x32, 0xC0]
-        && VirtualProtect(ptr_2 as _, 2, PAGE_EXECUTE_READWRITE, &mut old_2).is_ok()
-    {
-        (*ptr_2) = [0xB0, 0x01];
-        VirtualProtect(ptr_2 as _, 2, old_2, &mut old_2).ok();
-    }
+fn apply_no_logo() {
+    let offset = version::get_base_addresses().func_remove_intro_screens;
+    apply_patch("Remove intro screens", offset, &[0x74, 0x53], &[0x90, 0x90]);
 }
 
-unsafe fn apply_font_patch() {
-    let module_base = GetModuleHandleW(None).unwrap();
-    let offset = BaseAddresses::from(version::get_version()).font_patch;
+fn apply_event_patch() {
+    let base_addresses = version::get_base_addresses();
+    apply_patch("Event draw 1", base_addresses.event_patch1, &[0x32, 0xC0], &[0xB0, 0x01]);
+    apply_patch("Event draw 2", base_addresses.event_patch2, &[0x32, 0xC0], &[0xB0, 0x01]);
+}
 
-    let ptr = (module_base.0 as usize + offset) as *mut u8;
-    let mut old = PAGE_PROTECTION_FLAGS(0);
-    if *ptr == 0x48 && VirtualProtect(ptr as _, 1, PAGE_EXECUTE_READWRITE, &mut old).is_ok() {
-        (*ptr) = 0xC3;
-        VirtualProtect(ptr as _, 1, old, &mut old).ok();
-    }
+fn apply_font_patch() {
+    let offset = version::get_base_addresses().font_patch;
+    apply_patch("Font", offset, &[0x48], &[0xC3]);
 }
 
 fn start_practice_tool(hmodule: HINSTANCE) {
     let practice_tool = PracticeTool::new();
 
-    unsafe {
-        apply_event_patch(); // Needed for event draw
-        apply_font_patch(); // Needed for event draw & altimeter
-    }
+    apply_event_patch(); // Needed for event draw
+    apply_font_patch(); // Needed for event draw & altimeter
 
     if let Err(e) = Hudhook::builder()
         .with::<ImguiDx12Hooks>(practice_tool)
@@ -150,6 +125,7 @@ fn start_practice_tool(hmodule: HINSTANCE) {
         .apply()
     {
         error!("Couldn't apply hooks: {e:?}");
+        PATCHES.lock().revert_all().ok();
         eject();
     }
 }
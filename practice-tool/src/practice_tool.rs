@@ -1,14 +1,16 @@
 use std::fmt::Write;
+use std::path::PathBuf;
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 
 use const_format::formatcp;
 use hudhook::tracing::metadata::LevelFilter;
 use hudhook::tracing::*;
 use hudhook::{ImguiRenderLoop, RenderContext};
 use imgui::*;
+use libeldenring::patch::PATCHES;
 use libeldenring::prelude::*;
-use libeldenring::version;
+use libeldenring::{offsets, version};
 use pkg_version::*;
 use practice_tool_core::crossbeam_channel::{self, Receiver, Sender};
 use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
@@ -38,7 +40,10 @@ enum UiState {
 }
 
 pub(crate) struct PracticeTool {
+    config: Config,
     settings: Settings,
+    profile: usize,
+    profile_names: Vec<String>,
     pointers: Pointers,
     version_label: String,
     widgets: Vec<Box<dyn Widget>>,
@@ -49,6 +54,9 @@ pub(crate) struct PracticeTool {
     ui_state: UiState,
     fonts: Option<FontIDs>,
     config_err: Option<String>,
+    // Modification time of the config file, polled if `watch_config` is set.
+    config_mtime: Option<SystemTime>,
+    config_checked: Instant,
     update_available: Update,
 
     position_bufs: [String; 4],
@@ -69,38 +77,9 @@ impl PracticeTool {
         hudhook::alloc_console().ok();
         log_panics::init();
 
-        fn load_config() -> Result<Config, String> {
-            let config_path = crate::util::get_dll_path()
-                .map(|mut path| {
-                    path.pop();
-                    path.push("jdsd_er_practice_tool.toml");
-                    path
-                })
-                .ok_or_else(|| "Couldn't find config file".to_string())?;
-
-            if !config_path.exists() {
-                std::fs::write(&config_path, include_str!("../../jdsd_er_practice_tool.toml"))
-                    .map_err(|e| format!("Couldn't write default config file: {}", e))?;
-            }
-
-            let config_content = std::fs::read_to_string(config_path)
-                .map_err(|e| format!("Couldn't read config file: {}", e))?;
-            println!("{}", config_content);
-            Config::parse(&config_content).map_err(String::from)
-        }
-
         let (config, config_err) = match load_config() {
             Ok(config) => (config, None),
-            Err(e) => (
-                Config::default(),
-                Some({
-                    error!("{}", e);
-                    format!(
-                        "Configuration error, please review your jdsd_er_practice_tool.toml \
-                         file.\n\n{e}"
-                    )
-                }),
-            ),
+            Err(e) => (Config::default(), Some(config_error(e))),
         };
 
         let log_file = util::get_dll_path()
@@ -150,38 +129,68 @@ impl PracticeTool {
             hudhook::free_console().ok();
         }
 
+        let (log_tx, log_rx) = crossbeam_channel::unbounded();
+
+        let param_patches = config.param_patches.clone();
+        let patches_log_tx = log_tx.clone();
         wait_option_thread(
             || unsafe {
                 let mut params = PARAMS.write();
-                if let Err(e) = params.refresh() {
-                    error!("{}", e);
-                }
-                params.get_equip_param_goods()
+                params.refresh().map_err(|e| error!("{}", e)).ok()
             },
-            |mut epg| {
-                if let Some(spectral_steed_whistle) =
-                    epg.find(|i| i.id == 130).and_then(|p| p.param)
-                {
-                    spectral_steed_whistle.icon_id = 12;
-                };
+            move |()| {
+                for e in PARAMS.read().apply_patches(&param_patches) {
+                    error!("Couldn't apply param patch: {}", e);
+                    patches_log_tx.send(format!("Param patch error: {e}")).ok();
+                }
             },
         );
 
         let update_available =
             if config.settings.disable_update_prompt { Update::UpToDate } else { Update::check() };
 
+        fn load_offsets() -> Result<(), String> {
+            let Some(offsets_path) = crate::util::get_dll_path().map(|mut path| {
+                path.pop();
+                path.push("jdsd_er_offsets.toml");
+                path
+            }) else {
+                return Ok(());
+            };
+
+            if !offsets_path.exists() {
+                return Ok(());
+            }
+
+            offsets::load_override(&offsets_path)
+        }
+
+        let config_err = match load_offsets() {
+            Ok(()) => config_err,
+            Err(e) => {
+                error!("{}", e);
+                Some(config_err.unwrap_or_else(|| {
+                    format!("Offsets error, please review your jdsd_er_offsets.toml file.\n\n{e}")
+                }))
+            },
+        };
+
         let pointers = Pointers::new();
         let version_label = {
             let (maj, min, patch) = version::get_version().into();
             format!("Game Ver {}.{:02}.{}", maj, min, patch)
         };
         let settings = config.settings.clone();
-        let widgets = config.make_commands(&pointers);
-        let (log_tx, log_rx) = crossbeam_channel::unbounded();
+        let profile_names = config.profile_names();
+        let profile = config.profile_index(settings.profile.as_deref());
+        let widgets = config.make_commands(profile, &pointers);
         info!("Practice tool initialized");
 
         PracticeTool {
+            config,
             settings,
+            profile,
+            profile_names,
             pointers,
             version_label,
             widgets,
@@ -191,6 +200,8 @@ impl PracticeTool {
             fonts: None,
             ui_state: UiState::Closed,
             config_err,
+            config_mtime: config_mtime(),
+            config_checked: Instant::now(),
             position_prev: Default::default(),
             position_bufs: Default::default(),
             position_change_buf: Default::default(),
@@ -203,6 +214,62 @@ impl PracticeTool {
         }
     }
 
+    // Rebuilds the widgets and settings from the config file. On errors, the
+    // current ones are kept.
+    fn reload_config(&mut self) {
+        self.config_mtime = config_mtime();
+
+        match read_config().and_then(|cfg| self.config.reload(&cfg, &mut self.profile)) {
+            Ok(()) => {
+                self.profile_names = self.config.profile_names();
+                self.settings = self.config.settings.clone();
+                self.widgets = self.config.make_commands(self.profile, &self.pointers);
+                self.config_err = None;
+                self.log_tx.send("Config reloaded".to_string()).ok();
+            },
+            Err(e) => {
+                self.config_err = Some(config_error(e));
+                self.log_tx.send("Config error, keeping the previous config".to_string()).ok();
+            },
+        }
+    }
+
+    fn select_profile(&mut self, profile: usize) {
+        let Some(name) = self.profile_names.get(profile) else {
+            return;
+        };
+        self.log_tx.send(format!("Profile: {name}")).ok();
+        self.settings.profile = Some(name.clone());
+        self.profile = profile;
+        self.widgets = self.config.make_commands(profile, &self.pointers);
+        self.save_settings();
+    }
+
+    // Writes the settings changed from the UI back to the config file.
+    fn save_settings(&mut self) {
+        // Don't overwrite a config file that failed to load.
+        if self.config_err.is_some() {
+            return;
+        }
+
+        let result =
+            config_path().ok_or_else(|| "Couldn't find config file".to_string()).and_then(|path| {
+                let cfg = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Couldn't read config file: {e}"))?;
+                std::fs::write(&path, self.settings.write_to(&cfg)?)
+                    .map_err(|e| format!("Couldn't write config file: {e}"))
+            });
+
+        match result {
+            // Our own write is not a change to reload.
+            Ok(()) => self.config_mtime = config_mtime(),
+            Err(e) => {
+                error!("{e}");
+                self.log_tx.send(format!("Couldn't save settings: {e}")).ok();
+            },
+        }
+    }
+
     fn render_visible(&mut self, ui: &imgui::Ui) {
         let [dw, dh] = { ui.io().display_size };
         ui.window("##tool_window")
@@ -220,6 +287,14 @@ impl PracticeTool {
                     ui.text(e);
                 }
 
+                if self.profile_names.len() > 1 {
+                    let mut profile = self.profile;
+                    let _tok = ui.push_item_width(BUTTON_WIDTH * scaling_factor(ui));
+                    if ui.combo_simple_string("##profile", &mut profile, &self.profile_names) {
+                        self.select_profile(profile);
+                    }
+                }
+
                 if !(ui.io().want_capture_keyboard && ui.is_any_item_active()) {
                     for w in self.widgets.iter_mut() {
                         w.interact(ui);
@@ -230,6 +305,13 @@ impl PracticeTool {
                     w.render(ui);
                 }
 
+                if ui.button_with_size("Reload config", [
+                    BUTTON_WIDTH * scaling_factor(ui),
+                    BUTTON_HEIGHT,
+                ]) {
+                    self.reload_config();
+                }
+
                 if ui.button_with_size("Close", [BUTTON_WIDTH * scaling_factor(ui), BUTTON_HEIGHT])
                 {
                     self.ui_state = UiState::Closed;
@@ -244,6 +326,9 @@ impl PracticeTool {
                 {
                     self.ui_state = UiState::Closed;
                     self.pointers.cursor_show.set(false);
+                    if let Err(e) = PATCHES.lock().revert_all() {
+                        error!("{e}");
+                    }
                     hudhook::eject();
                 }
             });
@@ -298,6 +383,7 @@ impl PracticeTool {
                         );
                         ui.separator();
 
+                        let mut changed = false;
                         for indicator in &mut self.settings.indicators {
                             let label = match indicator.indicator {
                                 IndicatorType::GameVersion => "Game Version",
@@ -314,6 +400,7 @@ impl PracticeTool {
 
                             if ui.checkbox(label, &mut state) {
                                 indicator.enabled = state;
+                                changed = true;
                             }
 
                             if let IndicatorType::FrameCount = indicator.indicator {
@@ -334,6 +421,10 @@ impl PracticeTool {
                             }
                         }
 
+                        if changed {
+                            self.save_settings();
+                        }
+
                         ui.separator();
 
                         let btn_close_width =
@@ -398,6 +489,11 @@ impl PracticeTool {
                             self.settings.display
                         ));
                         ui.separator();
+                        ui.text("Game patches:");
+                        for (patch, status) in PATCHES.lock().iter() {
+                            ui.text(format!("  {}: {}", patch.name, status));
+                        }
+                        ui.separator();
                         ui.text("-- johndisandonato");
                         ui.text("   https://twitch.tv/johndisandonato");
                         if ui.is_item_clicked() {
@@ -656,6 +752,23 @@ impl ImguiRenderLoop for PracticeTool {
 
         let display = self.settings.display.is_pressed(ui);
         let hide = self.settings.hide.map(|k| k.is_pressed(ui)).unwrap_or(false);
+        let reload = self.settings.reload.map(|k| k.is_pressed(ui)).unwrap_or(false);
+        let cycle_profile = self.settings.cycle_profile.map(|k| k.is_pressed(ui)).unwrap_or(false);
+
+        if cycle_profile && !ui.io().want_capture_keyboard && !self.profile_names.is_empty() {
+            self.select_profile((self.profile + 1) % self.profile_names.len());
+        }
+
+        if reload && !ui.io().want_capture_keyboard {
+            self.reload_config();
+        } else if self.settings.watch_config
+            && self.config_checked.elapsed() > Duration::from_secs(1)
+        {
+            self.config_checked = Instant::now();
+            if config_mtime() != self.config_mtime {
+                self.reload_config();
+            }
+        }
 
         self.framecount += 1;
 
@@ -721,6 +834,38 @@ impl ImguiRenderLoop for PracticeTool {
     }
 }
 
+fn config_path() -> Option<PathBuf> {
+    let mut path = util::get_dll_path()?;
+    path.pop();
+    path.push("jdsd_er_practice_tool.toml");
+    Some(path)
+}
+
+fn config_mtime() -> Option<SystemTime> {
+    std::fs::metadata(config_path()?).and_then(|m| m.modified()).ok()
+}
+
+fn load_config() -> Result<Config, String> {
+    Config::parse(&read_config()?)
+}
+
+fn read_config() -> Result<String, String> {
+    let config_path = config_path().ok_or_else(|| "Couldn't find config file".to_string())?;
+
+    if !config_path.exists() {
+        std::fs::write(&config_path, include_str!("../../jdsd_er_practice_tool.toml"))
+            .map_err(|e| format!("Couldn't write default config file: {}", e))?;
+    }
+
+    debug!("Loading config from {}", config_path.display());
+    std::fs::read_to_string(config_path).map_err(|e| format!("Couldn't read config file: {}", e))
+}
+
+fn config_error(e: String) -> String {
+    error!("{}", e);
+    format!("Configuration error, please review your jdsd_er_practice_tool.toml file.\n\n{e}")
+}
+
 // Display some imgui debug information. Very expensive.
 fn imgui_debug(ui: &Ui) {
     let io = ui.io();
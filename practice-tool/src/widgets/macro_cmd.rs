@@ -0,0 +1,303 @@
+use std::time::{Duration, Instant};
+
+use hudhook::tracing::error;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::position::PositionStorage;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+use super::position::SavePosition;
+
+/// One step of a macro, as given in the config file.
+pub(crate) enum MacroStep {
+    /// Sets a flag, or toggles it if there is no state.
+    Flag {
+        label: String,
+        flag: Bitflag<u8>,
+        state: Option<bool>,
+    },
+    LoadPosition(String),
+    SavePosition(String),
+    Speed(f32),
+    Runes(u32),
+    Quitout,
+    Wait(u64),
+}
+
+enum Action<B: MemoryBackend = InProcess> {
+    Flag { label: String, flag: Bitflag<u8, B>, state: Option<bool> },
+    LoadPosition(String, SavePosition),
+    SavePosition(SavePosition),
+    Speed([PointerChain<f32, B>; 2], f32),
+    Runes(PointerChain<u32, B>, u32),
+    Quitout(PointerChain<u8, B>),
+    Wait(Duration),
+}
+
+impl Action {
+    fn new(step: MacroStep, chains: &Pointers) -> Self {
+        let slot = |slot: &str| {
+            SavePosition::new(
+                chains.global_position.clone(),
+                chains.chunk_position.clone(),
+                chains.torrent_chunk_position.clone(),
+                0.0,
+                Some(slot),
+            )
+        };
+
+        match step {
+            MacroStep::Flag { label, flag, state } => Action::Flag { label, flag, state },
+            MacroStep::LoadPosition(name) => {
+                let storage = slot(&name);
+                Action::LoadPosition(name, storage)
+            },
+            MacroStep::SavePosition(name) => Action::SavePosition(slot(&name)),
+            MacroStep::Speed(speed) => Action::Speed(
+                [chains.animation_speed.clone(), chains.torrent_animation_speed.clone()],
+                speed,
+            ),
+            MacroStep::Runes(amount) => Action::Runes(chains.runes.clone(), amount),
+            MacroStep::Quitout => Action::Quitout(chains.quitout.clone()),
+            MacroStep::Wait(ms) => Action::Wait(Duration::from_millis(ms)),
+        }
+    }
+}
+
+impl<B: MemoryBackend> Action<B> {
+    fn run(&mut self) -> Result<(), String> {
+        match self {
+            Action::Flag { label, flag, state: Some(state) } => {
+                flag.get().ok_or_else(|| format!("Couldn't read flag \"{label}\""))?;
+                flag.set(*state);
+            },
+            Action::Flag { label, flag, state: None } => {
+                flag.toggle().ok_or_else(|| format!("Couldn't toggle flag \"{label}\""))?;
+            },
+            Action::LoadPosition(slot, storage) => {
+                // Loading would teleport the player to the origin of map 0.
+                if !storage.is_saved() {
+                    return Err(format!("Position slot \"{slot}\" was never saved"));
+                }
+                check_position(storage)?;
+                storage.load();
+            },
+            Action::SavePosition(storage) => {
+                check_position(storage)?;
+                storage.save();
+            },
+            Action::Speed([ptr, ptr_torrent], speed) => {
+                ptr.write(*speed).ok_or("Couldn't write the animation speed")?;
+                // Torrent is not always there.
+                ptr_torrent.write(*speed);
+            },
+            Action::Runes(ptr, amount) => {
+                let current = ptr.read().ok_or("Couldn't read the runes")?;
+                ptr.write(current.saturating_add(*amount)).ok_or("Couldn't write the runes")?;
+            },
+            Action::Quitout(ptr) => {
+                ptr.write(1).ok_or("Couldn't quit out")?;
+            },
+            Action::Wait(_) => {},
+        }
+
+        Ok(())
+    }
+}
+
+// `SavePosition` does nothing when the position can't be read, which would
+// silently break the rest of the macro.
+fn check_position(storage: &mut SavePosition) -> Result<(), String> {
+    // Refreshes `is_valid` from the current position.
+    storage.display_current();
+    if storage.is_valid() {
+        Ok(())
+    } else {
+        Err("Couldn't read the player position".to_string())
+    }
+}
+
+struct Macro<B: MemoryBackend = InProcess> {
+    label: String,
+    actions: Vec<Action<B>>,
+    hotkey: Option<Key>,
+    // The next step to run, and when it may run, while the macro is running.
+    next: Option<(usize, Instant)>,
+    logs: Vec<String>,
+}
+
+impl<B: MemoryBackend> Macro<B> {
+    fn start(&mut self) {
+        if self.next.is_none() {
+            self.next = Some((0, Instant::now()));
+        }
+    }
+
+    // Runs at most one step per frame, so that the game sees the effect of
+    // each step before the next one.
+    fn step(&mut self) {
+        let Some((idx, at)) = self.next else {
+            return;
+        };
+        if Instant::now() < at {
+            return;
+        }
+
+        let Some(action) = self.actions.get_mut(idx) else {
+            self.next = None;
+            return;
+        };
+
+        self.next = match action.run() {
+            Ok(()) => match action {
+                Action::Wait(duration) => Some((idx + 1, Instant::now() + *duration)),
+                _ => Some((idx + 1, Instant::now())),
+            },
+            Err(e) => {
+                let log = format!("{}: step {} failed: {e}", self.label, idx + 1);
+                error!("{log}");
+                self.logs.push(log);
+                None
+            },
+        };
+    }
+}
+
+impl<B: MemoryBackend + Send + Sync> Widget for Macro<B> {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let label = match self.next {
+            Some((idx, _)) => {
+                format!("{} ({}/{})##macro", self.label, idx + 1, self.actions.len())
+            },
+            None => self.label.clone(),
+        };
+
+        if ui.button_with_size(label, [BUTTON_WIDTH * scale, BUTTON_HEIGHT]) {
+            self.start();
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if self.hotkey.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.start();
+        }
+
+        self.step();
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+pub(crate) fn macro_widget(
+    label: &str,
+    steps: Vec<MacroStep>,
+    chains: &Pointers,
+    hotkey: Option<Key>,
+) -> Box<dyn Widget> {
+    let label = match hotkey {
+        Some(key) => format!("{label} ({key})"),
+        None => label.to_string(),
+    };
+
+    Box::new(Macro {
+        label,
+        actions: steps.into_iter().map(|step| Action::new(step, chains)).collect(),
+        hotkey,
+        next: None,
+        logs: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use super::*;
+
+    const BASE: usize = 0x1000;
+
+    fn null_position() -> Position {
+        let chain = || PointerChain::new(&[0]);
+        Position {
+            x: chain(),
+            y: chain(),
+            z: chain(),
+            angle1: chain(),
+            angle2: chain(),
+            map_id: None,
+        }
+    }
+
+    #[test]
+    fn test_steps() {
+        let image = MemoryImage::new(BASE, 0x10);
+        let flag = Bitflag::new(PointerChain::with_backend(image.clone(), &[BASE]), 0x2);
+        let runes = PointerChain::<u32, _>::with_backend(image.clone(), &[BASE + 0x4]);
+        let quitout = PointerChain::<u8, _>::with_backend(image.clone(), &[BASE + 0x8]);
+        runes.write(100).unwrap();
+
+        let never_saved = SavePosition::new(
+            null_position(),
+            null_position(),
+            null_position(),
+            0.0,
+            Some("macro test slot"),
+        );
+
+        let mut m = Macro {
+            label: "Test".to_string(),
+            actions: vec![
+                Action::Flag { label: "Flag".to_string(), flag: flag.clone(), state: Some(true) },
+                Action::Wait(Duration::from_millis(50)),
+                Action::Flag { label: "Flag".to_string(), flag: flag.clone(), state: None },
+                Action::Runes(runes.clone(), 1000),
+                Action::LoadPosition("macro test slot".to_string(), never_saved),
+                Action::Quitout(quitout.clone()),
+            ],
+            hotkey: None,
+            next: None,
+            logs: Vec::new(),
+        };
+
+        // Nothing runs until the macro is started.
+        m.step();
+        assert_eq!(flag.get(), Some(false));
+
+        // One step at a time.
+        m.start();
+        m.step();
+        assert_eq!(flag.get(), Some(true));
+        m.step();
+        assert!(matches!(m.next, Some((2, _))));
+
+        // The next step waits.
+        m.step();
+        assert_eq!(flag.get(), Some(true));
+        sleep(Duration::from_millis(60));
+        m.step();
+        assert_eq!(flag.get(), Some(false));
+
+        m.step();
+        assert_eq!(runes.read(), Some(1100));
+
+        // A failing step stops the macro.
+        m.step();
+        assert!(m.next.is_none());
+        assert_eq!(m.logs, [
+            "Test: step 5 failed: Position slot \"macro test slot\" was never saved"
+        ]);
+        m.step();
+        assert_eq!(quitout.read(), Some(0));
+
+        // Starting again runs it from the top.
+        m.start();
+        m.step();
+        assert!(matches!(m.next, Some((1, _))));
+    }
+}
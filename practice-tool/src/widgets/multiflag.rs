@@ -1,20 +1,20 @@
-use libeldenring::memedit::Bitflag;
+use libeldenring::memedit::{Bitflag, InProcess, MemoryBackend};
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::flag::{Flag, FlagWidget};
 use practice_tool_core::widgets::Widget;
 
 #[derive(Debug)]
-struct MultiFlag {
-    bitflags: Vec<Bitflag<u8>>,
+struct MultiFlag<B: MemoryBackend = InProcess> {
+    bitflags: Vec<Bitflag<u8, B>>,
 }
 
-impl MultiFlag {
-    fn new(bitflags: Vec<Bitflag<u8>>) -> Self {
+impl<B: MemoryBackend> MultiFlag<B> {
+    fn new(bitflags: Vec<Bitflag<u8, B>>) -> Self {
         Self { bitflags }
     }
 }
 
-impl Flag for MultiFlag {
+impl<B: MemoryBackend> Flag for MultiFlag<B> {
     fn set(&mut self, value: bool) {
         for flag in &self.bitflags {
             flag.set(value);
@@ -33,3 +33,32 @@ pub(crate) fn multi_flag(
 ) -> Box<dyn Widget> {
     Box::new(FlagWidget::new(label, MultiFlag::new(bitflags), key))
 }
+
+#[cfg(test)]
+mod tests {
+    use libeldenring::memedit::{MemoryImage, PointerChain};
+
+    use super::*;
+
+    #[test]
+    fn test_multi_flag() {
+        let image = MemoryImage::new(0x1000, 0x2);
+        let flags = [(0x1000, 0x1), (0x1001, 0x80)].map(|(addr, mask)| {
+            Bitflag::new(PointerChain::with_backend(image.clone(), &[addr]), mask)
+        });
+        let mut multi_flag = MultiFlag::new(flags.to_vec());
+
+        multi_flag.set(true);
+        assert_eq!(image.snapshot(), [0x1, 0x80]);
+        assert_eq!(multi_flag.get(), Some(true));
+
+        // The state is the first flag's.
+        flags[0].set(false);
+        assert_eq!(multi_flag.get(), Some(false));
+
+        multi_flag.set(false);
+        assert_eq!(image.snapshot(), [0, 0]);
+
+        assert_eq!(MultiFlag::<MemoryImage>::new(Vec::new()).get(), None);
+    }
+}
@@ -14,7 +14,7 @@ pub(crate) fn nudge_position(
     key_nudge_down: Option<Key>,
 ) -> Box<dyn Widget> {
     Box::new(NudgePosition::new(
-        SavePosition::new(global_position, chunk_position, torrent_chunk_position, nudge),
+        SavePosition::new(global_position, chunk_position, torrent_chunk_position, nudge, None),
         key_nudge_up,
         key_nudge_down,
     ))
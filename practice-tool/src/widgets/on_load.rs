@@ -0,0 +1,157 @@
+use libeldenring::memedit::{Bitflag, InProcess, MemoryBackend};
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::widgets::Widget;
+
+// Turns flags on as soon as their pointer chains resolve. Many of them are
+// reset when the game rebuilds its structures on a loading screen: with
+// `persist`, they are turned on again every time the chains resolve after
+// having failed.
+struct OnLoad<B: MemoryBackend = InProcess> {
+    widget: Box<dyn Widget>,
+    flags: Vec<Bitflag<u8, B>>,
+    persist: bool,
+    resolved: bool,
+    applied: bool,
+}
+
+impl<B: MemoryBackend> OnLoad<B> {
+    fn apply(&mut self) {
+        let resolved = self.flags.iter().all(|flag| flag.get().is_some());
+
+        if resolved && !self.resolved && (self.persist || !self.applied) {
+            for flag in &self.flags {
+                flag.set(true);
+            }
+            self.applied = true;
+        }
+
+        self.resolved = resolved;
+    }
+}
+
+impl<B: MemoryBackend + Send + Sync> Widget for OnLoad<B> {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.apply();
+        self.widget.render(ui);
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.widget.render_closed(ui);
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.apply();
+        self.widget.interact(ui);
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        self.widget.log(tx);
+    }
+}
+
+pub(crate) fn on_load(
+    widget: Box<dyn Widget>,
+    flags: Vec<Bitflag<u8>>,
+    on_load: bool,
+    persist: bool,
+) -> Box<dyn Widget> {
+    if on_load || persist {
+        Box::new(OnLoad { widget, flags, persist, resolved: false, applied: false })
+    } else {
+        widget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libeldenring::memedit::{MemoryImage, PointerChain};
+
+    use super::*;
+
+    const BASE: usize = 0x1000;
+
+    struct Empty;
+
+    impl Widget for Empty {
+        fn render(&mut self, _ui: &imgui::Ui) {}
+    }
+
+    // Two flags behind the pointer at `BASE`, which the game nulls while
+    // loading.
+    fn on_load(persist: bool) -> (MemoryImage, Vec<Bitflag<u8, MemoryImage>>, OnLoad<MemoryImage>) {
+        let image = MemoryImage::new(BASE, 0x20);
+        let flags = [(0x10, 0x1), (0x11, 0x4)]
+            .map(|(offset, mask)| {
+                Bitflag::new(PointerChain::with_backend(image.clone(), &[BASE, offset]), mask)
+            })
+            .to_vec();
+        let on_load = OnLoad {
+            widget: Box::new(Empty),
+            flags: flags.clone(),
+            persist,
+            resolved: false,
+            applied: false,
+        };
+        (image, flags, on_load)
+    }
+
+    fn resolve(image: &MemoryImage) {
+        image.write(BASE, &BASE.to_ne_bytes()).unwrap();
+    }
+
+    fn fail(image: &MemoryImage) {
+        image.write(BASE, &0usize.to_ne_bytes()).unwrap();
+    }
+
+    fn states(flags: &[Bitflag<u8, MemoryImage>]) -> Vec<Option<bool>> {
+        flags.iter().map(|flag| flag.get()).collect()
+    }
+
+    #[test]
+    fn test_apply_once() {
+        let (image, flags, mut on_load) = on_load(false);
+
+        // Nothing to set before the chains resolve.
+        on_load.apply();
+        assert_eq!(states(&flags), [None, None]);
+
+        resolve(&image);
+        on_load.apply();
+        assert_eq!(states(&flags), [Some(true), Some(true)]);
+
+        // Turning a flag off by hand sticks.
+        flags[0].set(false);
+        on_load.apply();
+        assert_eq!(states(&flags), [Some(false), Some(true)]);
+
+        // So does a loading screen.
+        fail(&image);
+        on_load.apply();
+        image.write(BASE + 0x10, &[0, 0]).unwrap();
+        resolve(&image);
+        on_load.apply();
+        assert_eq!(states(&flags), [Some(false), Some(false)]);
+    }
+
+    #[test]
+    fn test_apply_persist() {
+        let (image, flags, mut on_load) = on_load(true);
+
+        resolve(&image);
+        on_load.apply();
+        assert_eq!(states(&flags), [Some(true), Some(true)]);
+
+        // Only set again after the chains fail.
+        flags[0].set(false);
+        on_load.apply();
+        assert_eq!(states(&flags), [Some(false), Some(true)]);
+
+        // The game resets the flags while loading.
+        fail(&image);
+        on_load.apply();
+        image.write(BASE + 0x10, &[0, 0]).unwrap();
+        resolve(&image);
+        on_load.apply();
+        assert_eq!(states(&flags), [Some(true), Some(true)]);
+    }
+}
@@ -1,55 +1,89 @@
+use std::collections::HashMap;
 use std::fmt::Write;
+use std::sync::Arc;
 
+use libeldenring::memedit::{InProcess, MemoryBackend};
 use libeldenring::prelude::Position as ErPosition;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::nudge_position::NudgePositionStorage;
 use practice_tool_core::widgets::position::{Position, PositionStorage};
 use practice_tool_core::widgets::Widget;
 
-pub(super) struct SavePosition {
-    global_position: ErPosition,
-    chunk_position: ErPosition,
-    torrent_chunk_position: ErPosition,
+pub(super) struct SavePosition<B: MemoryBackend = InProcess> {
+    global_position: ErPosition<B>,
+    chunk_position: ErPosition<B>,
+    torrent_chunk_position: ErPosition<B>,
 
     label_current: String,
     label_stored: String,
     valid: bool,
     nudge: f32,
 
-    saved_position: [f32; 5],
-    saved_map_id: u32,
+    saved: Slot,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Saved {
+    position: [f32; 5],
+    map_id: u32,
+}
+
+// Empty until a position is saved.
+type Slot = Arc<Mutex<Option<Saved>>>;
+
+// Saved positions by slot name, shared between position widgets and macros.
+// They outlive the widgets, so reloading the config keeps them.
+static SLOTS: Lazy<Mutex<HashMap<String, Slot>>> = Lazy::new(Default::default);
+
+// Slots named after a hotkey go through `Key`, so that one hotkey spelled in
+// two ways is one slot.
+pub(crate) fn slot_name(name: &str) -> String {
+    name.parse::<Key>().map(|key| key.to_string()).unwrap_or_else(|_| name.to_string())
 }
 
-impl SavePosition {
+impl<B: MemoryBackend> SavePosition<B> {
+    /// Positions saved in a named slot can be loaded by any widget using the
+    /// same slot; without a name, the storage is private.
     pub(super) fn new(
-        global_position: ErPosition,
-        chunk_position: ErPosition,
-        torrent_chunk_position: ErPosition,
+        global_position: ErPosition<B>,
+        chunk_position: ErPosition<B>,
+        torrent_chunk_position: ErPosition<B>,
         nudge: f32,
+        slot: Option<&str>,
     ) -> Self {
+        let saved = match slot {
+            Some(slot) => SLOTS.lock().entry(slot_name(slot)).or_default().clone(),
+            None => Default::default(),
+        };
+
         Self {
             global_position,
             chunk_position,
             torrent_chunk_position,
-            saved_position: [0.0; 5],
-            saved_map_id: 0u32,
+            saved,
             label_current: String::new(),
             label_stored: String::new(),
             valid: false,
             nudge,
         }
     }
+
+    /// Whether a position was ever saved in this storage's slot.
+    pub(super) fn is_saved(&self) -> bool {
+        self.saved.lock().is_some()
+    }
 }
 
-impl PositionStorage for SavePosition {
+impl<B: MemoryBackend> PositionStorage for SavePosition<B> {
     fn save(&mut self) {
         if let (Some([x, y, z, _, _]), Some([_, _, _, r1, r2]), Some(m)) = (
             self.global_position.read(),
             self.chunk_position.read(),
             self.global_position.read_map_id(),
         ) {
-            self.saved_position = [x, y, z, r1, r2];
-            self.saved_map_id = m;
+            *self.saved.lock() = Some(Saved { position: [x, y, z, r1, r2], map_id: m });
             self.valid = true;
         } else {
             self.valid = false;
@@ -57,12 +91,16 @@ impl PositionStorage for SavePosition {
     }
 
     fn load(&mut self) {
+        // Nothing to go back to yet.
+        let Some(Saved { position: [sx, sy, sz, sr1, sr2], map_id }) = *self.saved.lock() else {
+            return;
+        };
+
         if let (Some([gx, gy, gz, _, _]), Some([cx, cy, cz, _, _])) =
             (self.global_position.read(), self.chunk_position.read())
         {
-            let [sx, sy, sz, sr1, sr2] = self.saved_position;
             self.chunk_position.write([sx - gx + cx, sy - gy + cy, sz - gz + cz, sr1, sr2]);
-            self.chunk_position.write_map_id(self.saved_map_id);
+            self.chunk_position.write_map_id(map_id);
 
             if let Some([tcx, tcy, tcz, _, _]) = self.torrent_chunk_position.read() {
                 self.torrent_chunk_position.write([
@@ -102,7 +140,7 @@ impl PositionStorage for SavePosition {
     fn display_stored(&mut self) -> &str {
         self.label_stored.clear();
 
-        let [x, y, z, a, _] = self.saved_position;
+        let [x, y, z, a, _] = self.saved.lock().unwrap_or_default().position;
 
         write!(self.label_stored, "{:7.1} {:7.1} {:7.1} {:7.1}", x, y, z, a).ok();
 
@@ -114,7 +152,7 @@ impl PositionStorage for SavePosition {
     }
 }
 
-impl NudgePositionStorage for SavePosition {
+impl<B: MemoryBackend> NudgePositionStorage for SavePosition<B> {
     fn nudge_up(&mut self) {
         if let Some(y) = self.chunk_position.y.read() {
             self.chunk_position.y.write(y + self.nudge);
@@ -140,10 +178,94 @@ pub(crate) fn save_position(
     torrent_chunk_position: ErPosition,
     key_load: Option<Key>,
     key_save: Option<Key>,
+    slot: Option<&str>,
 ) -> Box<dyn Widget> {
+    // Unless named, the slot is the load hotkey, e.g. `{ load_position = "h" }`.
+    let slot = slot.map(str::to_string).or_else(|| key_load.map(|key| key.to_string()));
     Box::new(Position::new(
-        SavePosition::new(global_position, chunk_position, torrent_chunk_position, 0.0),
+        SavePosition::new(
+            global_position,
+            chunk_position,
+            torrent_chunk_position,
+            0.0,
+            slot.as_deref(),
+        ),
         key_load,
         key_save,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use libeldenring::memedit::{MemoryImage, PointerChain};
+
+    use super::*;
+
+    const BASE: usize = 0x1000;
+
+    // Five floats and a map id at `addr`.
+    fn position(image: &MemoryImage, addr: usize) -> ErPosition<MemoryImage> {
+        let chain = |offset| PointerChain::with_backend(image.clone(), &[addr + offset]);
+        ErPosition {
+            x: chain(0x0),
+            y: chain(0x4),
+            z: chain(0x8),
+            angle1: chain(0xc),
+            angle2: chain(0x10),
+            map_id: Some(chain(0x14).cast()),
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let image = MemoryImage::new(BASE, 0x60);
+        let [global, chunk, torrent] =
+            [0x0, 0x20, 0x40].map(|offset| position(&image, BASE + offset));
+        global.write([100., 200., 300., 0., 0.]);
+        global.write_map_id(0x3c2c2400);
+        chunk.write([10., 20., 30., 1.5, 0.5]);
+        torrent.write([12., 20., 30., 0., 0.]);
+
+        let mut storage =
+            SavePosition::new(global.clone(), chunk.clone(), torrent.clone(), 2., None);
+
+        // Nothing to load yet.
+        storage.load();
+        assert!(!storage.is_saved());
+        assert_eq!(chunk.read(), Some([10., 20., 30., 1.5, 0.5]));
+
+        storage.save();
+        assert!(storage.is_valid());
+        assert!(storage.is_saved());
+        assert_eq!(storage.display_stored(), "  100.0   200.0   300.0     1.5");
+
+        // The player moves 10 along x, and to another map.
+        global.write([110., 200., 300., 0., 0.]);
+        chunk.write([20., 20., 30., 0., 0.]);
+        chunk.write_map_id(0x3c2c2401);
+        torrent.write([22., 20., 30., 0., 0.]);
+
+        storage.load();
+        assert_eq!(chunk.read(), Some([10., 20., 30., 1.5, 0.5]));
+        assert_eq!(chunk.read_map_id(), Some(0x3c2c2400));
+        assert_eq!(torrent.read(), Some([12., 20., 30., 1.5, 0.5]));
+
+        storage.nudge_up();
+        assert_eq!(chunk.y.read(), Some(22.));
+        assert_eq!(torrent.y.read(), Some(22.));
+    }
+
+    #[test]
+    fn test_unreadable() {
+        let image = MemoryImage::new(BASE, 0x20);
+        let global = position(&image, BASE);
+        let chunk = position(&image, BASE + 0x100);
+
+        let mut storage = SavePosition::new(global.clone(), chunk.clone(), chunk, 0., None);
+        storage.save();
+        assert!(!storage.is_valid());
+        assert!(!storage.is_saved());
+        assert_eq!(storage.display_current(), "    0.0     0.0     0.0     0.0");
+        assert!(!storage.is_valid());
+    }
+}
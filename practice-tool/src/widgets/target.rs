@@ -1,16 +1,18 @@
 use std::f32::consts::PI;
 use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use hudhook::tracing::error;
 use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, ImVec2};
 use imgui::{ProgressBar, StyleColor};
+use libeldenring::backend::InProcess;
+use libeldenring::detour::{alloc_code_cave, asm, Detour};
 use libeldenring::memedit::PointerChain;
 use libeldenring::pointer_chain;
 use libeldenring::prelude::Position as ErPosition;
+use once_cell::sync::OnceCell;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::Widget;
-use windows::Win32::System::Memory::{
-    VirtualAlloc, MEM_COMMIT, MEM_RESERVE, PAGE_EXECUTE_READWRITE,
-};
 
 #[derive(Debug, Default)]
 struct EnemyInfo {
@@ -74,15 +76,19 @@ struct EntityPointerChains {
     position: PointerChain<EntityPosition>,
 }
 
+// Widgets are rebuilt on config reloads and profile switches, while a game
+// thread could still be running in the hook of a dropped one. So all of them
+// share one code cave, allocated on the first `enable` and never freed, like
+// the ones of `Detour`, and the hook stores the entity in a static.
+static CAVE_ADDR: OnceCell<Option<usize>> = OnceCell::new();
+static ENTITY_ADDR: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Debug)]
 pub(crate) struct Target {
     label: String,
-    alloc_addr: PointerChain<[u8; 22]>,
-    detour_addr: PointerChain<[u8; 11]>,
-    detour_orig_data: [u8; 11],
+    detour_addr: usize,
+    detour: Option<Detour>,
     hotkey: Option<Key>,
-    is_enabled: bool,
-    entity_addr: u64,
     player_position: ErPosition,
 
     distance_text: String,
@@ -97,35 +103,16 @@ impl Target {
         player_position: ErPosition,
         hotkey: Option<Key>,
     ) -> Self {
-        let detour_addr = detour_addr.cast();
-        let mut allocate_near = detour_addr.eval().unwrap() as usize;
-
-        let alloc_addr = loop {
-            let c = unsafe {
-                VirtualAlloc(
-                    Some(allocate_near as *mut _),
-                    0x20,
-                    MEM_COMMIT | MEM_RESERVE,
-                    PAGE_EXECUTE_READWRITE,
-                )
-            };
-            if c.is_null() {
-                allocate_near += 65536;
-            } else {
-                break pointer_chain!(c as usize);
-            }
-        };
+        // Unwrap is valid because the address is static.
+        let detour_addr = detour_addr.eval().unwrap() as usize;
 
         Target {
             label: hotkey
                 .map(|k| format!("Target entity info ({k})"))
                 .unwrap_or_else(|| "Target entity info".to_string()),
-            alloc_addr,
             detour_addr,
-            detour_orig_data: Default::default(),
+            detour: None,
             hotkey,
-            is_enabled: false,
-            entity_addr: 0,
             player_position,
 
             distance_text: String::new(),
@@ -133,17 +120,18 @@ impl Target {
     }
 
     fn get_data(&self) -> Option<EnemyInfo> {
-        if !self.is_enabled || self.entity_addr == 0 {
+        let entity_addr = ENTITY_ADDR.load(Ordering::Relaxed) as usize;
+        if !self.is_enabled() || entity_addr == 0 {
             return None;
         }
 
         let epc = EntityPointerChains {
-            hp: pointer_chain!(self.entity_addr as usize + 0x190, 0, 0x138),
-            sp: pointer_chain!(self.entity_addr as usize + 0x190, 0, 0x154),
-            mp: pointer_chain!(self.entity_addr as usize + 0x190, 0, 0x148),
-            res: pointer_chain!(self.entity_addr as usize + 0x190, 0x20, 0x10),
-            poise: pointer_chain!(self.entity_addr as usize + 0x190, 0x40, 0x10),
-            position: pointer_chain!(self.entity_addr as usize + 0x190, 0x68, 0x54),
+            hp: pointer_chain!(entity_addr + 0x190, 0, 0x138),
+            sp: pointer_chain!(entity_addr + 0x190, 0, 0x154),
+            mp: pointer_chain!(entity_addr + 0x190, 0, 0x148),
+            res: pointer_chain!(entity_addr + 0x190, 0x20, 0x10),
+            poise: pointer_chain!(entity_addr + 0x190, 0x40, 0x10),
+            position: pointer_chain!(entity_addr + 0x190, 0x68, 0x54),
         };
 
         let [hp, _, max_hp] = epc.hp.read()?;
@@ -156,68 +144,36 @@ impl Target {
         Some(EnemyInfo { hp, max_hp, mp, max_mp, sp, max_sp, res, poise, position })
     }
 
-    fn enable(&mut self) {
-        // Unwraps are valid because the addresses are static.
-
-        self.detour_orig_data = self.detour_addr.read().unwrap();
-
-        let detour_addr = self.detour_addr.eval().unwrap();
-        let alloc_addr = self.alloc_addr.eval().unwrap();
-
-        let data_ptr = (&self.entity_addr as *const u64) as usize;
-        let going_jmp_to = (alloc_addr as isize - detour_addr as isize - 5) as i32;
-        let returning_jmp_to = (detour_addr as isize - alloc_addr as isize - 11) as i32;
-
-        // jmp going; nop...
-        let mut detour_bytes: [u8; 11] = [0xE9, 0, 0, 0, 0, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90];
-
-        let mut patch_data: [u8; 22] = [
-            0x48, 0xa3, 0, 0, 0, 0, 0, 0, 0, 0, // mov [data_ptr], rax
-            0x48, 0x89, 0x8d, 0, 0, 0, 0, // mov [r13+...], rcx
-            0xe9, 0, 0, 0, 0, // jmp returning
-        ];
-
-        detour_bytes[1..5].copy_from_slice(&u32_to_array(going_jmp_to as _));
-        patch_data[2..10].copy_from_slice(&u64_to_array(data_ptr as _));
-        patch_data[10..17].copy_from_slice(&self.detour_orig_data[4..]);
-        patch_data[18..].copy_from_slice(&u32_to_array(returning_jmp_to as _));
-
-        self.alloc_addr.write(patch_data);
-        self.detour_addr.write(detour_bytes);
-        self.is_enabled = true;
+    fn is_enabled(&self) -> bool {
+        self.detour.is_some()
     }
 
-    fn disable(&mut self) {
-        self.detour_addr.write(self.detour_orig_data);
-        self.is_enabled = false;
-    }
-}
+    fn enable(&mut self) {
+        let Some(cave_addr) = *CAVE_ADDR.get_or_init(|| alloc_code_cave(self.detour_addr, 0x100))
+        else {
+            error!("Couldn't allocate a code cave for the target entity hook");
+            return;
+        };
 
-#[inline]
-fn u32_to_array(val: u32) -> [u8; 4] {
-    let mut buf = [0u8; 4];
+        // The hooked code holds the entity pointer in rax.
+        let data_ptr = ENTITY_ADDR.as_ptr() as usize;
+        let payload = asm::movabs_store_rax(data_ptr);
 
-    for (i, item) in buf.iter_mut().enumerate() {
-        *item = ((val >> (i * 8)) & 0xff) as u8;
+        match Detour::with_backend(InProcess, self.detour_addr, cave_addr, &payload) {
+            Ok(detour) => self.detour = Some(detour),
+            Err(e) => error!("Couldn't hook target entity: {e}"),
+        }
     }
 
-    buf
-}
-
-#[inline]
-fn u64_to_array(val: u64) -> [u8; 8] {
-    let mut buf = [0u8; 8];
-
-    for (i, item) in buf.iter_mut().enumerate() {
-        *item = ((val >> (i * 8)) & 0xff) as u8;
+    fn disable(&mut self) {
+        self.detour = None;
+        ENTITY_ADDR.store(0, Ordering::Relaxed);
     }
-
-    buf
 }
 
 impl Widget for Target {
     fn render(&mut self, ui: &imgui::Ui) {
-        let mut state = self.is_enabled;
+        let mut state = self.is_enabled();
 
         if ui.checkbox(&self.label, &mut state) {
             if state {
@@ -229,14 +185,14 @@ impl Widget for Target {
     }
 
     fn render_closed(&mut self, ui: &imgui::Ui) {
-        if !self.is_enabled {
+        if !self.is_enabled() {
             return;
         }
 
         let Some(EnemyInfo { hp, max_hp, mp, max_mp, sp, max_sp, res, poise, position }) =
             self.get_data()
         else {
-            if self.is_enabled {
+            if self.is_enabled() {
                 ui.text("No enemy locked on")
             };
             return;
@@ -383,7 +339,7 @@ impl Widget for Target {
         }
 
         if self.hotkey.map(|k| k.is_pressed(ui)).unwrap_or(false) {
-            if self.is_enabled {
+            if self.is_enabled() {
                 self.disable();
             } else {
                 self.enable();
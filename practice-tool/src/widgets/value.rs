@@ -0,0 +1,89 @@
+use imgui::Ui;
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_WIDTH};
+
+pub(crate) trait Editable: Copy + Default {
+    fn input(ui: &Ui, label: &str, value: &mut Self) -> bool;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl Editable for u8 {
+    fn input(ui: &Ui, label: &str, value: &mut Self) -> bool {
+        ui.input_scalar(label, value).enter_returns_true(true).build()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as u8
+    }
+}
+
+impl Editable for i32 {
+    fn input(ui: &Ui, label: &str, value: &mut Self) -> bool {
+        ui.input_int(label, value).enter_returns_true(true).build()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as i32
+    }
+}
+
+impl Editable for f32 {
+    fn input(ui: &Ui, label: &str, value: &mut Self) -> bool {
+        ui.input_float(label, value).enter_returns_true(true).build()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+// Shows the value in memory and writes it back when edited. The hotkey, if
+// any, writes the preset value.
+struct Value<T> {
+    label: String,
+    ptr: PointerChain<T>,
+    hotkey: Option<Key>,
+    preset: Option<T>,
+}
+
+impl<T: Editable> Widget for Value<T> {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let _tok = ui.push_item_width(BUTTON_WIDTH * scaling_factor(ui) * 0.5);
+
+        match self.ptr.read() {
+            Some(mut value) => {
+                if T::input(ui, &self.label, &mut value) {
+                    self.ptr.write(value);
+                }
+            },
+            None => {
+                let _tok = ui.begin_disabled(true);
+                T::input(ui, &self.label, &mut T::default());
+            },
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if let (Some(key), Some(preset)) = (self.hotkey, self.preset) {
+            if key.is_pressed(ui) {
+                self.ptr.write(preset);
+            }
+        }
+    }
+}
+
+pub(crate) fn value_edit<T: Editable + 'static>(
+    label: &str,
+    ptr: PointerChain<T>,
+    hotkey: Option<Key>,
+    preset: Option<f64>,
+) -> Box<dyn Widget> {
+    let preset = preset.map(T::from_f64);
+    let label = match (hotkey, preset) {
+        (Some(key), Some(_)) => format!("{label} ({key})"),
+        _ => label.to_string(),
+    };
+
+    Box::new(Value { label, ptr, hotkey, preset })
+}
@@ -1,6 +1,6 @@
-import pandas as pd
 import re
 import sys
+import xml.etree.ElementTree as ET
 from glob import glob
 from pathlib import Path
 from itertools import zip_longest
@@ -20,6 +20,14 @@ pub static PARAM_VTABLE: Lazy<HashMap<String, BoxedVisitorLambda>> = Lazy::new(|
     ].into_iter().collect()
 }});'''
 
+PARAM_FIELDS_TEMPLATE = '''
+
+pub static PARAM_FIELDS: Lazy<HashMap<String, &'static [FieldMeta]>> = Lazy::new(|| {{
+    [
+        {fields_entries}
+    ].into_iter().collect()
+}});'''
+
 STRUCT_TEMPLATE = '''
     #[derive(ParamStruct, Debug)]
     #[repr(C)]
@@ -32,6 +40,17 @@ FIELD_TEMPLATE = '''
         pub {field_name}: {field_type},
 '''.strip()
 
+TYPE_SIZES = {
+    'i8': 1,
+    'u8': 1,
+    'i16': 2,
+    'u16': 2,
+    'i32': 4,
+    'u32': 4,
+    'f32': 4,
+    'u64': 8,
+}
+
 def to_snake_case(s):
     return SNAKECASE_CLEAN_RE.sub('_', SNAKECASE_RE.sub(r'_\1', s).lower())
 
@@ -44,6 +63,44 @@ def to_slug(s):
     return SLUG_RE.sub('', s).lower()
 
 
+def to_rust_str(s):
+    escaped = s.replace('\\', '\\\\').replace('"', '\\"')
+    escaped = escaped.replace('\r', '\\r').replace('\n', '\\n').replace('\t', '\\t')
+    return f'"{escaped}"'
+
+
+def format_paramdex(flag=None, offset=None, doc=None):
+    params = []
+    if flag is not None:
+        params.append(f'flag = {to_rust_str(flag)}')
+    if offset is not None:
+        params.append(f'offset = {offset:#x}')
+    for key, value in (doc or {}).items():
+        if value:
+            params.append(f'{key} = {to_rust_str(value)}')
+
+    if not params:
+        return None
+    return '#[paramdex({})]'.format(', '.join(params))
+
+
+def read_refs(meta_path):
+    # Refs look like `EquipParamWeapon(refCategory=0),EquipParamGoods`; only
+    # the param names are kept.
+    if not meta_path.exists():
+        return {}
+
+    fields = ET.parse(meta_path).getroot().find('Field')
+    if fields is None:
+        return {}
+
+    return dict(
+        (f.tag, ','.join(re.sub(r'\([^)]*\)', '', f.get('Refs')).split(',')))
+        for f in fields
+        if f.get('Refs')
+    )
+
+
 def build_param_layouts(paramdex_path, xtask_path):
     paramdex_path = Path(paramdex_path)
     xtask_path = Path(xtask_path)
@@ -67,20 +124,29 @@ def build_param_layouts(paramdex_path, xtask_path):
     assert(xml_files.keys() == param_names.keys())
 
     return [
-        ParamLayout(name=param_names[i], layout=xml_files[i])
+        ParamLayout(
+            name=param_names[i],
+            layout=xml_files[i],
+            refs=read_refs(paramdex_path / 'ER/Meta' / xml_files[i].name),
+        )
         for i in sorted(param_names.keys())
     ]
 
 
 class ParamLayout:
-    def __init__(self, name, layout):
+    def __init__(self, name, layout, refs):
         self.name = name
-        # self.name = to_camel_case(pd.read_xml(layout)['ParamType'][0])
         self.name_snake_case = to_snake_case(name)
         self.fields = ParamLayout.dedup_fields(ParamLayout.group_bitfields([
-            Field(i) for i in pd.read_xml(layout, xpath='./Fields/*')['Def']
+            Field(i.get('Def'), i, refs) for i in ET.parse(layout).getroot().find('Fields')
         ]))
 
+        # Paramdex layouts are packed; the derive checks that `repr(C)` agrees.
+        offset = 0
+        for f in self.fields:
+            f.offset = offset
+            offset += f.byte_size
+
     def get_struct(self):
         fields = '\n        '.join(
             field.format()
@@ -132,16 +198,22 @@ class Bitfield:
         self.name = f'bitfield{idx}'
         self.type = dtype
         self.fields = list(enumerate(ParamLayout.dedup_fields(fields)))
+        self.byte_size = TYPE_SIZES[dtype]
+        self.offset = None
 
     def format(self):
         field_tpl = FIELD_TEMPLATE.format(
             field_name=ParamLayout.fix_name(to_snake_case(self.name)),
             field_type=self.type
         )
-        return '\n        '.join(
-            '''#[bitflag({flag_name}, {idx})]'''.format(flag_name=ParamLayout.fix_name(flag.name), idx=idx)
-            for idx, flag in self.fields
-        ) + '\n        ' + field_tpl
+        lines = []
+        for idx, flag in self.fields:
+            flag_name = ParamLayout.fix_name(flag.name)
+            lines.append('''#[bitflag({flag_name}, {idx})]'''.format(flag_name=flag_name, idx=idx))
+            if paramdex := format_paramdex(flag=flag_name, doc=flag.doc):
+                lines.append(paramdex)
+        lines.append(format_paramdex(offset=self.offset))
+        return '\n        '.join(lines) + '\n        ' + field_tpl
 
     def rename(self, idx):
         self.name = self.name + f'_{idx}'
@@ -165,13 +237,14 @@ class Field:
         'dummy8': 'u8',
     }
 
-    def __init__(self, definition):
+    def __init__(self, definition, element, refs):
         if matches := Field.def_array_re.match(definition):
             self.kind = 'array'
             self.name = matches.group(2)
             array_count = int(matches.group(3))
             dtype = Field.type_map.get(matches.group(1))
             self.type = f'[{dtype}; {array_count}]'
+            self.byte_size = TYPE_SIZES[dtype] * array_count
         elif matches := Field.def_bitfield_re.match(definition):
             self.kind = 'bitfield'
             self.name = matches.group(2)
@@ -190,11 +263,20 @@ class Field:
             self.kind = 'normal'
             self.name = matches.group(2)
             self.type = Field.type_map.get(matches.group(1))
+            self.byte_size = TYPE_SIZES[self.type]
         else:
             raise ValueError(f'Couldn\'t parse: {definition}')
 
+        self.offset = None
+        self.doc = {
+            'display_name': element.findtext('DisplayName'),
+            'description': element.findtext('Description'),
+            'enum_type': element.findtext('Enum'),
+            'refs': refs.get(self.name),
+        }
+
     def format(self):
-        return FIELD_TEMPLATE.format(
+        return format_paramdex(offset=self.offset, doc=self.doc) + '\n        ' + FIELD_TEMPLATE.format(
             field_name=ParamLayout.fix_name(to_snake_case(self.name)),
             field_type=self.type
         )
@@ -232,5 +314,13 @@ unsafe fn get_lambda<T: ParamStruct>() -> BoxedVisitorLambda {
         )
     ), end='')
 
+    print(PARAM_FIELDS_TEMPLATE.format(
+        fields_entries='\n        '.join(
+            '''("{param_name}".to_string(), {param_name}::FIELDS),'''
+            .format(param_name=l.name)
+            for l in layouts
+        )
+    ), end='')
+
     for l in layouts:
         print(dedent(l.get_struct()), end='')
@@ -1,42 +1,534 @@
-use std::env;
-use std::fs::File;
-use std::io::Write;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
 use std::process::Command;
 
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
+use once_cell::sync::Lazy;
 use practice_tool_tasks::params::{checkout_paramdex, codegen_param_names};
+use regex::Regex;
 
 use crate::{project_root, Result};
 
 pub(crate) fn codegen() -> Result<()> {
     checkout_paramdex()?;
-    run_python_script()?;
-    codegen_param_names(
-        "target/Paramdex/ER/Names",
-        "lib/libeldenring/src/codegen/param_names.json",
+
+    let param_data = generate(&project_root().join("target/Paramdex"))?;
+    let param_data_path = project_root().join("lib/libeldenring/src/codegen/param_data.rs");
+    fs::write(&param_data_path, param_data)?;
+    rustfmt(&param_data_path)?;
+
+    codegen_param_names("target/Paramdex/ER/Names", "target/param_names.json")?;
+    let param_names = fs::read_to_string(project_root().join("target/param_names.json"))?;
+    fs::write(
+        project_root().join("lib/libeldenring/src/codegen/param_names.bin"),
+        encode_param_names(&param_names)?,
     )?;
 
     Ok(())
 }
 
-fn run_python_script() -> Result<()> {
-    let python = env::var("PYTHON").unwrap_or_else(|_| "python".to_string());
-    let cmd = Command::new(python)
-        .current_dir(project_root().join("target"))
-        .args(&[
-            project_root().join("xtask/src/codegen/params.py"),
-            project_root().join("target/Paramdex"),
-            project_root().join("xtask"),
-        ])
-        .output()
-        .context("python")?;
+const HEADER: &str = r#"// **********************************
+// *** AUTOGENERATED, DO NOT EDIT ***
+// **********************************
+use std::collections::HashMap;
+use std::ffi::c_void;
+
+use once_cell::sync::Lazy;
+use macro_param::ParamStruct;
+use crate::prelude::*;
+
+unsafe fn get_lambda<T: ParamStruct>() -> BoxedVisitorLambda {
+    Box::new(|ptr, v| {
+        if let Some(r) = (ptr as *mut T).as_mut() {
+            r.visit(&mut *v);
+        }
+    })
+}
+
+type BoxedVisitorLambda = Box<dyn Fn(*const c_void, &mut dyn ParamVisitor) + Send + Sync>;
+"#;
+
+static SNAKECASE_CLEAN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"_+").unwrap());
+static SLUG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^a-zA-Z]+").unwrap());
+static REFS_CONDITION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\([^)]*\)").unwrap());
+static DEF_ARRAY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\w+)\s+(\w+)\[(\d+)\]").unwrap());
+static DEF_BITFIELD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\w+)\s+(\w+):(\d+)").unwrap());
+static DEF_BASIC_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\w+)\s+(\w+)").unwrap());
+
+/// Generates `param_data.rs` from the ER paramdefs of a Paramdex checkout.
+/// The output is not formatted, see [`rustfmt`].
+fn generate(paramdex: &Path) -> Result<String> {
+    let layouts = read_layouts(paramdex)?;
+
+    let mut out = HEADER.to_string();
+
+    let vtable_entries = layouts
+        .iter()
+        .map(|l| format!("(\"{0}\".to_string(), unsafe {{ get_lambda::<{0}>() }}),", l.name))
+        .collect::<Vec<_>>()
+        .join("\n        ");
+    write!(
+        out,
+        "\npub static PARAM_VTABLE: Lazy<HashMap<String, BoxedVisitorLambda>> = Lazy::new(|| \
+         {{\n    [\n        {vtable_entries}\n    ].into_iter().collect()\n}});"
+    )?;
+
+    let fields_entries = layouts
+        .iter()
+        .map(|l| format!("(\"{0}\".to_string(), {0}::FIELDS),", l.name))
+        .collect::<Vec<_>>()
+        .join("\n        ");
+    write!(
+        out,
+        "\n\npub static PARAM_FIELDS: Lazy<HashMap<String, &'static [FieldMeta]>> = Lazy::new(|| \
+         {{\n    [\n        {fields_entries}\n    ].into_iter().collect()\n}});"
+    )?;
 
-    if !cmd.status.success() {
-        bail!("python codegen failed");
+    for layout in &layouts {
+        out.push_str(&layout.format_struct());
     }
 
-    File::create(project_root().join("lib/libeldenring/src/codegen/param_data.rs"))?
-        .write_all(&cmd.stdout)?;
+    Ok(out)
+}
+
+/// Formats a generated file with the workspace's `rustfmt.toml`, which needs
+/// the nightly `rustfmt` like `cargo fmt` in CI.
+fn rustfmt(path: &Path) -> Result<()> {
+    let status = Command::new("rustfmt")
+        .args(["+nightly", "--edition", "2021"])
+        .arg(path)
+        .current_dir(project_root())
+        .status()
+        .context("rustfmt")?;
+
+    if !status.success() {
+        bail!("rustfmt failed on {}", path.display());
+    }
 
     Ok(())
 }
+
+/// Reads every paramdef in `ER/Defs`, sorted by their case-insensitive name.
+fn read_layouts(paramdex: &Path) -> Result<Vec<ParamLayout>> {
+    let mut defs = BTreeMap::new();
+    for entry in fs::read_dir(paramdex.join("ER/Defs")).context("ER/Defs")? {
+        let path = entry?.path();
+        if path.extension() != Some("xml".as_ref()) {
+            continue;
+        }
+
+        let stem = path.file_stem().and_then(|s| s.to_str()).context("Invalid paramdef name")?;
+        let name = stem.replace("_ST", "");
+        defs.insert(to_slug(&name), (name, path));
+    }
+
+    // Currently broken
+    defs.remove("defaultkeyassign");
+
+    defs.into_values()
+        .map(|(name, path)| {
+            let refs = read_refs(&paramdex.join("ER/Meta").join(path.file_name().unwrap()))?;
+            let xml = fs::read_to_string(&path)?;
+            ParamLayout::parse(name, &xml, &refs)
+                .with_context(|| format!("Couldn't parse {}", path.display()))
+        })
+        .collect()
+}
+
+/// Reads the `Refs` of a paramdef's fields from its Paramdex meta file.
+fn read_refs(meta_path: &Path) -> Result<HashMap<String, String>> {
+    // Refs look like `EquipParamWeapon(refCategory=0),EquipParamGoods`; only
+    // the param names are kept.
+    if !meta_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let xml = fs::read_to_string(meta_path)?;
+    let doc = roxmltree::Document::parse(&xml)
+        .with_context(|| format!("Couldn't parse {}", meta_path.display()))?;
+
+    let Some(fields) = child(doc.root_element(), "Field") else {
+        return Ok(HashMap::new());
+    };
+
+    Ok(fields
+        .children()
+        .filter(|f| f.is_element())
+        .filter_map(|f| {
+            let refs = f.attribute("Refs").filter(|r| !r.is_empty())?;
+            Some((f.tag_name().name().to_string(), REFS_CONDITION_RE.replace_all(refs, "").into()))
+        })
+        .collect())
+}
+
+fn child<'a, 'i>(node: roxmltree::Node<'a, 'i>, tag: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn child_text(node: roxmltree::Node, tag: &str) -> Option<String> {
+    child(node, tag).and_then(|n| n.text()).filter(|t| !t.is_empty()).map(String::from)
+}
+
+struct ParamLayout {
+    name: String,
+    fields: Vec<Entry>,
+}
+
+impl ParamLayout {
+    fn parse(name: String, xml: &str, refs: &HashMap<String, String>) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml)?;
+        let fields = child(doc.root_element(), "Fields").context("No Fields element")?;
+
+        let fields = fields
+            .children()
+            .filter(|f| f.is_element())
+            .map(|f| Field::parse(f.attribute("Def").context("Field without Def")?, f, refs))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut fields = group_bitfields(fields);
+        dedup_fields(&mut fields);
+
+        // Fields are laid out like `repr(C)` does, which the derive checks.
+        let mut offset = 0usize;
+        for f in &mut fields {
+            offset = offset.next_multiple_of(f.align);
+            f.offset = offset;
+            offset += f.byte_size;
+        }
+
+        Ok(ParamLayout { name, fields })
+    }
+
+    fn format_struct(&self) -> String {
+        let fields = self.fields.iter().map(Entry::format).collect::<Vec<_>>().join("\n    ");
+        format!(
+            "\n#[derive(ParamStruct, Debug)]\n#[repr(C)]\npub struct {} {{\n    {fields}\n}}\n",
+            self.name
+        )
+        // Blank lines are left without indentation.
+        .replace("{\n    \n}", "{\n\n}")
+    }
+}
+
+/// A field of a param struct, or a group of bit flags sharing an integer.
+struct Entry {
+    name: String,
+    ty: String,
+    byte_size: usize,
+    align: usize,
+    offset: usize,
+    doc: Vec<(&'static str, String)>,
+    flags: Vec<Field>,
+}
+
+impl Entry {
+    fn format(&self) -> String {
+        let mut lines = Vec::new();
+        if self.flags.is_empty() {
+            lines.push(format_paramdex(None, Some(self.offset), &self.doc));
+        } else {
+            for (idx, flag) in self.flags.iter().enumerate() {
+                let flag_name = fix_name(&flag.name);
+                lines.push(format!("#[bitflag({flag_name}, {idx})]"));
+                lines.push(format_paramdex(Some(&flag_name), None, &flag.doc));
+            }
+            lines.push(format_paramdex(None, Some(self.offset), &[]));
+        }
+        lines.push(format!("pub {}: {},", fix_name(&to_snake_case(&self.name)), self.ty));
+        lines.join("\n    ")
+    }
+}
+
+enum FieldKind {
+    Normal,
+    Bitfield { bits: usize },
+}
+
+struct Field {
+    kind: FieldKind,
+    name: String,
+    ty: String,
+    byte_size: usize,
+    align: usize,
+    doc: Vec<(&'static str, String)>,
+}
+
+impl Field {
+    fn parse(def: &str, node: roxmltree::Node, refs: &HashMap<String, String>) -> Result<Self> {
+        let rust_type = |ty: &str| {
+            let rust_type = match ty {
+                "s8" => "i8",
+                "u8" => "u8",
+                "s16" => "i16",
+                "u16" => "u16",
+                "s32" => "i32",
+                "u32" => "u32",
+                "f32" => "f32",
+                "fixstr" => "u8",
+                "fixstrW" => "u16",
+                "dummy8" => "u8",
+                _ => return Err(anyhow!("Unknown type {ty} in {def}")),
+            };
+            Ok((rust_type, type_size(rust_type)))
+        };
+
+        let (kind, name, ty, align, byte_size) = if let Some(m) = DEF_ARRAY_RE.captures(def) {
+            let (ty, size) = rust_type(&m[1])?;
+            let count: usize = m[3].parse()?;
+            (
+                FieldKind::Normal,
+                m.get(2).unwrap().as_str(),
+                format!("[{ty}; {count}]"),
+                size,
+                size * count,
+            )
+        } else if let Some(m) = DEF_BITFIELD_RE.captures(def) {
+            let (ty, size) = rust_type(&m[1])?;
+            let bits = match ty {
+                "u8" | "u16" | "u32" => size * 8,
+                _ => bail!("Unsupported bitfield type in {def}"),
+            };
+            (FieldKind::Bitfield { bits }, m.get(2).unwrap().as_str(), ty.to_string(), size, size)
+        } else if let Some(m) = DEF_BASIC_RE.captures(def) {
+            let (ty, size) = rust_type(&m[1])?;
+            (FieldKind::Normal, m.get(2).unwrap().as_str(), ty.to_string(), size, size)
+        } else {
+            bail!("Couldn't parse: {def}");
+        };
+
+        let doc = [
+            ("display_name", child_text(node, "DisplayName")),
+            ("description", child_text(node, "Description")),
+            ("enum_type", child_text(node, "Enum")),
+            ("refs", refs.get(name).filter(|r| !r.is_empty()).cloned()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect();
+
+        Ok(Field { kind, name: name.to_string(), ty, byte_size, align, doc })
+    }
+}
+
+/// Groups consecutive bit flags into integers of their type. Trailing flags
+/// which don't fill a whole integer are dropped.
+fn group_bitfields(fields: Vec<Field>) -> Vec<Entry> {
+    let mut grouped = Vec::new();
+    let mut flags: Vec<Field> = Vec::new();
+    let mut bitfield_idx = 0;
+    for f in fields {
+        match f.kind {
+            FieldKind::Normal => grouped.push(Entry {
+                name: f.name,
+                ty: f.ty,
+                byte_size: f.byte_size,
+                align: f.align,
+                offset: 0,
+                doc: f.doc,
+                flags: Vec::new(),
+            }),
+            FieldKind::Bitfield { bits } => {
+                flags.push(f);
+                if flags.len() == bits {
+                    let last = flags.last().unwrap();
+                    let (ty, byte_size, align) = (last.ty.clone(), last.byte_size, last.align);
+                    let mut flags = std::mem::take(&mut flags);
+                    dedup_names(flags.iter_mut().map(|f| &mut f.name));
+                    grouped.push(Entry {
+                        name: format!("bitfield{bitfield_idx}"),
+                        ty,
+                        byte_size,
+                        align,
+                        offset: 0,
+                        doc: Vec::new(),
+                        flags,
+                    });
+                    bitfield_idx += 1;
+                }
+            },
+        }
+    }
+    grouped
+}
+
+fn dedup_fields(fields: &mut [Entry]) {
+    dedup_names(fields.iter_mut().map(|f| &mut f.name));
+}
+
+/// Appends a counter to names whose snake case was already seen.
+fn dedup_names<'a>(names: impl Iterator<Item = &'a mut String>) {
+    let mut seen = HashSet::new();
+    let mut idx = 0;
+    for name in names {
+        let snake_case = to_snake_case(name);
+        if seen.contains(&snake_case) {
+            write!(name, "_{idx}").unwrap();
+            idx += 1;
+        }
+        seen.insert(snake_case);
+    }
+}
+
+fn format_paramdex(
+    flag: Option<&str>,
+    offset: Option<usize>,
+    doc: &[(&'static str, String)],
+) -> String {
+    let params = flag
+        .map(|flag| format!("flag = {}", to_rust_str(flag)))
+        .into_iter()
+        .chain(offset.map(|offset| format!("offset = {offset:#x}")))
+        .chain(doc.iter().map(|(key, value)| format!("{key} = {}", to_rust_str(value))))
+        .collect::<Vec<_>>();
+
+    format!("#[paramdex({})]", params.join(", "))
+}
+
+fn type_size(ty: &str) -> usize {
+    match ty {
+        "i8" | "u8" => 1,
+        "i16" | "u16" => 2,
+        "i32" | "u32" | "f32" => 4,
+        _ => unreachable!("{ty}"),
+    }
+}
+
+fn fix_name(name: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field{name}")
+    } else if name == "type" {
+        "ty".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Inserts an underscore before each run of capital letters except at the
+/// start, so `SFXVariationID` becomes `s_fxvariation_id`. This is not
+/// `heck`'s snake case, which would rename every generated field.
+fn to_snake_case(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 4);
+    let mut prev_upper = false;
+    for (i, c) in s.chars().enumerate() {
+        let upper = c.is_ascii_uppercase();
+        if upper && (i == 1 || (i > 0 && !prev_upper)) {
+            out.push('_');
+        }
+        out.push(c);
+        prev_upper = upper;
+    }
+    SNAKECASE_CLEAN_RE.replace_all(&out.to_lowercase(), "_").into_owned()
+}
+
+fn to_slug(s: &str) -> String {
+    SLUG_RE.replace_all(s, "").to_lowercase()
+}
+
+fn to_rust_str(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+/// Packs the row names collected from Paramdex, a JSON object of params to
+/// ids to names, in the format read by `libeldenring`'s `ParamNames`.
+fn encode_param_names(json: &str) -> Result<Vec<u8>> {
+    let params: BTreeMap<String, BTreeMap<u32, String>> = serde_json::from_str(json)?;
+
+    let header_len = 4 + params.keys().map(|name| 1 + name.len() + 8).sum::<usize>();
+    let mut header = (params.len() as u32).to_le_bytes().to_vec();
+    let mut tables = Vec::new();
+
+    for (param, rows) in &params {
+        let name_len = u8::try_from(param.len()).context(format!("Param name {param}"))?;
+        let mut table = (rows.len() as u32).to_le_bytes().to_vec();
+        for id in rows.keys() {
+            table.extend(id.to_le_bytes());
+        }
+        for (id, name) in rows {
+            let len = u16::try_from(name.len()).context(format!("Name of {param} {id}"))?;
+            table.extend(len.to_le_bytes());
+        }
+        for name in rows.values() {
+            table.extend(name.as_bytes());
+        }
+
+        header.push(name_len);
+        header.extend(param.as_bytes());
+        header.extend(((header_len + tables.len()) as u32).to_le_bytes());
+        header.extend((table.len() as u32).to_le_bytes());
+        tables.extend(table);
+    }
+
+    header.extend(tables);
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The golden file is the output of `fixtures/params.py`, the generator
+    // this one replaced, for the same fixtures. From `xtask/src/codegen`:
+    //
+    //     cd fixtures && python3 params.py Paramdex . > param_data.rs.golden
+    //
+    // None of the fixtures need padding, which `params.py` didn't add.
+    #[test]
+    fn test_golden() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/codegen/fixtures");
+        let generated = generate(&fixtures.join("Paramdex")).unwrap();
+        let golden = fs::read_to_string(fixtures.join("param_data.rs.golden")).unwrap();
+        assert_eq!(generated, golden);
+    }
+
+    #[test]
+    fn test_encode_param_names() {
+        let encoded = encode_param_names(r#"{"Ab": {"20": "x", "10": "yz"}, "C": {}}"#).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(encoded, [
+            2, 0, 0, 0,
+            2, b'A', b'b', 25, 0, 0, 0, 19, 0, 0, 0,
+            1, b'C', 44, 0, 0, 0, 4, 0, 0, 0,
+            2, 0, 0, 0, 10, 0, 0, 0, 20, 0, 0, 0, 2, 0, 1, 0, b'y', b'z', b'x',
+            0, 0, 0, 0,
+        ]);
+    }
+
+    #[test]
+    fn test_alignment() {
+        let xml = r#"<PARAMDEF><Fields>
+            <Field Def="u8 a" />
+            <Field Def="s32 b" />
+            <Field Def="s8 c" />
+            <Field Def="u16 d[2]" />
+            <Field Def="u8 e" />
+            <Field Def="u16 f0:1" /><Field Def="u16 f1:1" /><Field Def="u16 f2:1" />
+            <Field Def="u16 f3:1" /><Field Def="u16 f4:1" /><Field Def="u16 f5:1" />
+            <Field Def="u16 f6:1" /><Field Def="u16 f7:1" /><Field Def="u16 f8:1" />
+            <Field Def="u16 f9:1" /><Field Def="u16 f10:1" /><Field Def="u16 f11:1" />
+            <Field Def="u16 f12:1" /><Field Def="u16 f13:1" /><Field Def="u16 f14:1" />
+            <Field Def="u16 f15:1" />
+            <Field Def="fixstr g[3]" />
+            <Field Def="f32 h" />
+        </Fields></PARAMDEF>"#;
+
+        let layout = ParamLayout::parse("Test".to_string(), xml, &HashMap::new()).unwrap();
+        let offsets = layout.fields.iter().map(|f| f.offset).collect::<Vec<_>>();
+        assert_eq!(offsets, [0x0, 0x4, 0x8, 0xa, 0xe, 0x10, 0x12, 0x18]);
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(to_snake_case("refId_default"), "ref_id_default");
+        assert_eq!(to_snake_case("SFXVariationID"), "s_fxvariation_id");
+        assert_eq!(to_snake_case("isEquip"), "is_equip");
+        assert_eq!(to_snake_case("2ndIconId"), "2nd_icon_id");
+    }
+}